@@ -0,0 +1,70 @@
+//! Route lookup against thousands of registered endpoints.
+//!
+//! `cargo bench --bench router` compares the compiled router with the previous approach of
+//! compiling a regex per template on every request.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use regex::Regex;
+use rust_mock::router::Router;
+
+/// `count` endpoints spread over literal, templated and nested paths.
+fn templates(count: usize) -> Vec<(String, String)> {
+    (0..count)
+        .map(|i| {
+            let path = match i % 4 {
+                0 => format!("/api/v1/resource{}", i),
+                1 => format!("/api/v1/resource{}/{{id}}", i),
+                2 => format!("/api/v1/resource{}/{{id}}/items/{{item}}", i),
+                _ => format!("/api/v1/resource{}/{{id}}/export.{{format}}", i),
+            };
+            ("GET".to_string(), path)
+        })
+        .collect()
+}
+
+fn regex_scan<'a>(routes: &'a [(String, String)], method: &str, path: &str) -> Option<&'a str> {
+    routes.iter()
+        .find(|(m, template)| {
+            let pattern = template.replace('{', "(?P<").replace('}', ">[^/]+)");
+            m == method && Regex::new(&format!("^{}$", pattern)).is_ok_and(|re| re.is_match(path))
+        })
+        .map(|(_, template)| template.as_str())
+}
+
+fn bench_lookup(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lookup");
+    for count in [100, 1_000, 5_000] {
+        let routes = templates(count);
+        let router = Router::build(routes.iter().map(|(m, p)| (m.as_str(), p.as_str())));
+        let last = count - 2;
+        let requests = [
+            ("literal", format!("/api/v1/resource{}", count - 4)),
+            ("param", format!("/api/v1/resource{}/42/items/7", last)),
+            ("miss", "/api/v2/unknown/path".to_string()),
+        ];
+
+        for (name, path) in &requests {
+            assert!(router.find("GET", path).is_some() == (*name != "miss"), "{}", path);
+            group.bench_with_input(BenchmarkId::new(format!("router/{}", name), count), path, |b, path| {
+                b.iter(|| router.find("GET", path))
+            });
+        }
+        if count <= 1_000 {
+            let (_, path) = &requests[1];
+            group.bench_with_input(BenchmarkId::new("regex_scan/param", count), path, |b, path| {
+                b.iter(|| regex_scan(&routes, "GET", path))
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_build(c: &mut Criterion) {
+    let routes = templates(5_000);
+    c.bench_function("build/5000", |b| {
+        b.iter(|| Router::build(routes.iter().map(|(m, p)| (m.as_str(), p.as_str()))))
+    });
+}
+
+criterion_group!(benches, bench_lookup, bench_build);
+criterion_main!(benches);
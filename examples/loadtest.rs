@@ -0,0 +1,239 @@
+//! Load-test harness: registers endpoints on a mock server and drives concurrent traffic at it.
+//!
+//! ```bash
+//! cargo run --release --example loadtest -- --endpoints 1000 --concurrency 64 --duration 10
+//! # against a server that is already running
+//! cargo run --release --example loadtest -- --url http://localhost:8090
+//! # side by side with another build, e.g. an older release, while the dashboard polls the log
+//! cargo run --release --example loadtest -- --url http://localhost:8090 --baseline http://localhost:8091 --log-readers 4
+//! ```
+
+use clap::Parser;
+use serde_json::json;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use rust_mock::{start_server, ServerConfig};
+
+#[derive(Parser)]
+#[command(about = "Drive concurrent traffic at a RustMock server")]
+struct Args {
+    /// Target an already running server instead of starting one in-process
+    #[arg(long)]
+    url: Option<String>,
+
+    /// Run the same load against this server too and compare the two
+    #[arg(long)]
+    baseline: Option<String>,
+
+    /// Port for the in-process server
+    #[arg(long, default_value = "18190")]
+    port: u16,
+
+    /// Endpoints to register, half literal and half templated
+    #[arg(long, default_value = "1000")]
+    endpoints: usize,
+
+    /// Concurrent client connections
+    #[arg(long, default_value = "64")]
+    concurrency: usize,
+
+    /// Clients fetching /__mock/logs in a loop alongside the traffic
+    #[arg(long, default_value = "0")]
+    log_readers: usize,
+
+    /// Seconds of traffic per round
+    #[arg(long, default_value = "10")]
+    duration: u64,
+
+    /// Rounds to run, alternating with the baseline so both see the same conditions
+    #[arg(long, default_value = "3")]
+    rounds: usize,
+}
+
+#[derive(Default)]
+struct Report {
+    requests: usize,
+    errors: usize,
+    log_reads: usize,
+    elapsed: Duration,
+    latencies: Vec<Duration>,
+}
+
+impl Report {
+    fn merge(&mut self, other: Report) {
+        self.requests += other.requests;
+        self.errors += other.errors;
+        self.log_reads += other.log_reads;
+        self.elapsed += other.elapsed;
+        self.latencies.extend(other.latencies);
+        self.latencies.sort();
+    }
+
+    fn throughput(&self) -> f64 {
+        self.requests as f64 / self.elapsed.as_secs_f64()
+    }
+
+    fn percentile(&self, p: f64) -> Duration {
+        let sorted = &self.latencies;
+        sorted.get(((sorted.len() as f64 - 1.0) * p).round() as usize).copied().unwrap_or_default()
+    }
+
+    fn print(&self, base: &str) {
+        println!("{}", base);
+        println!("  Requests:    {} ({} errors) in {:.1}s", self.requests, self.errors, self.elapsed.as_secs_f64());
+        println!("  Throughput:  {:.0} req/s", self.throughput());
+        println!(
+            "  Latency:     p50 {:?}  p90 {:?}  p99 {:?}  max {:?}",
+            self.percentile(0.50),
+            self.percentile(0.90),
+            self.percentile(0.99),
+            self.latencies.last().copied().unwrap_or_default(),
+        );
+        if self.log_reads > 0 {
+            println!("  Log reads:   {}", self.log_reads);
+        }
+    }
+}
+
+fn endpoint_path(i: usize) -> String {
+    if i.is_multiple_of(2) {
+        format!("/load/items{}", i)
+    } else {
+        format!("/load/items{}/{{id}}", i)
+    }
+}
+
+fn request_path(i: usize) -> String {
+    endpoint_path(i).replace("{id}", "42")
+}
+
+async fn register(client: &reqwest::Client, base: &str, args: &Args) {
+    for _ in 0..50 {
+        if client.get(format!("{}/__mock/config", base)).send().await.is_ok() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    for i in 0..args.endpoints {
+        client
+            .post(format!("{}/__mock/endpoints", base))
+            .json(&json!({"method": "GET", "path": endpoint_path(i), "response": {"item": i}}))
+            .send()
+            .await
+            .expect("Failed to register endpoint");
+    }
+    println!("Registered {} endpoints on {}", args.endpoints, base);
+}
+
+async fn run(client: &reqwest::Client, base: &str, args: &Args) -> Report {
+    client.delete(format!("{}/__mock/logs", base)).send().await.expect("Failed to clear logs");
+    let stop = Arc::new(AtomicBool::new(false));
+    let started = Instant::now();
+    let workers: Vec<_> = (0..args.concurrency)
+        .map(|worker| {
+            let client = client.clone();
+            let base = base.to_string();
+            let stop = stop.clone();
+            let endpoints = args.endpoints.max(1);
+            tokio::spawn(async move {
+                let mut latencies = Vec::new();
+                let mut errors = 0usize;
+                let mut n = worker;
+                while !stop.load(Ordering::Relaxed) {
+                    n = n.wrapping_mul(7919).wrapping_add(1);
+                    let url = format!("{}{}", base, request_path(n % endpoints));
+                    let sent = Instant::now();
+                    match client.get(url).send().await {
+                        Ok(response) if response.status().is_success() => {
+                            let _ = response.bytes().await;
+                            latencies.push(sent.elapsed());
+                        }
+                        _ => errors += 1,
+                    }
+                }
+                (latencies, errors)
+            })
+        })
+        .collect();
+    let readers: Vec<_> = (0..args.log_readers)
+        .map(|_| {
+            let client = client.clone();
+            let url = format!("{}/__mock/logs", base);
+            let stop = stop.clone();
+            tokio::spawn(async move {
+                let mut reads = 0usize;
+                while !stop.load(Ordering::Relaxed) {
+                    if let Ok(response) = client.get(&url).send().await {
+                        let _ = response.bytes().await;
+                        reads += 1;
+                    }
+                }
+                reads
+            })
+        })
+        .collect();
+
+    tokio::time::sleep(Duration::from_secs(args.duration)).await;
+    stop.store(true, Ordering::Relaxed);
+
+    let mut latencies = Vec::new();
+    let mut errors = 0;
+    for worker in workers {
+        let (worker_latencies, worker_errors) = worker.await.unwrap();
+        latencies.extend(worker_latencies);
+        errors += worker_errors;
+    }
+    let mut log_reads = 0;
+    for reader in readers {
+        log_reads += reader.await.unwrap();
+    }
+    let elapsed = started.elapsed();
+
+    Report { requests: latencies.len(), errors, log_reads, elapsed, latencies }
+}
+
+#[tokio::main(flavor = "multi_thread")]
+async fn main() {
+    let args = Args::parse();
+    let base = match &args.url {
+        Some(url) => url.trim_end_matches('/').to_string(),
+        None => {
+            let cfg = ServerConfig { host: "127.0.0.1".to_string(), port: args.port, no_dashboard: true, ..Default::default() };
+            std::thread::spawn(move || actix_web::rt::System::new().block_on(start_server(cfg)));
+            format!("http://127.0.0.1:{}", args.port)
+        }
+    };
+
+    let client = reqwest::Client::builder().pool_max_idle_per_host(args.concurrency + args.log_readers).build().unwrap();
+    let baseline = args.baseline.as_deref().map(|url| url.trim_end_matches('/'));
+    register(&client, &base, &args).await;
+    if let Some(baseline) = baseline {
+        register(&client, baseline, &args).await;
+    }
+
+    let mut report = Report::default();
+    let mut baseline_report = Report::default();
+    for round in 1..=args.rounds.max(1) {
+        println!("Round {}/{}", round, args.rounds.max(1));
+        report.merge(run(&client, &base, &args).await);
+        if let Some(baseline) = baseline {
+            baseline_report.merge(run(&client, baseline, &args).await);
+        }
+    }
+
+    println!();
+    let Some(baseline) = baseline else {
+        report.print(&base);
+        return;
+    };
+    baseline_report.print(&format!("Baseline {}", baseline));
+    report.print(&base);
+    let change = |before: f64, after: f64| (after - before) / before * 100.0;
+    println!(
+        "Change:        throughput {:+.1}%  p99 latency {:+.1}%",
+        change(baseline_report.throughput(), report.throughput()),
+        change(baseline_report.percentile(0.99).as_secs_f64(), report.percentile(0.99).as_secs_f64()),
+    );
+}
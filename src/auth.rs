@@ -0,0 +1,82 @@
+use actix_web::body::{EitherBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::{web, Error, HttpResponse};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use log::warn;
+use serde_json::json;
+
+use crate::AppState;
+
+const REALM: &str = "RustMock admin";
+
+/// Credentials required on the `/__mock` admin API; with neither set the API is open.
+#[derive(Clone, Debug, Default)]
+pub struct AdminAuth {
+    pub token: Option<String>,
+    /// `user:password`
+    pub basic: Option<String>,
+}
+
+impl AdminAuth {
+    pub fn is_enabled(&self) -> bool {
+        self.token.is_some() || self.basic.is_some()
+    }
+
+    /// Checks an `Authorization` header value against the configured credentials.
+    pub fn authorizes(&self, header: Option<&str>) -> bool {
+        if !self.is_enabled() {
+            return true;
+        }
+        let Some((scheme, credentials)) = header.and_then(|h| h.trim().split_once(' ')) else {
+            return false;
+        };
+        let credentials = credentials.trim();
+        if scheme.eq_ignore_ascii_case("bearer") {
+            return self.token.as_ref().is_some_and(|token| constant_time_eq(token.as_bytes(), credentials.as_bytes()));
+        }
+        if scheme.eq_ignore_ascii_case("basic") {
+            let Some(expected) = &self.basic else { return false };
+            return STANDARD.decode(credentials)
+                .is_ok_and(|decoded| constant_time_eq(expected.as_bytes(), &decoded));
+        }
+        false
+    }
+
+    fn challenges(&self) -> Vec<String> {
+        let mut challenges = Vec::new();
+        if self.token.is_some() {
+            challenges.push(format!("Bearer realm=\"{}\"", REALM));
+        }
+        if self.basic.is_some() {
+            challenges.push(format!("Basic realm=\"{}\", charset=\"UTF-8\"", REALM));
+        }
+        challenges
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Middleware for the admin scope: rejects requests without valid credentials with 401.
+pub async fn require_admin(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, Error> {
+    let auth = req.app_data::<web::Data<AppState>>()
+        .map(|data| data.admin_auth.clone())
+        .unwrap_or_default();
+    let header = req.headers().get("authorization").and_then(|v| v.to_str().ok());
+    if auth.authorizes(header) {
+        return next.call(req).await.map(ServiceResponse::map_into_left_body);
+    }
+
+    warn!("Rejected unauthenticated admin request {} {}", req.method(), req.path());
+    let mut response = HttpResponse::Unauthorized();
+    for challenge in auth.challenges() {
+        response.append_header(("WWW-Authenticate", challenge));
+    }
+    let response = response.json(json!({"error": "Admin API requires authentication"}));
+    Ok(req.into_response(response).map_into_right_body())
+}
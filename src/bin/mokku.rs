@@ -2,7 +2,7 @@ use clap::{Parser, Subcommand};
 use colored::Colorize;
 use inquire::{Select, Text};
 use std::path::PathBuf;
-use RustMock::{init_logger, load_openapi_from_file, start_server, ServerConfig, EndpointConfig};
+use rust_mock::{init_logger, load_openapi_from_file, start_server, ServerConfig, EndpointConfig};
 
 #[derive(Parser)]
 #[command(
@@ -30,6 +30,102 @@ struct Cli {
     /// Auto-open dashboard in browser
     #[arg(long, short = 'o', global = true)]
     open: bool,
+
+    /// Cache proxied GET responses for this many seconds
+    #[arg(long, global = true)]
+    proxy_cache_ttl: Option<u64>,
+
+    /// Serve gRPC on this port
+    #[arg(long, global = true)]
+    grpc_port: Option<u16>,
+
+    /// .proto file or descriptor set to serve over gRPC (repeatable)
+    #[arg(long = "proto", global = true)]
+    proto_files: Vec<String>,
+
+    /// Bearer token for the admin API (also sent by `mokku mock`; default: $ADMIN_TOKEN)
+    #[arg(long, global = true)]
+    admin_token: Option<String>,
+
+    /// user:password accepted via basic auth on the admin API
+    #[arg(long, global = true)]
+    admin_basic_auth: Option<String>,
+
+    /// Interface for a separate admin listener (default: --host)
+    #[arg(long, global = true)]
+    admin_host: Option<String>,
+
+    /// Serve the admin API and dashboard on a separate port
+    #[arg(long, global = true)]
+    admin_port: Option<u16>,
+
+    /// Path prefix of the admin API (default: /__mock)
+    #[arg(long, global = true)]
+    admin_prefix: Option<String>,
+
+    /// Path the dashboard is mounted at (default: /)
+    #[arg(long, global = true)]
+    dashboard_path: Option<String>,
+
+    /// Don't serve the dashboard, leaving every path free for mocks
+    #[arg(long, global = true)]
+    no_dashboard: bool,
+
+    /// Serve the dashboard from this directory instead of the embedded build
+    #[arg(long, global = true)]
+    ui_dir: Option<String>,
+
+    /// Also serve mocks over HTTPS on this port
+    #[arg(long, global = true)]
+    https_port: Option<u16>,
+
+    /// PEM certificate chain for HTTPS (default: generated from a local CA)
+    #[arg(long, global = true)]
+    tls_cert: Option<String>,
+
+    /// PEM private key for --tls-cert
+    #[arg(long, global = true)]
+    tls_key: Option<String>,
+
+    /// Hostname or IP the generated certificate covers (repeatable)
+    #[arg(long = "tls-hostname", global = true)]
+    tls_hostnames: Vec<String>,
+
+    /// Where generated certificates are written (default: .rustmock/certs)
+    #[arg(long, global = true)]
+    tls_dir: Option<String>,
+
+    /// Request log entries to keep, oldest dropped first (default: 10000)
+    #[arg(long, global = true)]
+    max_logs: Option<usize>,
+
+    /// Origin allowed cross-origin access, e.g. https://*.example.com or * for any (repeatable; default: CORS off)
+    #[arg(long = "cors-origin", global = true)]
+    cors_origins: Vec<String>,
+
+    /// Method preflights may request (repeatable; default: whatever is asked for)
+    #[arg(long = "cors-method", global = true)]
+    cors_methods: Vec<String>,
+
+    /// Request header preflights may ask for (repeatable; default: whatever is asked for)
+    #[arg(long = "cors-header", global = true)]
+    cors_headers: Vec<String>,
+
+    /// Allow cookies and credentials on cross-origin requests
+    #[arg(long, global = true)]
+    cors_credentials: bool,
+
+    /// Seconds browsers may cache a preflight answer
+    #[arg(long, global = true)]
+    cors_max_age: Option<u64>,
+
+    /// Serve the mock OAuth2/OIDC provider at /.well-known/openid-configuration and /oauth
+    #[arg(long, global = true)]
+    oauth: bool,
+
+    /// Development mode: allow CORS from localhost origins unless --cors-origin is given
+    #[arg(long, global = true)]
+    dev: bool,
 }
 
 #[derive(Clone, Subcommand)]
@@ -61,6 +157,10 @@ enum Commands {
         /// Server port (if --start is used)
         #[arg(long, short = 'p')]
         port: Option<u16>,
+
+        /// Reject requests missing the credentials the spec's security requirements declare
+        #[arg(long)]
+        enforce_security: bool,
     },
 
     /// Create a quick mock endpoint
@@ -106,12 +206,12 @@ async fn main() -> anyhow::Result<()> {
             start_server_with_browser(config, should_open).await?;
         }
 
-        Commands::Import { file, start, open, port } => {
-            handle_import(file, start, open || cli.open, port, &cli).await?;
+        Commands::Import { file, start, open, port, enforce_security } => {
+            handle_import(file, start, open || cli.open, port, enforce_security, &cli).await?;
         }
 
         Commands::Mock { method, path, status, body, server } => {
-            handle_mock(method, path, status, body, server).await?;
+            handle_mock(method, path, status, body, server, &cli).await?;
         }
 
         Commands::Replay { name } => {
@@ -149,11 +249,7 @@ async fn run_interactive_mode(cli: Cli) -> anyhow::Result<()> {
                 .prompt()
                 .unwrap_or(true);
 
-            let config = ServerConfig {
-                host: cli.host.unwrap_or_else(|| "0.0.0.0".to_string()),
-                port,
-                default_proxy_url: cli.proxy,
-            };
+            let config = build_server_config(&cli, Some(port));
 
             start_server_with_browser(config, open_browser).await?;
         }
@@ -182,6 +278,7 @@ async fn run_interactive_mode(cli: Cli) -> anyhow::Result<()> {
                 start,
                 open,
                 cli.port,
+                false,
                 &cli,
             )
             .await?;
@@ -216,6 +313,7 @@ async fn run_interactive_mode(cli: Cli) -> anyhow::Result<()> {
                 Some(status),
                 Some(body),
                 server,
+                &cli,
             )
             .await?;
         }
@@ -236,6 +334,7 @@ async fn handle_import(
     start: bool,
     open: bool,
     port: Option<u16>,
+    enforce_security: bool,
     cli: &Cli,
 ) -> anyhow::Result<()> {
     println!("{} {}", "📥 Importing OpenAPI spec from".bright_blue(), file.display());
@@ -246,9 +345,11 @@ async fn handle_import(
     println!("{} OpenAPI spec loaded successfully", "✓".bright_green());
 
     if start {
-        let config = build_server_config(cli, port);
-
-        std::env::set_var("OPENAPI_FILE", file.to_string_lossy().to_string());
+        let config = ServerConfig {
+            openapi_file: Some(file.to_string_lossy().to_string()),
+            enforce_security,
+            ..build_server_config(cli, port)
+        };
 
         println!("{} Starting server with imported endpoints...", "🚀".bright_cyan());
         start_server_with_browser(config, open).await?;
@@ -281,6 +382,7 @@ async fn handle_mock(
     status: Option<u16>,
     body: Option<String>,
     server: String,
+    cli: &Cli,
 ) -> anyhow::Result<()> {
     let method = if let Some(m) = method {
         m.to_uppercase()
@@ -324,12 +426,12 @@ async fn handle_mock(
         path: path.clone(),
         response,
         status: Some(status),
-        headers: None,
-        proxy_url: None,
+        ..Default::default()
     };
 
     let client = reqwest::Client::new();
-    let url = format!("{}/__mock/endpoints", server.trim_end_matches('/'));
+    let admin_prefix = cli.admin_prefix.as_deref().unwrap_or("/__mock").trim_end_matches('/');
+    let url = format!("{}{}/endpoints", server.trim_end_matches('/'), admin_prefix);
 
     println!(
         "{} Creating mock: {} {} → {}",
@@ -339,7 +441,12 @@ async fn handle_mock(
         status.to_string().bright_green()
     );
 
-    match client.post(&url).json(&endpoint).send().await {
+    let mut request = client.post(&url).json(&endpoint);
+    if let Some(token) = cli.admin_token.clone().or_else(|| std::env::var("ADMIN_TOKEN").ok()) {
+        request = request.bearer_auth(token);
+    }
+
+    match request.send().await {
         Ok(response) => {
             if response.status().is_success() {
                 println!("{} Mock endpoint created successfully!", "✓".bright_green());
@@ -389,17 +496,52 @@ fn build_server_config(cli: &Cli, port_override: Option<u16>) -> ServerConfig {
         host: cli.host.clone().unwrap_or_else(|| "0.0.0.0".to_string()),
         port: port_override.or(cli.port).unwrap_or(8090),
         default_proxy_url: cli.proxy.clone(),
+        proxy_cache_ttl: cli.proxy_cache_ttl,
+        grpc_port: cli.grpc_port,
+        proto_files: cli.proto_files.clone(),
+        admin_token: cli.admin_token.clone(),
+        admin_basic_auth: cli.admin_basic_auth.clone(),
+        admin_host: cli.admin_host.clone(),
+        admin_port: cli.admin_port,
+        admin_prefix: cli.admin_prefix.clone(),
+        dashboard_path: cli.dashboard_path.clone(),
+        no_dashboard: cli.no_dashboard,
+        ui_dir: cli.ui_dir.clone(),
+        https_port: cli.https_port,
+        tls_cert: cli.tls_cert.clone(),
+        tls_key: cli.tls_key.clone(),
+        tls_hostnames: cli.tls_hostnames.clone(),
+        tls_dir: cli.tls_dir.clone(),
+        max_logs: cli.max_logs,
+        cors_origins: cli.cors_origins.clone(),
+        cors_methods: cli.cors_methods.clone(),
+        cors_headers: cli.cors_headers.clone(),
+        cors_credentials: cli.cors_credentials,
+        cors_max_age: cli.cors_max_age,
+        openapi_file: None,
+        enforce_security: false,
+        oauth: cli.oauth,
+        dev: cli.dev,
     }
 }
 
 async fn start_server_with_browser(config: ServerConfig, open_browser: bool) -> anyhow::Result<()> {
-    let url = format!("http://localhost:{}", config.port);
+    let dashboard_path = config.dashboard_path.as_deref().unwrap_or("/").trim_end_matches('/');
+    let url = format!("http://localhost:{}{}/", config.admin_port.unwrap_or(config.port), dashboard_path);
+    let open_browser = open_browser && !config.no_dashboard;
 
     println!();
     println!("{}", "🚀 Starting Mokku Server...".bright_cyan().bold());
     println!();
-    println!("  {} {}", "Dashboard:".bright_black(), url.bright_white().underline());
+    if config.no_dashboard {
+        println!("  {} {}", "Dashboard:".bright_black(), "disabled".bright_black());
+    } else {
+        println!("  {} {}", "Dashboard:".bright_black(), url.bright_white().underline());
+    }
     println!("  {} http://{}:{}", "Bind:".bright_black(), config.host, config.port);
+    if let Some(https_port) = config.https_port {
+        println!("  {} https://{}:{}", "HTTPS:".bright_black(), config.host, https_port);
+    }
 
     if let Some(ref proxy) = config.default_proxy_url {
         println!("  {} {}", "Proxy:".bright_black(), proxy.bright_yellow());
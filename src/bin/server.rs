@@ -1,5 +1,5 @@
 use clap::Parser;
-use RustMock::{init_logger, start_server, ServerConfig};
+use rust_mock::{init_logger, start_server, ServerConfig};
 
 #[derive(Parser)]
 #[command(name = "RustMock", version, about = "Mock API server")]
@@ -12,6 +12,108 @@ struct Args {
 
     #[arg(long)]
     default_proxy_url: Option<String>,
+
+    #[arg(long)]
+    proxy_cache_ttl: Option<u64>,
+
+    #[arg(long)]
+    grpc_port: Option<u16>,
+
+    /// .proto files or binary descriptor sets to serve over gRPC
+    #[arg(long = "proto")]
+    proto_files: Vec<String>,
+
+    /// Bearer token required on the /__mock admin API
+    #[arg(long)]
+    admin_token: Option<String>,
+
+    /// user:password accepted via basic auth on the /__mock admin API
+    #[arg(long)]
+    admin_basic_auth: Option<String>,
+
+    /// Interface for a separate admin listener (default: --host)
+    #[arg(long)]
+    admin_host: Option<String>,
+
+    /// Serve the admin API and dashboard on this port instead of --port
+    #[arg(long)]
+    admin_port: Option<u16>,
+
+    /// Path prefix of the admin API (default: /__mock)
+    #[arg(long)]
+    admin_prefix: Option<String>,
+
+    /// Path the dashboard is mounted at (default: /)
+    #[arg(long)]
+    dashboard_path: Option<String>,
+
+    /// Don't serve the dashboard, leaving every path free for mocks
+    #[arg(long)]
+    no_dashboard: bool,
+
+    /// Serve the dashboard from this directory instead of the embedded build
+    #[arg(long)]
+    ui_dir: Option<String>,
+
+    /// Also serve mocks over HTTPS on this port
+    #[arg(long)]
+    https_port: Option<u16>,
+
+    /// PEM certificate chain for HTTPS (default: generated from a local CA)
+    #[arg(long)]
+    tls_cert: Option<String>,
+
+    /// PEM private key for --tls-cert
+    #[arg(long)]
+    tls_key: Option<String>,
+
+    /// Hostname or IP the generated certificate covers (repeatable)
+    #[arg(long = "tls-hostname")]
+    tls_hostnames: Vec<String>,
+
+    /// Where generated certificates are written (default: .rustmock/certs)
+    #[arg(long)]
+    tls_dir: Option<String>,
+
+    /// Request log entries to keep, oldest dropped first (default: 10000)
+    #[arg(long)]
+    max_logs: Option<usize>,
+
+    /// Origin allowed cross-origin access, e.g. https://*.example.com or * for any (repeatable; default: CORS off)
+    #[arg(long = "cors-origin")]
+    cors_origins: Vec<String>,
+
+    /// Method preflights may request (repeatable; default: whatever is asked for)
+    #[arg(long = "cors-method")]
+    cors_methods: Vec<String>,
+
+    /// Request header preflights may ask for (repeatable; default: whatever is asked for)
+    #[arg(long = "cors-header")]
+    cors_headers: Vec<String>,
+
+    /// Allow cookies and credentials on cross-origin requests
+    #[arg(long)]
+    cors_credentials: bool,
+
+    /// Seconds browsers may cache a preflight answer
+    #[arg(long)]
+    cors_max_age: Option<u64>,
+
+    /// OpenAPI spec to import at startup
+    #[arg(long)]
+    openapi_file: Option<String>,
+
+    /// Enforce the imported spec's security requirements
+    #[arg(long)]
+    enforce_security: bool,
+
+    /// Serve the mock OAuth2/OIDC provider at /.well-known/openid-configuration and /oauth
+    #[arg(long)]
+    oauth: bool,
+
+    /// Development mode: allow CORS from localhost origins unless --cors-origin is given
+    #[arg(long)]
+    dev: bool,
 }
 
 #[actix_web::main]
@@ -24,6 +126,32 @@ async fn main() -> std::io::Result<()> {
         host: args.host,
         port: args.port,
         default_proxy_url: args.default_proxy_url,
+        proxy_cache_ttl: args.proxy_cache_ttl,
+        grpc_port: args.grpc_port,
+        proto_files: args.proto_files,
+        admin_token: args.admin_token,
+        admin_basic_auth: args.admin_basic_auth,
+        admin_host: args.admin_host,
+        admin_port: args.admin_port,
+        admin_prefix: args.admin_prefix,
+        dashboard_path: args.dashboard_path,
+        no_dashboard: args.no_dashboard,
+        ui_dir: args.ui_dir,
+        https_port: args.https_port,
+        tls_cert: args.tls_cert,
+        tls_key: args.tls_key,
+        tls_hostnames: args.tls_hostnames,
+        tls_dir: args.tls_dir,
+        max_logs: args.max_logs,
+        cors_origins: args.cors_origins,
+        cors_methods: args.cors_methods,
+        cors_headers: args.cors_headers,
+        cors_credentials: args.cors_credentials,
+        cors_max_age: args.cors_max_age,
+        openapi_file: args.openapi_file,
+        enforce_security: args.enforce_security,
+        oauth: args.oauth,
+        dev: args.dev,
     };
 
     start_server(config).await
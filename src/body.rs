@@ -0,0 +1,92 @@
+use actix_web::web::Bytes;
+use base64::Engine;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// Upper bound on how much of a body is kept in a log entry.
+pub const MAX_LOGGED_BODY: usize = 64 * 1024;
+
+#[derive(Serialize, Clone, Debug)]
+pub struct BodyCapture {
+    pub encoding: String,
+    pub data: String,
+    pub size: usize,
+    pub truncated: bool,
+}
+
+impl BodyCapture {
+    /// `prefix` is what was kept of the body, `size` the full length seen on the wire.
+    pub fn new(prefix: &[u8], size: usize) -> Self {
+        let kept = &prefix[..prefix.len().min(MAX_LOGGED_BODY)];
+        let (encoding, data) = match std::str::from_utf8(kept) {
+            Ok(text) => ("text", text.to_string()),
+            // A cut in the middle of a multi-byte character is still text.
+            Err(e) if e.error_len().is_none() => ("text", String::from_utf8_lossy(&kept[..e.valid_up_to()]).into_owned()),
+            Err(_) => ("base64", base64::engine::general_purpose::STANDARD.encode(kept)),
+        };
+        Self {
+            encoding: encoding.to_string(),
+            data,
+            size,
+            truncated: size > kept.len(),
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct FormCapture {
+    pub fields: Map<String, Value>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<UploadedFile>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct UploadedFile {
+    pub field: String,
+    pub filename: String,
+    pub content_type: Option<String>,
+    pub size: usize,
+}
+
+impl FormCapture {
+    /// Repeated field names collect into an array.
+    fn add_field(&mut self, name: String, value: String) {
+        match self.fields.get_mut(&name) {
+            Some(Value::Array(values)) => values.push(Value::String(value)),
+            Some(existing) => *existing = Value::Array(vec![existing.take(), Value::String(value)]),
+            None => {
+                self.fields.insert(name, Value::String(value));
+            }
+        }
+    }
+}
+
+/// Parsed view of `application/x-www-form-urlencoded` and `multipart/form-data` bodies.
+pub async fn parse_form(content_type: &str, body: &Bytes) -> Option<FormCapture> {
+    let mut form = FormCapture::default();
+    if content_type.starts_with("application/x-www-form-urlencoded") {
+        for (name, value) in serde_urlencoded::from_bytes::<Vec<(String, String)>>(body).ok()? {
+            form.add_field(name, value);
+        }
+        return Some(form);
+    }
+    if !content_type.starts_with("multipart/form-data") {
+        return None;
+    }
+
+    let boundary = multer::parse_boundary(content_type).ok()?;
+    let body = body.clone();
+    let stream = futures_util::stream::once(async move { Ok::<_, std::io::Error>(body) });
+    let mut multipart = multer::Multipart::new(stream, boundary);
+    while let Ok(Some(field)) = multipart.next_field().await {
+        let name = field.name().unwrap_or("").to_string();
+        let filename = field.file_name().map(str::to_string);
+        let content_type = field.content_type().map(|m| m.to_string());
+        let Ok(data) = field.bytes().await else { break };
+        match filename {
+            Some(filename) => form.files.push(UploadedFile { field: name, filename, content_type, size: data.len() }),
+            None => form.add_field(name, BodyCapture::new(&data, data.len()).data),
+        }
+    }
+    Some(form)
+}
@@ -0,0 +1,247 @@
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use chrono::Local;
+use log::info;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::AppState;
+
+/// Entries kept when `max_entries` isn't set.
+pub const DEFAULT_MAX_ENTRIES: usize = 1000;
+
+/// Request headers carrying a user's credentials; requests sending them are only cached when
+/// they're listed in `vary_headers`, so one user's responses are never served to another.
+const CREDENTIAL_HEADERS: [&str; 2] = ["authorization", "cookie"];
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct CacheConfig {
+    pub enabled: bool,
+    /// Fixed time-to-live; when unset, freshness comes from the upstream `Cache-Control`.
+    #[serde(default)]
+    pub ttl_secs: Option<u64>,
+    /// Request headers that are part of the cache key (e.g. `Authorization`, `Accept-Language`).
+    #[serde(default)]
+    pub vary_headers: Vec<String>,
+    /// Most entries kept, the oldest evicted first; [`DEFAULT_MAX_ENTRIES`] when unset.
+    #[serde(default)]
+    pub max_entries: Option<usize>,
+}
+
+pub struct CachedResponse {
+    pub method: String,
+    pub url: String,
+    pub status: u16,
+    pub body: Option<Value>,
+    pub headers: HashMap<String, String>,
+    pub etag: Option<String>,
+    pub stored_at: Instant,
+    pub stored_at_str: String,
+    pub fresh_for: Duration,
+    /// Counted under the cache's read lock.
+    pub hits: AtomicU64,
+}
+
+impl CachedResponse {
+    pub fn is_fresh(&self) -> bool {
+        self.stored_at.elapsed() < self.fresh_for
+    }
+}
+
+#[derive(Default)]
+pub struct ProxyCache {
+    pub config: CacheConfig,
+    pub entries: HashMap<String, CachedResponse>,
+    /// Request headers each `method url` varies on, as learned from the upstream's `Vary`.
+    vary: HashMap<String, Vec<String>>,
+}
+
+pub enum Lookup {
+    Fresh(u16, Option<Value>, HashMap<String, String>),
+    Revalidate(String),
+    /// Stale with no validator; the entry should be dropped.
+    Expired,
+    Miss,
+}
+
+impl ProxyCache {
+    pub fn new(config: CacheConfig) -> Self {
+        Self { config, ..Default::default() }
+    }
+
+    /// Whether `req` may be answered from, or stored in, the cache.
+    pub fn accepts(&self, req: &HttpRequest) -> bool {
+        CREDENTIAL_HEADERS.iter().all(|name| {
+            !req.headers().contains_key(*name) || self.config.vary_headers.iter().any(|v| v.eq_ignore_ascii_case(name))
+        })
+    }
+
+    pub fn key(&self, method: &str, url: &str, req: &HttpRequest) -> String {
+        let base = format!("{} {}", method, url);
+        let mut names: Vec<String> = self.config.vary_headers.iter().map(|name| name.to_lowercase()).collect();
+        for name in self.vary.get(&base).into_iter().flatten() {
+            if !names.contains(name) {
+                names.push(name.clone());
+            }
+        }
+        let mut key = base;
+        for name in names {
+            let value = req.headers().get(name.as_str())
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or("");
+            key.push_str(&format!("|{}={}", name, value));
+        }
+        key
+    }
+
+    pub fn lookup(&self, key: &str) -> Lookup {
+        match self.entries.get(key) {
+            Some(entry) if entry.is_fresh() => {
+                entry.hits.fetch_add(1, Ordering::Relaxed);
+                Lookup::Fresh(entry.status, entry.body.clone(), entry.headers.clone())
+            }
+            Some(entry) => match &entry.etag {
+                Some(etag) => Lookup::Revalidate(etag.clone()),
+                None => Lookup::Expired,
+            },
+            None => Lookup::Miss,
+        }
+    }
+
+    /// Handles a `304 Not Modified` from the upstream by restarting the entry's freshness.
+    pub fn refresh(&mut self, key: &str, headers: &HashMap<String, String>) -> Option<(u16, Option<Value>, HashMap<String, String>)> {
+        let ttl = self.ttl_for(headers);
+        let entry = self.entries.get_mut(key)?;
+        entry.stored_at = Instant::now();
+        entry.stored_at_str = Local::now().to_rfc3339();
+        entry.fresh_for = ttl.unwrap_or(Duration::ZERO);
+        *entry.hits.get_mut() += 1;
+        Some((entry.status, entry.body.clone(), entry.headers.clone()))
+    }
+
+    /// Stores a response to `req`, keyed by the request headers its `Vary` names.
+    pub fn store(&mut self, req: &HttpRequest, method: &str, url: &str, status: u16, body: &Option<Value>, headers: &HashMap<String, String>) {
+        if status != 200 {
+            return;
+        }
+        let cache_control = header(headers, "cache-control").unwrap_or_default().to_lowercase();
+        if cache_control.contains("no-store") || cache_control.contains("private") {
+            return;
+        }
+        let vary: Vec<String> = header(headers, "vary").unwrap_or_default()
+            .split(',')
+            .map(|name| name.trim().to_lowercase())
+            .filter(|name| !name.is_empty())
+            .collect();
+        if vary.iter().any(|name| name == "*") {
+            return;
+        }
+        let etag = header(headers, "etag");
+        let fresh_for = self.ttl_for(headers).unwrap_or(Duration::ZERO);
+        if fresh_for.is_zero() && etag.is_none() {
+            return;
+        }
+
+        let base = format!("{} {}", method, url);
+        match vary.is_empty() {
+            true => self.vary.remove(&base),
+            false => self.vary.insert(base, vary),
+        };
+        let key = self.key(method, url, req);
+        let max_entries = self.config.max_entries.unwrap_or(DEFAULT_MAX_ENTRIES);
+        while !self.entries.contains_key(&key) && self.entries.len() >= max_entries.max(1) {
+            let Some(oldest) = self.entries.iter().min_by_key(|(_, entry)| entry.stored_at).map(|(key, _)| key.clone()) else { break };
+            self.entries.remove(&oldest);
+        }
+
+        info!("Caching {} {} for {}s", method, url, fresh_for.as_secs());
+        self.entries.insert(key, CachedResponse {
+            method: method.to_string(),
+            url: url.to_string(),
+            status,
+            body: body.clone(),
+            headers: headers.clone(),
+            etag,
+            stored_at: Instant::now(),
+            stored_at_str: Local::now().to_rfc3339(),
+            fresh_for,
+            hits: AtomicU64::new(0),
+        });
+    }
+
+    fn ttl_for(&self, headers: &HashMap<String, String>) -> Option<Duration> {
+        if let Some(ttl) = self.config.ttl_secs {
+            return Some(Duration::from_secs(ttl));
+        }
+        let cache_control = header(headers, "cache-control")?.to_lowercase();
+        if cache_control.contains("no-cache") {
+            return Some(Duration::ZERO);
+        }
+        let max_age = |directive: &str| cache_control.split(',')
+            .filter_map(|d| d.trim().strip_prefix(directive))
+            .find_map(|v| v.trim().parse::<u64>().ok());
+        max_age("s-maxage=").or_else(|| max_age("max-age=")).map(Duration::from_secs)
+    }
+}
+
+fn header(headers: &HashMap<String, String>, name: &str) -> Option<String> {
+    headers.iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(name))
+        .map(|(_, v)| v.clone())
+}
+
+#[derive(Deserialize)]
+pub struct PurgeQuery {
+    pub url_prefix: Option<String>,
+}
+
+pub async fn get_cache(data: web::Data<AppState>) -> impl Responder {
+    let cache = data.proxy_cache.read().unwrap();
+    let mut entries: Vec<Value> = cache.entries.iter().map(|(key, entry)| {
+        json!({
+            "key": key,
+            "method": entry.method,
+            "url": entry.url,
+            "status": entry.status,
+            "etag": entry.etag,
+            "stored_at": entry.stored_at_str,
+            "age_secs": entry.stored_at.elapsed().as_secs(),
+            "fresh": entry.is_fresh(),
+            "hits": entry.hits.load(Ordering::Relaxed)
+        })
+    }).collect();
+    entries.sort_by(|a, b| a["key"].as_str().cmp(&b["key"].as_str()));
+
+    HttpResponse::Ok().json(json!({
+        "config": cache.config,
+        "entries": entries
+    }))
+}
+
+pub async fn set_cache(data: web::Data<AppState>, cfg: web::Json<CacheConfig>) -> impl Responder {
+    let mut cache = data.proxy_cache.write().unwrap();
+    cache.config = cfg.into_inner();
+    if !cache.config.enabled {
+        cache.entries.clear();
+        cache.vary.clear();
+    }
+    info!("Proxy cache config: {:?}", cache.config);
+    HttpResponse::Ok().json(json!({"config": cache.config}))
+}
+
+pub async fn purge_cache(data: web::Data<AppState>, query: web::Query<PurgeQuery>) -> impl Responder {
+    let mut cache = data.proxy_cache.write().unwrap();
+    let before = cache.entries.len();
+    match &query.url_prefix {
+        Some(prefix) => cache.entries.retain(|_, entry| !entry.url.starts_with(prefix.as_str())),
+        None => {
+            cache.entries.clear();
+            cache.vary.clear();
+        }
+    }
+    let purged = before - cache.entries.len();
+    info!("Purged {} cached proxy responses", purged);
+    HttpResponse::Ok().json(json!({"purged": purged}))
+}
@@ -0,0 +1,147 @@
+use actix_web::body::{EitherBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{self, HeaderMap, HeaderName, HeaderValue};
+use actix_web::http::Method;
+use actix_web::middleware::Next;
+use actix_web::{web, Error, HttpResponse};
+use log::warn;
+use serde::Serialize;
+use serde_json::json;
+
+use crate::AppState;
+
+/// Response headers scripts can always read, so they are never listed in `Access-Control-Expose-Headers`.
+const SAFELISTED: &[&str] = &["cache-control", "content-language", "content-length", "content-type", "expires", "last-modified", "pragma"];
+
+/// Origins allowed in development mode when none are configured: local dev servers on any port.
+pub const DEV_ORIGINS: &[&str] = &[
+    "http://localhost",
+    "http://localhost:*",
+    "http://127.0.0.1",
+    "http://127.0.0.1:*",
+    "http://[::1]",
+    "http://[::1]:*",
+    "https://localhost",
+    "https://localhost:*",
+    "https://127.0.0.1",
+    "https://127.0.0.1:*",
+    "https://[::1]",
+    "https://[::1]:*",
+];
+
+/// Which cross-origin callers get CORS headers and what their preflights are allowed.
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct CorsPolicy {
+    /// Exact origins or patterns like `https://*.example.com`; `*` allows any origin.
+    pub origins: Vec<String>,
+    /// Allowed methods; whatever a preflight asks for when empty.
+    pub methods: Vec<String>,
+    /// Allowed request headers; whatever a preflight asks for when empty.
+    pub headers: Vec<String>,
+    pub credentials: bool,
+    pub max_age: Option<u64>,
+}
+
+impl CorsPolicy {
+    pub fn allows(&self, origin: &str) -> bool {
+        self.origins.iter().any(|allowed| match allowed.split_once('*') {
+            Some((prefix, suffix)) => origin.len() >= prefix.len() + suffix.len() && origin.starts_with(prefix) && origin.ends_with(suffix),
+            None => allowed.eq_ignore_ascii_case(origin),
+        })
+    }
+
+    fn preflight(&self, req: &ServiceRequest, origin: &HeaderValue) -> HttpResponse {
+        let requested = |name: HeaderName| req.headers().get(name).and_then(|v| v.to_str().ok()).unwrap_or_default().to_string();
+        let methods = match self.methods.is_empty() {
+            true => requested(header::ACCESS_CONTROL_REQUEST_METHOD),
+            false => self.methods.join(", "),
+        };
+        let headers = match self.headers.is_empty() {
+            true => requested(header::ACCESS_CONTROL_REQUEST_HEADERS),
+            false => self.headers.join(", "),
+        };
+
+        let mut response = HttpResponse::NoContent();
+        response
+            .insert_header((header::ACCESS_CONTROL_ALLOW_ORIGIN, origin.clone()))
+            .insert_header((header::ACCESS_CONTROL_ALLOW_METHODS, methods))
+            .insert_header((header::VARY, "Origin, Access-Control-Request-Method, Access-Control-Request-Headers"));
+        if !headers.is_empty() {
+            response.insert_header((header::ACCESS_CONTROL_ALLOW_HEADERS, headers));
+        }
+        if self.credentials {
+            response.insert_header((header::ACCESS_CONTROL_ALLOW_CREDENTIALS, "true"));
+        }
+        if let Some(max_age) = self.max_age {
+            response.insert_header((header::ACCESS_CONTROL_MAX_AGE, max_age.to_string()));
+        }
+        response.finish()
+    }
+
+    /// Adds CORS headers to an actual response, replacing any a proxied upstream sent for its own origins.
+    fn decorate(&self, headers: &mut HeaderMap, origin: &HeaderValue) {
+        let exposed: Vec<&str> = headers.keys()
+            .map(HeaderName::as_str)
+            .filter(|name| !SAFELISTED.contains(name) && *name != "vary" && !name.starts_with("access-control-"))
+            .collect();
+        let exposed = HeaderValue::from_str(&exposed.join(", ")).ok().filter(|v| !v.is_empty());
+
+        headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin.clone());
+        headers.remove(header::ACCESS_CONTROL_ALLOW_CREDENTIALS);
+        headers.remove(header::ACCESS_CONTROL_EXPOSE_HEADERS);
+        if self.credentials {
+            headers.insert(header::ACCESS_CONTROL_ALLOW_CREDENTIALS, HeaderValue::from_static("true"));
+        }
+        if let Some(exposed) = exposed {
+            headers.insert(header::ACCESS_CONTROL_EXPOSE_HEADERS, exposed);
+        }
+        headers.append(header::VARY, HeaderValue::from_static("Origin"));
+    }
+}
+
+fn is_preflight(req: &ServiceRequest) -> bool {
+    req.method() == Method::OPTIONS && req.headers().contains_key(header::ACCESS_CONTROL_REQUEST_METHOD)
+}
+
+/// Whether an `OPTIONS` stub was registered for the request's path, which then answers its
+/// own preflights.
+fn has_options_stub(data: &AppState, path: &str) -> bool {
+    data.dynamic.read().unwrap().contains_key(&("OPTIONS".to_string(), path.to_string()))
+        || data.router.read().unwrap().find("OPTIONS", path).is_some()
+}
+
+/// Middleware answering CORS preflights and decorating responses for allowed origins.
+///
+/// Only wraps stubbed and proxied traffic; the admin API and dashboard never get CORS headers.
+pub async fn handle_cors(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, Error> {
+    let data = req.app_data::<web::Data<AppState>>().cloned();
+    let policy = data.as_ref().and_then(|data| data.cors.as_ref());
+    let origin = req.headers().get(header::ORIGIN).cloned();
+    let (Some(policy), Some(origin)) = (policy, origin) else {
+        return next.call(req).await.map(ServiceResponse::map_into_left_body);
+    };
+    let allowed = origin.to_str().is_ok_and(|o| policy.allows(o));
+
+    if is_preflight(&req) {
+        if data.as_ref().is_some_and(|data| has_options_stub(data, req.path())) {
+            return next.call(req).await.map(ServiceResponse::map_into_left_body);
+        }
+        let response = match allowed {
+            true => policy.preflight(&req, &origin),
+            false => {
+                warn!("Rejected CORS preflight from {:?} for {}", origin, req.path());
+                HttpResponse::Forbidden().json(json!({"error": "Origin not allowed by CORS policy"}))
+            }
+        };
+        return Ok(req.into_response(response).map_into_right_body());
+    }
+
+    let mut response = next.call(req).await?;
+    if allowed {
+        policy.decorate(response.headers_mut(), &origin);
+    }
+    Ok(response.map_into_left_body())
+}
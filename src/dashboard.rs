@@ -0,0 +1,161 @@
+use actix_files::Files;
+use actix_web::{guard, web, HttpRequest, HttpResponse, Responder};
+use log::warn;
+use serde_json::json;
+use std::{fs, path::PathBuf};
+
+use crate::AppState;
+
+pub const DEFAULT_ADMIN_PREFIX: &str = "/__mock";
+const UI_DIR: &str = "./ui/dist";
+
+#[cfg(feature = "embed-ui")]
+#[derive(rust_embed::RustEmbed)]
+#[folder = "ui/dist/"]
+#[allow_missing = true]
+struct EmbeddedUi;
+
+/// Where the dashboard's files come from.
+#[derive(Clone, Debug)]
+pub enum UiSource {
+    Dir(PathBuf),
+    /// Built into the binary by the `embed-ui` feature.
+    #[cfg(feature = "embed-ui")]
+    Embedded,
+}
+
+impl UiSource {
+    /// `--ui-dir` when given, then the assets built into the binary, then `./ui/dist`.
+    pub fn resolve(ui_dir: Option<&str>) -> Option<UiSource> {
+        if let Some(dir) = ui_dir {
+            let dir = PathBuf::from(dir);
+            if !dir.join("index.html").is_file() {
+                warn!("No index.html in UI directory {}", dir.display());
+            }
+            return Some(UiSource::Dir(dir));
+        }
+        #[cfg(feature = "embed-ui")]
+        if EmbeddedUi::get("index.html").is_some() {
+            return Some(UiSource::Embedded);
+        }
+        let dir = PathBuf::from(UI_DIR);
+        dir.join("index.html").is_file().then_some(UiSource::Dir(dir))
+    }
+
+    fn index_html(&self) -> Option<String> {
+        match self {
+            UiSource::Dir(dir) => fs::read_to_string(dir.join("index.html")).ok(),
+            #[cfg(feature = "embed-ui")]
+            UiSource::Embedded => EmbeddedUi::get("index.html").map(|f| String::from_utf8_lossy(&f.data).into_owned()),
+        }
+    }
+}
+
+/// Where the dashboard is mounted, which admin prefix it should call and where its files live.
+#[derive(Clone, Debug)]
+pub struct Dashboard {
+    /// `""` for the root, otherwise e.g. `/__dashboard` (no trailing slash).
+    pub mount: String,
+    pub admin_prefix: String,
+    pub source: UiSource,
+}
+
+/// Normalizes a configured mount point or prefix to `/segment[/segment...]`, `""` meaning the root.
+pub fn normalize_path(path: &str) -> Result<String, String> {
+    let trimmed = path.trim().trim_end_matches('/');
+    if trimmed.is_empty() {
+        return Ok(String::new());
+    }
+    let valid = trimmed.starts_with('/')
+        && trimmed.chars().all(|c| c.is_ascii_alphanumeric() || "/-_.~".contains(c))
+        && !trimmed.contains("//");
+    if valid {
+        Ok(trimmed.to_string())
+    } else {
+        Err(format!("Invalid path {:?}: use /segment with letters, digits, - _ . ~", path))
+    }
+}
+
+impl Dashboard {
+    /// The dashboard's index.html with the mount point and admin prefix injected for the UI.
+    fn index_html(&self) -> Option<String> {
+        let html = self.source.index_html()?;
+        let config = json!({
+            "adminPrefix": self.admin_prefix,
+            "basePath": if self.mount.is_empty() { "/" } else { &self.mount },
+        });
+        let script = format!("<script>window.__RUSTMOCK__ = {};</script>", config);
+        Some(match html.find("</head>") {
+            Some(i) => format!("{}{}{}", &html[..i], script, &html[i..]),
+            None => format!("{}{}", script, html),
+        })
+    }
+}
+
+/// A dashboard as registered on one listener.
+struct Mount {
+    dashboard: Dashboard,
+    /// Hand paths that are not UI files to mocked traffic instead of the UI's index.
+    mocks_fallback: bool,
+}
+
+async fn index(mount: web::Data<Mount>) -> HttpResponse {
+    match mount.dashboard.index_html() {
+        Some(html) => HttpResponse::Ok().content_type("text/html; charset=utf-8").body(html),
+        None => HttpResponse::NotFound().body("Dashboard not built: run `npm run build` in ui/"),
+    }
+}
+
+#[cfg(feature = "embed-ui")]
+async fn embedded_asset(
+    req: HttpRequest,
+    body: web::Bytes,
+    data: web::Data<AppState>,
+    mount: web::Data<Mount>,
+) -> HttpResponse {
+    let Some(file) = EmbeddedUi::get(req.match_info().query("file")) else {
+        return not_a_file(req, body, data, mount).await;
+    };
+    let etag = format!("\"{}\"", file.metadata.sha256_hash().iter().map(|b| format!("{:02x}", b)).collect::<String>());
+    let cached = req.headers().get("if-none-match").and_then(|v| v.to_str().ok()) == Some(etag.as_str());
+    if cached {
+        return HttpResponse::NotModified().insert_header(("ETag", etag)).finish();
+    }
+    HttpResponse::Ok()
+        .content_type(file.metadata.mimetype())
+        .insert_header(("ETag", etag))
+        .body(file.data.into_owned())
+}
+
+async fn not_a_file(req: HttpRequest, body: web::Bytes, data: web::Data<AppState>, mount: web::Data<Mount>) -> HttpResponse {
+    if mount.mocks_fallback {
+        crate::dispatch(req.clone(), body, data).await.respond_to(&req).map_into_boxed_body()
+    } else {
+        index(mount).await
+    }
+}
+
+/// GET routes for the dashboard. Paths under the mount that are not UI files go to mocked
+/// traffic when `mocks_fallback` is set, otherwise to the UI's index for client-side routing.
+pub fn configure(cfg: &mut web::ServiceConfig, dashboard: &Dashboard, mocks_fallback: bool) {
+    if !dashboard.mount.is_empty() {
+        let target = format!("{}/", dashboard.mount);
+        cfg.service(web::resource(&dashboard.mount)
+            .guard(guard::Get())
+            .to(move || {
+                let target = target.clone();
+                async move { HttpResponse::PermanentRedirect().insert_header(("Location", target)).finish() }
+            }));
+    }
+    let scope = web::scope(&dashboard.mount)
+        .guard(guard::Get())
+        .app_data(web::Data::new(Mount { dashboard: dashboard.clone(), mocks_fallback }))
+        .route("/", web::get().to(index))
+        .route("/index.html", web::get().to(index));
+    let scope = match &dashboard.source {
+        UiSource::Dir(dir) => scope.service(Files::new("/", dir).default_handler(web::route().to(not_a_file))),
+        #[cfg(feature = "embed-ui")]
+        UiSource::Embedded => scope.route("/{file:.*}", web::get().to(embedded_asset)),
+    };
+    cfg.service(scope);
+}
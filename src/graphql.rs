@@ -0,0 +1,435 @@
+use actix_web::{web, HttpResponse, Responder};
+use graphql_parser::query::{self, Definition, OperationDefinition, Selection, TypeCondition};
+use graphql_parser::schema::{self, TypeDefinition, TypeExtension};
+use log::info;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+use crate::ws::json_contains;
+use crate::AppState;
+
+type Fragments<'q> = HashMap<String, &'q query::FragmentDefinition<'q, String>>;
+
+#[derive(Clone, Debug)]
+enum TypeRef {
+    Named(String),
+    List(Box<TypeRef>),
+    NonNull(Box<TypeRef>),
+}
+
+#[derive(Clone, Debug)]
+enum TypeKind {
+    Scalar,
+    Object(HashMap<String, TypeRef>),
+    Interface(HashMap<String, TypeRef>),
+    Union(Vec<String>),
+    Enum(Vec<String>),
+    InputObject,
+}
+
+/// The parts of an SDL schema needed to shape mock responses.
+#[derive(Clone, Debug)]
+pub struct Schema {
+    types: HashMap<String, TypeKind>,
+    implementations: HashMap<String, Vec<String>>,
+    query: String,
+    mutation: String,
+    subscription: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GraphqlOverride {
+    #[serde(default)]
+    pub operation_name: Option<String>,
+    /// Matches when every listed variable is present with an equal value.
+    #[serde(default)]
+    pub variables: Option<Value>,
+    pub response: Value,
+    #[serde(default)]
+    pub status: Option<u16>,
+}
+
+pub struct GraphqlState {
+    pub path: String,
+    pub sdl: Option<String>,
+    pub schema: Option<Schema>,
+    pub overrides: Vec<GraphqlOverride>,
+}
+
+impl Default for GraphqlState {
+    fn default() -> Self {
+        Self {
+            path: "/graphql".to_string(),
+            sdl: None,
+            schema: None,
+            overrides: Vec::new(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct SchemaRequest {
+    pub sdl: String,
+    #[serde(default)]
+    pub path: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct GraphqlRequest {
+    query: String,
+    #[serde(default, rename = "operationName")]
+    operation_name: Option<String>,
+    #[serde(default)]
+    variables: Option<Value>,
+}
+
+pub struct GraphqlResult {
+    pub status: u16,
+    pub body: Value,
+    pub operation: Value,
+    pub matched: String,
+}
+
+fn type_ref(ty: &schema::Type<'static, String>) -> TypeRef {
+    match ty {
+        schema::Type::NamedType(name) => TypeRef::Named(name.clone()),
+        schema::Type::ListType(inner) => TypeRef::List(Box::new(type_ref(inner))),
+        schema::Type::NonNullType(inner) => TypeRef::NonNull(Box::new(type_ref(inner))),
+    }
+}
+
+fn field_map(fields: &[schema::Field<'static, String>]) -> HashMap<String, TypeRef> {
+    fields.iter().map(|f| (f.name.clone(), type_ref(&f.field_type))).collect()
+}
+
+impl Schema {
+    pub fn parse(sdl: &str) -> Result<Self, String> {
+        let doc = graphql_parser::parse_schema::<String>(sdl)
+            .map_err(|e| format!("Invalid GraphQL SDL: {}", e))?
+            .into_static();
+
+        let mut types = HashMap::new();
+        for scalar in ["String", "Int", "Float", "Boolean", "ID"] {
+            types.insert(scalar.to_string(), TypeKind::Scalar);
+        }
+        let mut implementations: HashMap<String, Vec<String>> = HashMap::new();
+        let mut roots = (None, None, None);
+        let mut extensions = Vec::new();
+
+        for definition in &doc.definitions {
+            match definition {
+                schema::Definition::SchemaDefinition(def) => {
+                    roots = (def.query.clone(), def.mutation.clone(), def.subscription.clone());
+                }
+                schema::Definition::TypeDefinition(def) => {
+                    let (name, kind) = match def {
+                        TypeDefinition::Scalar(t) => (t.name.clone(), TypeKind::Scalar),
+                        TypeDefinition::Object(t) => {
+                            for interface in &t.implements_interfaces {
+                                implementations.entry(interface.clone()).or_default().push(t.name.clone());
+                            }
+                            (t.name.clone(), TypeKind::Object(field_map(&t.fields)))
+                        }
+                        TypeDefinition::Interface(t) => (t.name.clone(), TypeKind::Interface(field_map(&t.fields))),
+                        TypeDefinition::Union(t) => (t.name.clone(), TypeKind::Union(t.types.clone())),
+                        TypeDefinition::Enum(t) => (t.name.clone(), TypeKind::Enum(t.values.iter().map(|v| v.name.clone()).collect())),
+                        TypeDefinition::InputObject(t) => (t.name.clone(), TypeKind::InputObject),
+                    };
+                    types.insert(name, kind);
+                }
+                schema::Definition::TypeExtension(ext) => extensions.push(ext),
+                schema::Definition::DirectiveDefinition(_) => {}
+            }
+        }
+
+        for ext in extensions {
+            match ext {
+                TypeExtension::Object(t) => {
+                    if let Some(TypeKind::Object(fields)) = types.get_mut(&t.name) {
+                        fields.extend(field_map(&t.fields));
+                    }
+                    for interface in &t.implements_interfaces {
+                        implementations.entry(interface.clone()).or_default().push(t.name.clone());
+                    }
+                }
+                TypeExtension::Interface(t) => {
+                    if let Some(TypeKind::Interface(fields)) = types.get_mut(&t.name) {
+                        fields.extend(field_map(&t.fields));
+                    }
+                }
+                TypeExtension::Union(t) => {
+                    if let Some(TypeKind::Union(members)) = types.get_mut(&t.name) {
+                        members.extend(t.types.iter().cloned());
+                    }
+                }
+                TypeExtension::Enum(t) => {
+                    if let Some(TypeKind::Enum(values)) = types.get_mut(&t.name) {
+                        values.extend(t.values.iter().map(|v| v.name.clone()));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let schema = Self {
+            types,
+            implementations,
+            query: roots.0.unwrap_or_else(|| "Query".to_string()),
+            mutation: roots.1.unwrap_or_else(|| "Mutation".to_string()),
+            subscription: roots.2.unwrap_or_else(|| "Subscription".to_string()),
+        };
+        if !matches!(schema.types.get(&schema.query), Some(TypeKind::Object(_))) {
+            return Err(format!("Schema has no query root type \"{}\"", schema.query));
+        }
+        Ok(schema)
+    }
+
+    fn fields_of(&self, type_name: &str) -> Option<&HashMap<String, TypeRef>> {
+        match self.types.get(type_name) {
+            Some(TypeKind::Object(fields)) | Some(TypeKind::Interface(fields)) => Some(fields),
+            _ => None,
+        }
+    }
+
+    /// The object type used to answer for an interface or union.
+    fn concrete_type(&self, type_name: &str) -> String {
+        match self.types.get(type_name) {
+            Some(TypeKind::Interface(_)) => self.implementations.get(type_name)
+                .and_then(|impls| impls.first().cloned())
+                .unwrap_or_else(|| type_name.to_string()),
+            Some(TypeKind::Union(members)) => members.first().cloned().unwrap_or_else(|| type_name.to_string()),
+            _ => type_name.to_string(),
+        }
+    }
+
+    fn condition_applies(&self, condition: &str, concrete: &str) -> bool {
+        condition == concrete
+            || self.implementations.get(condition).is_some_and(|impls| impls.iter().any(|i| i == concrete))
+            || matches!(self.types.get(condition), Some(TypeKind::Union(members)) if members.iter().any(|m| m == concrete))
+    }
+
+    fn select(
+        &self,
+        concrete: &str,
+        selection_set: &query::SelectionSet<'_, String>,
+        fragments: &Fragments,
+        out: &mut Map<String, Value>,
+        errors: &mut Vec<Value>,
+    ) {
+        for selection in &selection_set.items {
+            match selection {
+                Selection::Field(field) => {
+                    let key = field.alias.clone().unwrap_or_else(|| field.name.clone());
+                    if field.name == "__typename" {
+                        out.insert(key, json!(concrete));
+                        continue;
+                    }
+                    match self.fields_of(concrete).and_then(|f| f.get(&field.name)) {
+                        Some(ty) => {
+                            let value = self.mock_value(ty, field, fragments, errors);
+                            out.insert(key, value);
+                        }
+                        None => {
+                            errors.push(json!({"message": format!("Cannot query field \"{}\" on type \"{}\".", field.name, concrete)}));
+                            out.insert(key, Value::Null);
+                        }
+                    }
+                }
+                Selection::InlineFragment(fragment) => {
+                    let applies = match &fragment.type_condition {
+                        Some(TypeCondition::On(condition)) => self.condition_applies(condition, concrete),
+                        None => true,
+                    };
+                    if applies {
+                        self.select(concrete, &fragment.selection_set, fragments, out, errors);
+                    }
+                }
+                Selection::FragmentSpread(spread) => match fragments.get(&spread.fragment_name) {
+                    Some(fragment) => {
+                        let TypeCondition::On(condition) = &fragment.type_condition;
+                        if self.condition_applies(condition, concrete) {
+                            self.select(concrete, &fragment.selection_set, fragments, out, errors);
+                        }
+                    }
+                    None => errors.push(json!({"message": format!("Unknown fragment \"{}\".", spread.fragment_name)})),
+                },
+            }
+        }
+    }
+
+    fn mock_value(
+        &self,
+        ty: &TypeRef,
+        field: &query::Field<'_, String>,
+        fragments: &Fragments,
+        errors: &mut Vec<Value>,
+    ) -> Value {
+        match ty {
+            TypeRef::NonNull(inner) => self.mock_value(inner, field, fragments, errors),
+            TypeRef::List(inner) => json!([self.mock_value(inner, field, fragments, errors)]),
+            TypeRef::Named(name) => match (name.as_str(), self.types.get(name)) {
+                ("String", _) => json!(format!("{} string", field.name)),
+                ("ID", _) => json!("1"),
+                ("Int", _) => json!(1),
+                ("Float", _) => json!(1.5),
+                ("Boolean", _) => json!(true),
+                (_, Some(TypeKind::Enum(values))) => values.first().map(|v| json!(v)).unwrap_or(Value::Null),
+                (_, Some(TypeKind::Object(_) | TypeKind::Interface(_) | TypeKind::Union(_))) => {
+                    let concrete = self.concrete_type(name);
+                    let mut out = Map::new();
+                    self.select(&concrete, &field.selection_set, fragments, &mut out, errors);
+                    Value::Object(out)
+                }
+                (_, Some(TypeKind::Scalar)) => json!(format!("{} value", name)),
+                _ => Value::Null,
+            },
+        }
+    }
+}
+
+/// A request that can't be executed at all, which GraphQL over HTTP answers with a 400.
+fn graphql_error(message: String) -> GraphqlResult {
+    GraphqlResult {
+        status: 400,
+        body: json!({"errors": [{"message": message}]}),
+        operation: Value::Null,
+        matched: "GraphQL (invalid request)".to_string(),
+    }
+}
+
+/// Answers a GraphQL request to the GraphQL path once a schema or an override is loaded.
+///
+/// Without a schema, requests no override matches are left to the stubs and proxy.
+pub fn handle(state: &GraphqlState, method: &str, path: &str, body: &[u8]) -> Option<GraphqlResult> {
+    if (state.schema.is_none() && state.overrides.is_empty()) || method != "POST" || path != state.path {
+        return None;
+    }
+
+    let request: GraphqlRequest = match serde_json::from_slice(body) {
+        Ok(r) => r,
+        Err(e) => return Some(graphql_error(format!("Invalid GraphQL request body: {}", e))),
+    };
+    let document = match graphql_parser::parse_query::<String>(&request.query) {
+        Ok(d) => d,
+        Err(e) => return Some(graphql_error(format!("Syntax error: {}", e))),
+    };
+
+    let mut fragments: Fragments = HashMap::new();
+    let mut operations = Vec::new();
+    for definition in &document.definitions {
+        match definition {
+            Definition::Fragment(fragment) => {
+                fragments.insert(fragment.name.clone(), fragment);
+            }
+            Definition::Operation(operation) => operations.push(operation),
+        }
+    }
+
+    let (op_type, op_name, selection_set) = match operations.iter()
+        .map(|op| match op {
+            OperationDefinition::SelectionSet(set) => ("query", None, set),
+            OperationDefinition::Query(q) => ("query", q.name.clone(), &q.selection_set),
+            OperationDefinition::Mutation(m) => ("mutation", m.name.clone(), &m.selection_set),
+            OperationDefinition::Subscription(s) => ("subscription", s.name.clone(), &s.selection_set),
+        })
+        .find(|(_, name, _)| request.operation_name.is_none() || *name == request.operation_name)
+    {
+        Some(op) if request.operation_name.is_some() || operations.len() == 1 => op,
+        _ => return Some(graphql_error("Must provide a single operation or a matching operationName".to_string())),
+    };
+
+    let variables = request.variables.clone().unwrap_or_else(|| json!({}));
+    let root_fields: Vec<String> = selection_set.items.iter().filter_map(|s| match s {
+        Selection::Field(f) => Some(f.name.clone()),
+        _ => None,
+    }).collect();
+    let operation = json!({
+        "type": op_type,
+        "name": op_name,
+        "variables": variables,
+        "root_fields": root_fields
+    });
+
+    let matching_override = state.overrides.iter().find(|o| {
+        o.operation_name.as_ref().is_none_or(|name| Some(name) == op_name.as_ref())
+            && o.variables.as_ref().is_none_or(|expected| json_contains(&variables, expected))
+    });
+    if let Some(o) = matching_override {
+        info!("GraphQL {} {} answered by override", op_type, op_name.as_deref().unwrap_or("(anonymous)"));
+        return Some(GraphqlResult {
+            status: o.status.unwrap_or(200),
+            body: o.response.clone(),
+            operation,
+            matched: format!("GraphQL override {}", o.operation_name.as_deref().unwrap_or("*")),
+        });
+    }
+
+    let schema = state.schema.as_ref()?;
+    let root = match op_type {
+        "mutation" => &schema.mutation,
+        "subscription" => &schema.subscription,
+        _ => &schema.query,
+    };
+    if schema.fields_of(root).is_none() {
+        return Some(graphql_error(format!("Schema does not support {} operations", op_type)));
+    }
+
+    let mut data = Map::new();
+    let mut errors = Vec::new();
+    schema.select(root, selection_set, &fragments, &mut data, &mut errors);
+
+    let body = if errors.is_empty() {
+        json!({"data": data})
+    } else {
+        json!({"data": data, "errors": errors})
+    };
+    Some(GraphqlResult {
+        status: 200,
+        body,
+        operation,
+        matched: "GraphQL (generated)".to_string(),
+    })
+}
+
+pub async fn set_schema(data: web::Data<AppState>, req: web::Json<SchemaRequest>) -> impl Responder {
+    let schema = match Schema::parse(&req.sdl) {
+        Ok(s) => s,
+        Err(e) => return HttpResponse::BadRequest().json(json!({"error": e})),
+    };
+    let mut state = data.graphql.write().unwrap();
+    if let Some(path) = &req.path {
+        state.path = path.clone();
+    }
+    let type_count = schema.types.len();
+    state.schema = Some(schema);
+    state.sdl = Some(req.sdl.clone());
+    info!("Loaded GraphQL schema with {} types at {}", type_count, state.path);
+    HttpResponse::Ok().json(json!({"loaded": true, "path": state.path, "types": type_count}))
+}
+
+pub async fn get_graphql(data: web::Data<AppState>) -> impl Responder {
+    let state = data.graphql.read().unwrap();
+    HttpResponse::Ok().json(json!({
+        "path": state.path,
+        "sdl": state.sdl,
+        "overrides": state.overrides
+    }))
+}
+
+pub async fn clear_graphql(data: web::Data<AppState>) -> impl Responder {
+    *data.graphql.write().unwrap() = GraphqlState::default();
+    HttpResponse::Ok().json(json!({"cleared": true}))
+}
+
+pub async fn add_override(data: web::Data<AppState>, cfg: web::Json<GraphqlOverride>) -> impl Responder {
+    let cfg = cfg.into_inner();
+    info!("Added GraphQL override for {}", cfg.operation_name.as_deref().unwrap_or("*"));
+    data.graphql.write().unwrap().overrides.push(cfg);
+    HttpResponse::Ok().json(json!({"added": true}))
+}
+
+pub async fn clear_overrides(data: web::Data<AppState>) -> impl Responder {
+    data.graphql.write().unwrap().overrides.clear();
+    HttpResponse::Ok().json(json!({"cleared": true}))
+}
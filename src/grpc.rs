@@ -0,0 +1,399 @@
+use actix_web::{web, HttpResponse, Responder};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use chrono::Local;
+use h2::server::SendResponse;
+use h2::RecvStream;
+use log::{info, warn};
+use prost::Message;
+use prost_reflect::{DescriptorPool, DynamicMessage, MethodDescriptor, SerializeOptions};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::ws::json_contains;
+use crate::{AppState, RequestLog, MAX_PAYLOAD_SIZE};
+
+pub const INVALID_ARGUMENT: u32 = 3;
+pub const RESOURCE_EXHAUSTED: u32 = 8;
+pub const UNIMPLEMENTED: u32 = 12;
+pub const INTERNAL: u32 = 13;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GrpcStub {
+    /// Fully qualified (`pkg.Greeter`) or short (`Greeter`) service name.
+    pub service: String,
+    pub method: String,
+    /// Matches when the request message contains these fields.
+    #[serde(default, rename = "match")]
+    pub matcher: Option<Value>,
+    #[serde(default)]
+    pub response: Option<Value>,
+    /// Messages for server-streaming methods, sent after `response`.
+    #[serde(default)]
+    pub stream: Vec<Value>,
+    /// Wait before each streamed message.
+    #[serde(default)]
+    pub delay_ms: u64,
+    /// gRPC status code returned in the trailers.
+    #[serde(default)]
+    pub code: u32,
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+#[derive(Default)]
+pub struct GrpcState {
+    pub pool: DescriptorPool,
+    pub stubs: Vec<GrpcStub>,
+    /// Bumped whenever `pool` is replaced, so a load compiled from an older pool starts over.
+    pub generation: u64,
+}
+
+#[derive(Deserialize)]
+pub struct LoadProtosRequest {
+    pub files: Vec<String>,
+    #[serde(default)]
+    pub includes: Vec<String>,
+}
+
+/// `pool` with `.proto` files (compiled on the fly) or binary descriptor sets added.
+///
+/// `pool` itself is left alone, so a file that fails to load doesn't leave the others half-added.
+pub fn load_protos(pool: &DescriptorPool, files: &[String], includes: &[String]) -> Result<DescriptorPool, String> {
+    let mut pool = pool.clone();
+    let (descriptor_sets, protos): (Vec<&String>, Vec<&String>) = files.iter()
+        .partition(|f| Path::new(f).extension().is_some_and(|e| e != "proto"));
+
+    for file in descriptor_sets {
+        let bytes = std::fs::read(file).map_err(|e| format!("Failed to read {}: {}", file, e))?;
+        pool.decode_file_descriptor_set(bytes.as_slice())
+            .map_err(|e| format!("Invalid descriptor set {}: {}", file, e))?;
+    }
+
+    if !protos.is_empty() {
+        let mut includes: Vec<String> = includes.to_vec();
+        for proto in &protos {
+            let parent = Path::new(proto).parent().map(|p| p.to_string_lossy().to_string()).unwrap_or_default();
+            includes.push(if parent.is_empty() { ".".to_string() } else { parent });
+        }
+        let mut compiler = protox::Compiler::new(&includes).map_err(|e| e.to_string())?;
+        compiler.include_imports(true);
+        compiler.open_files(&protos).map_err(|e| e.to_string())?;
+        pool.add_file_descriptor_set(compiler.file_descriptor_set())
+            .map_err(|e| e.to_string())?;
+    }
+
+    Ok(pool)
+}
+
+fn find_method(pool: &DescriptorPool, path: &str) -> Option<MethodDescriptor> {
+    let (service, method) = path.trim_start_matches('/').split_once('/')?;
+    pool.get_service_by_name(service)?.methods().find(|m| m.name() == method)
+}
+
+fn service_matches(stub_service: &str, full_name: &str) -> bool {
+    stub_service == full_name || full_name.rsplit('.').next() == Some(stub_service)
+}
+
+fn to_json(message: &DynamicMessage) -> Value {
+    let options = SerializeOptions::new().skip_default_fields(false);
+    message.serialize_with_options(serde_json::value::Serializer, &options).unwrap_or(Value::Null)
+}
+
+/// Splits a gRPC body into its length-prefixed messages.
+fn decode_frames(mut body: Bytes) -> Result<Vec<Bytes>, String> {
+    let mut messages = Vec::new();
+    while body.has_remaining() {
+        if body.remaining() < 5 {
+            return Err("Truncated gRPC frame".to_string());
+        }
+        let compressed = body.get_u8();
+        let len = body.get_u32() as usize;
+        if compressed != 0 {
+            return Err("Compressed gRPC messages are not supported".to_string());
+        }
+        if body.remaining() < len {
+            return Err("Truncated gRPC frame".to_string());
+        }
+        messages.push(body.split_to(len));
+    }
+    Ok(messages)
+}
+
+fn encode_frame(message: &DynamicMessage) -> Bytes {
+    let encoded = message.encode_to_vec();
+    let mut frame = BytesMut::with_capacity(encoded.len() + 5);
+    frame.put_u8(0);
+    frame.put_u32(encoded.len() as u32);
+    frame.put_slice(&encoded);
+    frame.freeze()
+}
+
+fn trailers(code: u32, message: Option<&str>) -> http::HeaderMap {
+    let mut trailers = http::HeaderMap::new();
+    trailers.insert("grpc-status", http::HeaderValue::from(code));
+    if let Some(value) = message.and_then(|m| http::HeaderValue::from_str(m).ok()) {
+        trailers.insert("grpc-message", value);
+    }
+    trailers
+}
+
+struct CallOutcome {
+    request: Option<Value>,
+    responses: Vec<Value>,
+    frames: Vec<Bytes>,
+    delay_ms: u64,
+    code: u32,
+    message: Option<String>,
+    matched: Option<String>,
+    server_streaming: bool,
+}
+
+impl CallOutcome {
+    fn error(code: u32, message: String) -> Self {
+        Self { request: None, responses: Vec::new(), frames: Vec::new(), delay_ms: 0, code, message: Some(message), matched: None, server_streaming: false }
+    }
+}
+
+/// Binds the gRPC listener; calls are served on the current runtime until it shuts down.
+pub async fn listen(data: web::Data<AppState>, host: &str, port: u16) -> std::io::Result<()> {
+    let listener = TcpListener::bind((host, port)).await?;
+    info!("gRPC listening on {}:{}", host, port);
+    actix_web::rt::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((socket, _)) => {
+                    actix_web::rt::spawn(serve_connection(data.clone(), socket));
+                }
+                Err(e) => warn!("gRPC accept failed: {}", e),
+            }
+        }
+    });
+    Ok(())
+}
+
+async fn serve_connection(data: web::Data<AppState>, socket: TcpStream) {
+    let mut conn = match h2::server::handshake(socket).await {
+        Ok(conn) => conn,
+        Err(e) => {
+            warn!("gRPC handshake failed: {}", e);
+            return;
+        }
+    };
+    while let Some(result) = conn.accept().await {
+        match result {
+            Ok((request, respond)) => {
+                actix_web::rt::spawn(handle_call(data.clone(), request, respond));
+            }
+            Err(e) => {
+                warn!("gRPC connection error: {}", e);
+                return;
+            }
+        }
+    }
+}
+
+/// Reads a request body of at most [`MAX_PAYLOAD_SIZE`] bytes, like the HTTP listener.
+async fn read_body(body: &mut RecvStream) -> Result<Bytes, (u32, String)> {
+    let mut buffer = BytesMut::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|e| (INTERNAL, format!("Failed to read request: {}", e)))?;
+        let _ = body.flow_control().release_capacity(chunk.len());
+        if buffer.len() + chunk.len() > MAX_PAYLOAD_SIZE {
+            return Err((RESOURCE_EXHAUSTED, format!("Request is larger than {} bytes", MAX_PAYLOAD_SIZE)));
+        }
+        buffer.extend_from_slice(&chunk);
+    }
+    Ok(buffer.freeze())
+}
+
+async fn handle_call(data: web::Data<AppState>, request: http::Request<RecvStream>, mut respond: SendResponse<Bytes>) {
+    let log_id = data.next_log_id.fetch_add(1, Ordering::Relaxed);
+    let timestamp = Local::now().to_rfc3339();
+    let path = request.uri().path().to_string();
+    let request_headers = request.headers().iter()
+        .map(|(k, v)| (k.to_string(), v.to_str().unwrap_or("").to_string()))
+        .collect::<HashMap<_, _>>();
+    let (service, method) = path.trim_start_matches('/').split_once('/').unwrap_or((&path, ""));
+    let (service, method) = (service.to_string(), method.to_string());
+
+    let mut body = request.into_body();
+    let outcome = match read_body(&mut body).await {
+        Ok(bytes) => resolve(&data, &path, bytes),
+        Err((code, message)) => CallOutcome::error(code, message),
+    };
+
+    let (code, message) = (outcome.code, outcome.message.clone());
+    info!("gRPC {} -> status {}", path, code);
+
+    let response = http::Response::builder()
+        .status(200)
+        .header("content-type", "application/grpc")
+        .body(())
+        .unwrap();
+    match respond.send_response(response, false) {
+        Ok(mut stream) => {
+            for (i, frame) in outcome.frames.into_iter().enumerate() {
+                if i > 0 && outcome.delay_ms > 0 {
+                    actix_web::rt::time::sleep(Duration::from_millis(outcome.delay_ms)).await;
+                }
+                if stream.send_data(frame, false).is_err() {
+                    break;
+                }
+            }
+            let _ = stream.send_trailers(trailers(code, message.as_deref()));
+        }
+        Err(e) => warn!("gRPC response failed: {}", e),
+    }
+
+    let response_body = if outcome.server_streaming {
+        Some(Value::Array(outcome.responses.clone()))
+    } else {
+        outcome.responses.first().cloned()
+    };
+    data.logs.push(RequestLog {
+        id: log_id,
+        method: "POST".to_string(),
+        path: path.clone(),
+        request_headers,
+        request_body: outcome.request,
+        status: 200,
+        response_body,
+        response_headers: HashMap::from([("content-type".to_string(), "application/grpc".to_string())]),
+        timestamp,
+        matched_endpoint: outcome.matched,
+        grpc: Some(json!({
+            "service": service,
+            "method": method,
+            "status": code,
+            "message": message
+        })),
+        ..Default::default()
+    });
+}
+
+fn resolve(data: &web::Data<AppState>, path: &str, body: Bytes) -> CallOutcome {
+    let state = data.grpc.read().unwrap();
+    let Some(method) = find_method(&state.pool, path) else {
+        return CallOutcome::error(UNIMPLEMENTED, format!("Unknown method {}", path));
+    };
+
+    let request = match decode_frames(body) {
+        Ok(frames) => {
+            let frame = frames.into_iter().next().unwrap_or_default();
+            match DynamicMessage::decode(method.input(), frame) {
+                Ok(message) => to_json(&message),
+                Err(e) => return CallOutcome::error(INVALID_ARGUMENT, format!("Failed to decode {}: {}", method.input().full_name(), e)),
+            }
+        }
+        Err(e) => return CallOutcome::error(INVALID_ARGUMENT, e),
+    };
+
+    let service = method.parent_service().full_name().to_string();
+    let server_streaming = method.is_server_streaming();
+    let stub = state.stubs.iter().find(|s| {
+        service_matches(&s.service, &service)
+            && s.method == method.name()
+            && s.matcher.as_ref().is_none_or(|m| json_contains(&request, m))
+    });
+
+    match stub {
+        Some(stub) => {
+            let mut responses: Vec<Value> = stub.response.iter().chain(stub.stream.iter()).cloned().collect();
+            if !server_streaming {
+                responses.truncate(1);
+            }
+            let frames = responses.iter()
+                .map(|json| DynamicMessage::deserialize(method.output(), json.clone()).map(|m| encode_frame(&m)))
+                .collect::<Result<Vec<_>, _>>();
+            let frames = match frames {
+                Ok(frames) => frames,
+                Err(e) => return CallOutcome {
+                    request: Some(request),
+                    matched: Some(format!("gRPC {}/{}", stub.service, stub.method)),
+                    ..CallOutcome::error(INTERNAL, format!("Stub response is not a valid {}: {}", method.output().full_name(), e))
+                },
+            };
+            CallOutcome {
+                request: Some(request),
+                responses,
+                frames,
+                delay_ms: stub.delay_ms,
+                code: stub.code,
+                message: stub.message.clone(),
+                matched: Some(format!("gRPC {}/{}", stub.service, stub.method)),
+                server_streaming,
+            }
+        }
+        None => CallOutcome {
+            request: Some(request),
+            server_streaming,
+            ..CallOutcome::error(UNIMPLEMENTED, format!("No stub matches {}", path.trim_start_matches('/')))
+        },
+    }
+}
+
+pub async fn get_grpc(data: web::Data<AppState>) -> impl Responder {
+    let state = data.grpc.read().unwrap();
+    let services: Vec<Value> = state.pool.services().map(|s| json!({
+        "name": s.full_name(),
+        "methods": s.methods().map(|m| json!({
+            "name": m.name(),
+            "input": m.input().full_name(),
+            "output": m.output().full_name(),
+            "client_streaming": m.is_client_streaming(),
+            "server_streaming": m.is_server_streaming()
+        })).collect::<Vec<_>>()
+    })).collect();
+    HttpResponse::Ok().json(json!({"services": services, "stubs": state.stubs}))
+}
+
+/// Compiles off the worker threads and without holding the gRPC lock, which calls need.
+pub async fn load_protos_handler(data: web::Data<AppState>, req: web::Json<LoadProtosRequest>) -> impl Responder {
+    let req = req.into_inner();
+    loop {
+        let (base, generation) = {
+            let state = data.grpc.read().unwrap();
+            (state.pool.clone(), state.generation)
+        };
+        let (files, includes) = (req.files.clone(), req.includes.clone());
+        let pool = match web::block(move || load_protos(&base, &files, &includes)).await {
+            Ok(Ok(pool)) => pool,
+            Ok(Err(e)) => return HttpResponse::BadRequest().json(json!({"error": e})),
+            Err(e) => return HttpResponse::InternalServerError().json(json!({"error": e.to_string()})),
+        };
+        let mut state = data.grpc.write().unwrap();
+        if state.generation != generation {
+            // Another load landed meanwhile; build on top of it instead of dropping it.
+            continue;
+        }
+        state.pool = pool;
+        state.generation += 1;
+        let services = state.pool.services().len();
+        info!("Loaded {} proto file(s), {} service(s) available", req.files.len(), services);
+        return HttpResponse::Ok().json(json!({"loaded": true, "services": services}));
+    }
+}
+
+pub async fn add_stub(data: web::Data<AppState>, stub: web::Json<GrpcStub>) -> impl Responder {
+    let stub = stub.into_inner();
+    let mut state = data.grpc.write().unwrap();
+    let known = state.pool.services()
+        .filter(|s| service_matches(&stub.service, s.full_name()))
+        .any(|s| s.methods().any(|m| m.name() == stub.method));
+    if !known {
+        return HttpResponse::BadRequest().json(json!({"error": format!("Unknown gRPC method {}/{}", stub.service, stub.method)}));
+    }
+    info!("Added gRPC stub for {}/{}", stub.service, stub.method);
+    state.stubs.push(stub);
+    HttpResponse::Ok().json(json!({"added": true}))
+}
+
+pub async fn clear_stubs(data: web::Data<AppState>) -> impl Responder {
+    data.grpc.write().unwrap().stubs.clear();
+    HttpResponse::Ok().json(json!({"cleared": true}))
+}
@@ -1,21 +1,52 @@
-use actix_files::Files;
-use actix_web::{middleware::Logger, guard, web, App, HttpRequest, HttpResponse, HttpServer, Responder};
+use actix_web::{middleware::{self, Logger}, guard, web, App, HttpRequest, HttpResponse, HttpServer, Responder};
 use chrono::Local;
 use env_logger::Builder;
+use futures_util::StreamExt;
 use log::{info, warn, LevelFilter};
 use openapiv3::{OpenAPI, Operation, ReferenceOr, StatusCode};
-use regex::Regex;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::{collections::HashMap, env, fs, sync::Mutex};
-
-#[derive(Serialize, Clone)]
+use std::{collections::HashMap, env, fs, sync::{atomic::{AtomicU64, Ordering}, Mutex, RwLock}};
+
+pub mod auth;
+pub mod body;
+pub mod cache;
+pub mod cors;
+pub mod dashboard;
+pub mod graphql;
+pub mod grpc;
+pub mod logs;
+pub mod negotiate;
+pub mod oauth;
+pub mod resource;
+pub mod ratelimit;
+pub mod router;
+pub mod security;
+pub mod shadow;
+pub mod soap;
+pub mod sse;
+pub mod template;
+pub mod throttle;
+pub mod tls;
+pub mod variants;
+pub mod webhook;
+pub mod ws;
+
+use body::{BodyCapture, FormCapture};
+
+use cache::{CacheConfig, Lookup, ProxyCache};
+use shadow::{DriftSummary, ResponseDiff, ResponseSnapshot, ShadowMode};
+
+#[derive(Serialize, Clone, Default)]
 pub struct RequestLog {
+    pub id: u64,
     pub method: String,
     pub path: String,
     pub request_headers: HashMap<String, String>,
     pub query: String,
     pub request_body: Option<Value>,
+    pub request_body_raw: Option<BodyCapture>,
+    pub request_form: Option<FormCapture>,
 
     pub status: u16,
     pub response_body: Option<Value>,
@@ -23,21 +54,81 @@ pub struct RequestLog {
 
     pub timestamp: String,
     pub matched_endpoint: Option<String>,
+    pub path_params: Option<HashMap<String, String>>,
+    pub variant: Option<variants::VariantChoice>,
+    pub rate_limit: Option<ratelimit::RateLimitStatus>,
     pub proxied_to: Option<String>,
+    pub shadow_diff: Option<ResponseDiff>,
+    pub cache: Option<String>,
+    pub response_body_raw: Option<BodyCapture>,
+    pub ws_frames: Option<Vec<ws::WsFrameLog>>,
+    pub callbacks: Option<Vec<webhook::CallbackAttempt>>,
+    pub graphql: Option<Value>,
+    pub grpc: Option<Value>,
+    pub soap: Option<Value>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct DynamicEndpoint {
     pub response: Value,
     pub status: u16,
     pub headers: Option<HashMap<String, String>>,
     pub proxy_url: Option<String>,
+    pub shadow: Option<ShadowMode>,
+    pub sse: Option<sse::SseConfig>,
+    pub callbacks: Vec<webhook::WebhookConfig>,
+    pub variants: Option<variants::Variants>,
+    pub rate_limit: Option<ratelimit::RateLimiter>,
+    pub throttle: Option<throttle::ThrottleConfig>,
+    /// Reject requests without a valid bearer token from the mock OAuth provider.
+    pub require_auth: bool,
+    /// Fill `{$...}` runtime expressions in the response from the request.
+    pub template: bool,
+    /// Credentials required by the OpenAPI operation the endpoint was imported from.
+    pub security: Option<security::SecurityConfig>,
+    /// Sends the body in this encoding whatever the client's `Accept-Encoding`.
+    pub encoding: Option<negotiate::Encoding>,
+    /// Alternatives to the JSON `response`, keyed by media type and picked by `Accept`.
+    pub representations: HashMap<String, Value>,
 }
 
 pub struct AppState {
-    pub dynamic: Mutex<HashMap<(String, String), DynamicEndpoint>>,
-    pub logs: Mutex<Vec<RequestLog>>,
-    pub default_proxy_url: Mutex<Option<String>>,
+    pub dynamic: RwLock<HashMap<(String, String), DynamicEndpoint>>,
+    /// Compiled from `dynamic`'s keys; see [`AppState::rebuild_router`].
+    pub router: RwLock<router::Router>,
+    pub logs: logs::LogStore,
+    pub default_proxy_url: RwLock<Option<String>>,
+    pub next_log_id: AtomicU64,
+    pub drift: Mutex<HashMap<String, DriftSummary>>,
+    pub proxy_cache: RwLock<ProxyCache>,
+    pub ws_endpoints: Mutex<HashMap<String, ws::WsEndpointConfig>>,
+    /// Compiled from `ws_endpoints`' paths, like `router`.
+    pub ws_router: RwLock<router::Router>,
+    pub sse_clients: Mutex<Vec<sse::SseClient>>,
+    pub graphql: RwLock<graphql::GraphqlState>,
+    pub grpc: RwLock<grpc::GrpcState>,
+    pub soap: RwLock<Vec<soap::SoapStub>>,
+    /// Locked one at a time, so requests to other resources and to stubs don't wait.
+    pub resources: RwLock<HashMap<String, Mutex<resource::Resource>>>,
+    /// Applied to all mocked traffic, before any endpoint's own policy.
+    pub rate_limit: RwLock<Option<ratelimit::RateLimiter>>,
+    /// Slows all mocked traffic whose endpoint has no throttle of its own.
+    pub throttle: RwLock<Option<throttle::ThrottleConfig>>,
+    pub oauth: RwLock<oauth::OAuthProvider>,
+    pub admin_auth: auth::AdminAuth,
+    /// `None` when CORS is turned off.
+    pub cors: Option<cors::CorsPolicy>,
+}
+
+impl AppState {
+    /// Recompiles the router after `dynamic` changed; call while still holding its lock.
+    pub fn rebuild_router(&self, endpoints: &HashMap<(String, String), DynamicEndpoint>) {
+        *self.router.write().unwrap() = endpoint_router(endpoints);
+    }
+}
+
+fn endpoint_router(endpoints: &HashMap<(String, String), DynamicEndpoint>) -> router::Router {
+    router::Router::build(endpoints.keys().map(|(method, path)| (method.as_str(), path.as_str())))
 }
 
 #[derive(Debug, Clone)]
@@ -45,6 +136,51 @@ pub struct ServerConfig {
     pub host: String,
     pub port: u16,
     pub default_proxy_url: Option<String>,
+    pub proxy_cache_ttl: Option<u64>,
+    pub grpc_port: Option<u16>,
+    pub proto_files: Vec<String>,
+    /// Bearer token required on the admin API.
+    pub admin_token: Option<String>,
+    /// `user:password` accepted via basic auth on the admin API.
+    pub admin_basic_auth: Option<String>,
+    /// Serve the admin API and dashboard on a separate listener instead of `port`.
+    pub admin_host: Option<String>,
+    pub admin_port: Option<u16>,
+    /// Admin API prefix (default `/__mock`).
+    pub admin_prefix: Option<String>,
+    /// Where the dashboard is mounted (default `/`).
+    pub dashboard_path: Option<String>,
+    pub no_dashboard: bool,
+    /// Serve the dashboard from this directory instead of the embedded build.
+    pub ui_dir: Option<String>,
+    /// Also serve mocked traffic over HTTPS on this port.
+    pub https_port: Option<u16>,
+    /// PEM certificate chain and key; without them a local CA and certificate are generated.
+    pub tls_cert: Option<String>,
+    pub tls_key: Option<String>,
+    /// Names and IPs the generated certificate covers (default localhost, 127.0.0.1, ::1).
+    pub tls_hostnames: Vec<String>,
+    /// Where generated certificates are written (default `.rustmock/certs`).
+    pub tls_dir: Option<String>,
+    /// Request log entries kept, oldest dropped first (default 10000).
+    pub max_logs: Option<usize>,
+    /// Origins given CORS access, exact or like `https://*.example.com` (`*` for any);
+    /// CORS is off when empty.
+    pub cors_origins: Vec<String>,
+    /// Methods and request headers preflights may ask for; whatever they ask for when empty.
+    pub cors_methods: Vec<String>,
+    pub cors_headers: Vec<String>,
+    pub cors_credentials: bool,
+    /// Seconds browsers may cache a preflight answer.
+    pub cors_max_age: Option<u64>,
+    /// OpenAPI spec whose operations are imported at startup.
+    pub openapi_file: Option<String>,
+    /// Enforce the imported spec's security requirements.
+    pub enforce_security: bool,
+    /// Serve the mock OAuth2/OIDC provider from startup.
+    pub oauth: bool,
+    /// Development mode: CORS allows [`cors::DEV_ORIGINS`] when `cors_origins` is empty.
+    pub dev: bool,
 }
 
 impl Default for ServerConfig {
@@ -53,11 +189,37 @@ impl Default for ServerConfig {
             host: "0.0.0.0".to_string(),
             port: 8090,
             default_proxy_url: None,
+            proxy_cache_ttl: None,
+            grpc_port: None,
+            proto_files: Vec::new(),
+            admin_token: None,
+            admin_basic_auth: None,
+            admin_host: None,
+            admin_port: None,
+            admin_prefix: None,
+            dashboard_path: None,
+            no_dashboard: false,
+            ui_dir: None,
+            https_port: None,
+            tls_cert: None,
+            tls_key: None,
+            tls_hostnames: Vec::new(),
+            tls_dir: None,
+            max_logs: None,
+            cors_origins: Vec::new(),
+            cors_methods: Vec::new(),
+            cors_headers: Vec::new(),
+            cors_credentials: false,
+            cors_max_age: None,
+            openapi_file: None,
+            enforce_security: false,
+            oauth: false,
+            dev: false,
         }
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Default)]
 pub struct EndpointConfig {
     pub method: String,
     pub path: String,
@@ -65,6 +227,28 @@ pub struct EndpointConfig {
     pub status: Option<u16>,
     pub headers: Option<HashMap<String, String>>,
     pub proxy_url: Option<String>,
+    #[serde(default)]
+    pub shadow: Option<ShadowMode>,
+    #[serde(default)]
+    pub sse: Option<sse::SseConfig>,
+    #[serde(default)]
+    pub callbacks: Vec<webhook::WebhookConfig>,
+    #[serde(default)]
+    pub variants: Option<variants::VariantsConfig>,
+    #[serde(default)]
+    pub rate_limit: Option<ratelimit::RateLimitConfig>,
+    #[serde(default)]
+    pub throttle: Option<throttle::ThrottleConfig>,
+    #[serde(default)]
+    pub require_auth: bool,
+    #[serde(default)]
+    pub template: bool,
+    #[serde(default)]
+    pub security: Option<security::SecurityConfig>,
+    #[serde(default)]
+    pub encoding: Option<negotiate::Encoding>,
+    #[serde(default)]
+    pub representations: HashMap<String, Value>,
 }
 
 #[derive(Deserialize)]
@@ -83,9 +267,31 @@ pub struct UpdateConfig {
     pub status: Option<u16>,
     pub headers: Option<HashMap<String, String>>,
     pub proxy_url: Option<String>,
+    #[serde(default)]
+    pub shadow: Option<ShadowMode>,
+    #[serde(default)]
+    pub sse: Option<sse::SseConfig>,
+    #[serde(default)]
+    pub callbacks: Vec<webhook::WebhookConfig>,
+    #[serde(default)]
+    pub variants: Option<variants::VariantsConfig>,
+    #[serde(default)]
+    pub rate_limit: Option<ratelimit::RateLimitConfig>,
+    #[serde(default)]
+    pub throttle: Option<throttle::ThrottleConfig>,
+    #[serde(default)]
+    pub require_auth: bool,
+    #[serde(default)]
+    pub template: bool,
+    #[serde(default)]
+    pub security: Option<security::SecurityConfig>,
+    #[serde(default)]
+    pub encoding: Option<negotiate::Encoding>,
+    #[serde(default)]
+    pub representations: HashMap<String, Value>,
 }
 
-fn extract_example_response_for_status(op: &Operation, status: u16) -> Option<Value> {
+pub(crate) fn extract_example_response_for_status(op: &Operation, status: u16) -> Option<Value> {
     op.responses.responses.get(&StatusCode::Code(status))
         .and_then(|item| match item {
             ReferenceOr::Item(resp) => resp.content.get("application/json"),
@@ -94,37 +300,106 @@ fn extract_example_response_for_status(op: &Operation, status: u16) -> Option<Va
         .and_then(|media| media.example.clone())
 }
 
-fn matches_path_template(template: &str, actual_path: &str) -> bool {
-    let regex_pattern = template.replace('{', "(?P<").replace('}', ">[^/]+)");
-    match Regex::new(&format!("^{}$", regex_pattern)) {
-        Ok(re) => re.is_match(actual_path),
-        Err(_) => false,
-    }
+/// Examples of the operation's other media types for `status`, served when a client asks for them.
+fn extract_example_representations(op: &Operation, status: u16) -> HashMap<String, Value> {
+    let Some(ReferenceOr::Item(resp)) = op.responses.responses.get(&StatusCode::Code(status)) else { return HashMap::new() };
+    resp.content.iter()
+        .filter(|(media_type, _)| media_type.as_str() != negotiate::JSON && negotiate::is_concrete(media_type))
+        .filter_map(|(media_type, media)| Some((media_type.clone(), media.example.clone()?)))
+        .collect()
+}
+
+pub(crate) fn matches_path_template(template: &str, actual_path: &str) -> bool {
+    router::PathPattern::parse(template).matches(actual_path)
 }
 
 pub async fn add_endpoint(data: web::Data<AppState>, cfg: web::Json<EndpointConfig>) -> impl Responder {
+    if let Err(e) = router::validate(&cfg.path) {
+        return HttpResponse::BadRequest().json(json!({"added": false, "error": e}));
+    }
+    let variants = match cfg.variants.clone().map(variants::Variants::new).transpose() {
+        Ok(variants) => variants,
+        Err(e) => return HttpResponse::BadRequest().json(json!({"added": false, "error": e})),
+    };
+    let rate_limit = match cfg.rate_limit.clone().map(ratelimit::RateLimiter::new).transpose() {
+        Ok(rate_limit) => rate_limit,
+        Err(e) => return HttpResponse::BadRequest().json(json!({"added": false, "error": e})),
+    };
+    let throttle = match cfg.throttle.clone().map(throttle::ThrottleConfig::validate).transpose() {
+        Ok(throttle) => throttle,
+        Err(e) => return HttpResponse::BadRequest().json(json!({"added": false, "error": e})),
+    };
+    let security = match cfg.security.clone().map(security::SecurityConfig::validate).transpose() {
+        Ok(security) => security,
+        Err(e) => return HttpResponse::BadRequest().json(json!({"added": false, "error": e})),
+    };
+    if let Err(e) = negotiate::validate_representations(&cfg.representations) {
+        return HttpResponse::BadRequest().json(json!({"added": false, "error": e}));
+    }
+    if let Err(e) = webhook::validate(&cfg.callbacks) {
+        return HttpResponse::BadRequest().json(json!({"added": false, "error": e}));
+    }
     let status = cfg.status.unwrap_or(200);
     let ep = DynamicEndpoint {
         response: cfg.response.clone(),
         status,
         headers: cfg.headers.clone(),
         proxy_url: cfg.proxy_url.clone(),
+        shadow: cfg.shadow,
+        sse: cfg.sse.clone(),
+        callbacks: cfg.callbacks.clone(),
+        variants,
+        rate_limit,
+        throttle,
+        require_auth: cfg.require_auth,
+        template: cfg.template,
+        security,
+        encoding: cfg.encoding,
+        representations: cfg.representations.clone(),
     };
-    data.dynamic.lock().unwrap().insert((cfg.method.clone(), cfg.path.clone()), ep);
+    let mut dyn_map = data.dynamic.write().unwrap();
+    dyn_map.insert((cfg.method.clone(), cfg.path.clone()), ep);
+    data.rebuild_router(&dyn_map);
     info!("Added endpoint {} {}", cfg.method, cfg.path);
     HttpResponse::Ok().json(json!({"added": true}))
 }
 
 pub async fn remove_endpoint(data: web::Data<AppState>, cfg: web::Json<RemoveConfig>) -> impl Responder {
-    let mut dyn_map = data.dynamic.lock().unwrap();
+    let mut dyn_map = data.dynamic.write().unwrap();
     let key = (cfg.method.clone(), cfg.path.clone());
     let removed = dyn_map.remove(&key).is_some();
+    data.rebuild_router(&dyn_map);
     info!("Removed endpoint {} {}: {}", cfg.method, cfg.path, removed);
     HttpResponse::Ok().json(json!({"removed": removed}))
 }
 
 pub async fn update_endpoint(data: web::Data<AppState>, cfg: web::Json<UpdateConfig>) -> impl Responder {
-    let mut dyn_map = data.dynamic.lock().unwrap();
+    if let Err(e) = router::validate(&cfg.path) {
+        return HttpResponse::BadRequest().json(json!({"updated": false, "error": e}));
+    }
+    let variants = match cfg.variants.clone().map(variants::Variants::new).transpose() {
+        Ok(variants) => variants,
+        Err(e) => return HttpResponse::BadRequest().json(json!({"updated": false, "error": e})),
+    };
+    let rate_limit = match cfg.rate_limit.clone().map(ratelimit::RateLimiter::new).transpose() {
+        Ok(rate_limit) => rate_limit,
+        Err(e) => return HttpResponse::BadRequest().json(json!({"updated": false, "error": e})),
+    };
+    let throttle = match cfg.throttle.clone().map(throttle::ThrottleConfig::validate).transpose() {
+        Ok(throttle) => throttle,
+        Err(e) => return HttpResponse::BadRequest().json(json!({"updated": false, "error": e})),
+    };
+    let security = match cfg.security.clone().map(security::SecurityConfig::validate).transpose() {
+        Ok(security) => security,
+        Err(e) => return HttpResponse::BadRequest().json(json!({"updated": false, "error": e})),
+    };
+    if let Err(e) = negotiate::validate_representations(&cfg.representations) {
+        return HttpResponse::BadRequest().json(json!({"updated": false, "error": e}));
+    }
+    if let Err(e) = webhook::validate(&cfg.callbacks) {
+        return HttpResponse::BadRequest().json(json!({"updated": false, "error": e}));
+    }
+    let mut dyn_map = data.dynamic.write().unwrap();
 
     let old_key = (cfg.old_method.clone(), cfg.old_path.clone());
     let new_key = (cfg.method.clone(), cfg.path.clone());
@@ -151,8 +426,20 @@ pub async fn update_endpoint(data: web::Data<AppState>, cfg: web::Json<UpdateCon
         status,
         headers: cfg.headers.clone(),
         proxy_url: cfg.proxy_url.clone(),
+        shadow: cfg.shadow,
+        sse: cfg.sse.clone(),
+        callbacks: cfg.callbacks.clone(),
+        variants,
+        rate_limit,
+        throttle,
+        require_auth: cfg.require_auth,
+        template: cfg.template,
+        security,
+        encoding: cfg.encoding,
+        representations: cfg.representations.clone(),
     };
     dyn_map.insert(new_key.clone(), ep);
+    data.rebuild_router(&dyn_map);
 
     info!("Updated endpoint {} {} -> {} {}",
           cfg.old_method, cfg.old_path, cfg.method, cfg.path);
@@ -162,7 +449,7 @@ pub async fn update_endpoint(data: web::Data<AppState>, cfg: web::Json<UpdateCon
 
 pub async fn get_config(data: web::Data<AppState>) -> impl Responder {
     let mut list = Vec::new();
-    let dyn_map = data.dynamic.lock().unwrap();
+    let dyn_map = data.dynamic.read().unwrap();
     for ((m, p), ep) in dyn_map.iter() {
         list.push(json!({
             "method": m,
@@ -170,19 +457,30 @@ pub async fn get_config(data: web::Data<AppState>) -> impl Responder {
             "response": ep.response,
             "status": ep.status,
             "headers": ep.headers,
-            "proxy_url": ep.proxy_url
+            "proxy_url": ep.proxy_url,
+            "shadow": ep.shadow,
+            "sse": ep.sse,
+            "callbacks": ep.callbacks,
+            "variants": ep.variants.as_ref().map(|v| &v.config),
+            "rate_limit": ep.rate_limit.as_ref().map(|l| &l.config),
+            "throttle": ep.throttle,
+            "require_auth": ep.require_auth,
+            "template": ep.template,
+            "security": ep.security,
+            "encoding": ep.encoding,
+            "representations": ep.representations
         }));
     }
     HttpResponse::Ok().json(list)
 }
 
 pub async fn get_logs(data: web::Data<AppState>) -> impl Responder {
-    let logs = data.logs.lock().unwrap();
-    HttpResponse::Ok().json(&*logs)
+    let logs = data.logs.snapshot().await;
+    HttpResponse::Ok().json(logs.iter().map(|log| log.as_ref()).collect::<Vec<_>>())
 }
 
 pub async fn clear_logs(data: web::Data<AppState>) -> impl Responder {
-    data.logs.lock().unwrap().clear();
+    data.logs.clear();
     HttpResponse::Ok().json(json!({"cleared": true}))
 }
 
@@ -192,7 +490,7 @@ pub struct ProxyConfig {
 }
 
 pub async fn get_proxy(data: web::Data<AppState>) -> impl Responder {
-    let proxy_url = data.default_proxy_url.lock().unwrap().clone();
+    let proxy_url = data.default_proxy_url.read().unwrap().clone();
     HttpResponse::Ok().json(json!({
         "proxy_url": proxy_url,
         "enabled": proxy_url.is_some()
@@ -202,18 +500,18 @@ pub async fn get_proxy(data: web::Data<AppState>) -> impl Responder {
 pub async fn set_proxy(data: web::Data<AppState>, cfg: web::Json<ProxyConfig>) -> impl Responder {
     let url = cfg.url.trim().to_string();
     if url.is_empty() {
-        *data.default_proxy_url.lock().unwrap() = None;
+        *data.default_proxy_url.write().unwrap() = None;
         info!("Disabled default proxy");
         HttpResponse::Ok().json(json!({"proxy_url": null, "enabled": false}))
     } else {
-        *data.default_proxy_url.lock().unwrap() = Some(url.clone());
+        *data.default_proxy_url.write().unwrap() = Some(url.clone());
         info!("Set default proxy URL to: {}", url);
         HttpResponse::Ok().json(json!({"proxy_url": url, "enabled": true}))
     }
 }
 
 pub async fn delete_proxy(data: web::Data<AppState>) -> impl Responder {
-    *data.default_proxy_url.lock().unwrap() = None;
+    *data.default_proxy_url.write().unwrap() = None;
     info!("Deleted default proxy");
     HttpResponse::Ok().json(json!({"deleted": true}))
 }
@@ -221,6 +519,12 @@ pub async fn delete_proxy(data: web::Data<AppState>) -> impl Responder {
 #[derive(Deserialize)]
 pub struct ImportRequest {
     pub openapi_spec: Value,
+    /// Turn list/create + get-by-id path pairs into stateful resources instead of static stubs.
+    #[serde(default)]
+    pub infer_resources: bool,
+    /// Reject requests lacking the credentials in each operation's `security` requirements.
+    #[serde(default)]
+    pub enforce_security: bool,
 }
 
 pub fn load_openapi_from_file(path: &std::path::Path) -> Result<OpenAPI, String> {
@@ -243,6 +547,7 @@ pub fn load_openapi_from_file(path: &std::path::Path) -> Result<OpenAPI, String>
 pub fn import_openapi_spec(
     spec: &OpenAPI,
     dyn_map: &mut HashMap<(String, String), DynamicEndpoint>,
+    enforce_security: bool,
 ) -> (usize, Vec<Value>) {
     let mut imported_count = 0;
     let mut endpoints = Vec::new();
@@ -278,7 +583,10 @@ pub fn import_openapi_spec(
                         headers: Some(HashMap::from([
                             ("Content-Type".to_string(), "application/json".to_string()),
                         ])),
-                        proxy_url: None,
+                        callbacks: webhook::from_openapi(op),
+                        security: enforce_security.then(|| security::SecurityConfig::from_openapi(spec, op)).flatten(),
+                        representations: extract_example_representations(op, status),
+                        ..Default::default()
                     };
 
                     dyn_map.insert((method.to_string(), path.clone()), endpoint);
@@ -307,20 +615,38 @@ pub async fn import_openapi(data: web::Data<AppState>, req: web::Json<ImportRequ
         }
     };
 
-    let mut dyn_map = data.dynamic.lock().unwrap();
-    let (imported_count, endpoints) = import_openapi_spec(&spec, &mut dyn_map);
+    let mut dyn_map = data.dynamic.write().unwrap();
+    let (imported_count, endpoints) = import_openapi_spec(&spec, &mut dyn_map, req.enforce_security);
+
+    let mut resource_paths = Vec::new();
+    if req.infer_resources {
+        let mut resources = data.resources.write().unwrap();
+        for (cfg, item_path) in resource::infer_from_openapi(&spec) {
+            for method in ["GET", "POST"] {
+                dyn_map.remove(&(method.to_string(), cfg.path.clone()));
+            }
+            for method in ["GET", "PUT", "PATCH", "DELETE"] {
+                dyn_map.remove(&(method.to_string(), item_path.clone()));
+            }
+            info!("Inferred resource {} from {}", cfg.path, item_path);
+            resource_paths.push(cfg.path.clone());
+            resources.insert(cfg.path.clone(), Mutex::new(resource::Resource::new(cfg)));
+        }
+    }
+    data.rebuild_router(&dyn_map);
 
     HttpResponse::Ok().json(json!({
         "imported": true,
         "count": imported_count,
-        "endpoints": endpoints
+        "endpoints": endpoints,
+        "resources": resource_paths
     }))
 }
 
 pub async fn export_openapi(data: web::Data<AppState>) -> impl Responder {
     let mut paths_map = serde_json::Map::new();
 
-    let dyn_map = data.dynamic.lock().unwrap();
+    let dyn_map = data.dynamic.read().unwrap();
     for ((method, path), endpoint) in dyn_map.iter() {
         if !paths_map.contains_key(path) {
             paths_map.insert(path.clone(), json!({}));
@@ -379,22 +705,39 @@ pub async fn export_openapi(data: web::Data<AppState>) -> impl Responder {
         .json(openapi_spec)
 }
 
-async fn forward_to_proxy(
+const PROXY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+const MAX_BUFFERED_PROXY_BODY: u64 = 1024 * 1024;
+/// Largest request body mocked routes accept, over HTTP and gRPC alike.
+pub const MAX_PAYLOAD_SIZE: usize = 256 * 1024;
+
+pub enum ProxyBody {
+    Json(Option<Value>),
+    Stream(reqwest::Response),
+}
+
+type ProxyResult = Result<(u16, ProxyBody, HashMap<String, String>), String>;
+
+fn proxy_target_url(proxy_url: &str, req: &HttpRequest, query: &str) -> String {
+    if query.is_empty() {
+        format!("{}{}", proxy_url.trim_end_matches('/'), req.path())
+    } else {
+        format!("{}{}?{}", proxy_url.trim_end_matches('/'), req.path(), query)
+    }
+}
+
+async fn send_to_proxy(
     proxy_url: &str,
     req: &HttpRequest,
     body: &web::Bytes,
     query: &str,
-) -> Result<(u16, Option<Value>, HashMap<String, String>), String> {
+    extra_headers: &[(&str, &str)],
+) -> Result<reqwest::Response, String> {
     let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(30))
+        .connect_timeout(PROXY_TIMEOUT)
         .build()
         .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
 
-    let full_url = if query.is_empty() {
-        format!("{}{}", proxy_url.trim_end_matches('/'), req.path())
-    } else {
-        format!("{}{}?{}", proxy_url.trim_end_matches('/'), req.path(), query)
-    };
+    let full_url = proxy_target_url(proxy_url, req, query);
 
     info!("Proxying {} {} to {}", req.method(), req.path(), full_url);
 
@@ -412,6 +755,14 @@ async fn forward_to_proxy(
             }
         }
     }
+    for (key, value) in extra_headers {
+        if let (Ok(header_name), Ok(header_value)) = (
+            reqwest::header::HeaderName::from_bytes(key.as_bytes()),
+            reqwest::header::HeaderValue::from_str(value),
+        ) {
+            headers.insert(header_name, header_value);
+        }
+    }
 
     let method = match req.method().as_str() {
         "GET" => reqwest::Method::GET,
@@ -424,91 +775,541 @@ async fn forward_to_proxy(
         _ => reqwest::Method::GET,
     };
 
-    let response = client
+    // Only waiting for the response head is bounded; a streamed body may stay open indefinitely.
+    let send = client
         .request(method, &full_url)
         .headers(headers)
         .body(body.to_vec())
-        .send()
+        .send();
+
+    actix_web::rt::time::timeout(PROXY_TIMEOUT, send)
         .await
-        .map_err(|e| format!("Proxy request failed: {}", e))?;
+        .map_err(|_| "Proxy request failed: timed out waiting for upstream".to_string())?
+        .map_err(|e| format!("Proxy request failed: {}", e))
+}
 
+/// Small JSON responses are buffered so they can be logged, cached and diffed;
+/// everything else (downloads, chunked streams, SSE) is piped through as it arrives.
+fn should_stream(req: &HttpRequest, response: &reqwest::Response) -> bool {
     let status = response.status().as_u16();
+    if req.method() == actix_web::http::Method::HEAD || status == 204 || status == 304 {
+        return false;
+    }
+    let content_type = response.headers().get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("")
+        .to_lowercase();
+    let is_json = content_type.starts_with("application/json") || content_type.contains("+json");
+    match response.content_length() {
+        Some(len) => !is_json || len > MAX_BUFFERED_PROXY_BODY,
+        None => !is_json,
+    }
+}
 
-    let mut response_headers = HashMap::new();
+async fn fetch_from_proxy(
+    proxy_url: &str,
+    req: &HttpRequest,
+    body: &web::Bytes,
+    query: &str,
+    extra_headers: &[(&str, &str)],
+) -> ProxyResult {
+    let response = send_to_proxy(proxy_url, req, body, query, extra_headers).await?;
+
+    let status = response.status().as_u16();
+
+    let mut response_headers: HashMap<String, String> = HashMap::new();
     for (key, value) in response.headers() {
         if let Ok(val_str) = value.to_str() {
-            response_headers.insert(key.to_string(), val_str.to_string());
+            // Repeated list headers such as `Vary` combine into one; `Set-Cookie` can't.
+            match response_headers.get_mut(key.as_str()) {
+                Some(existing) if key != reqwest::header::SET_COOKIE => {
+                    existing.push_str(", ");
+                    existing.push_str(val_str);
+                }
+                _ => {
+                    response_headers.insert(key.to_string(), val_str.to_string());
+                }
+            }
         }
     }
 
+    if should_stream(req, &response) {
+        return Ok((status, ProxyBody::Stream(response), response_headers));
+    }
+
     let response_body = if status == 204 || status == 304 {
         None
     } else {
-        match response.bytes().await {
-            Ok(bytes) if bytes.is_empty() => None,
-            Ok(bytes) => {
+        match actix_web::rt::time::timeout(PROXY_TIMEOUT, response.bytes()).await {
+            Ok(Ok(bytes)) if bytes.is_empty() => None,
+            Ok(Ok(bytes)) => {
                 serde_json::from_slice::<Value>(&bytes).ok()
             }
-            Err(_) => None,
+            _ => None,
         }
     };
 
-    Ok((status, response_body, response_headers))
+    Ok((status, ProxyBody::Json(response_body), response_headers))
+}
+
+async fn forward_with_cache(
+    data: &AppState,
+    proxy_url: &str,
+    req: &HttpRequest,
+    body: &web::Bytes,
+    query: &str,
+) -> (ProxyResult, Option<String>) {
+    let method = req.method().as_str().to_uppercase();
+    let cacheable = {
+        let cache = data.proxy_cache.read().unwrap();
+        cache.config.enabled && cache.accepts(req)
+    };
+    if method != "GET" || !cacheable {
+        return (fetch_from_proxy(proxy_url, req, body, query, &[]).await, None);
+    }
+
+    let url = proxy_target_url(proxy_url, req, query);
+    let (key, lookup) = {
+        let cache = data.proxy_cache.read().unwrap();
+        let key = cache.key(&method, &url, req);
+        let lookup = cache.lookup(&key);
+        (key, lookup)
+    };
+
+    match lookup {
+        Lookup::Fresh(status, body, headers) => {
+            info!("Serving {} from proxy cache", url);
+            (Ok((status, ProxyBody::Json(body), headers)), Some("HIT".to_string()))
+        }
+        Lookup::Revalidate(etag) => {
+            let result = fetch_from_proxy(proxy_url, req, body, query, &[("if-none-match", &etag)]).await;
+            match result {
+                Ok((304, _, headers)) => {
+                    let refreshed = data.proxy_cache.write().unwrap().refresh(&key, &headers);
+                    match refreshed {
+                        Some((status, body, headers)) => (Ok((status, ProxyBody::Json(body), headers)), Some("REVALIDATED".to_string())),
+                        // Evicted while revalidating: the client's request was unconditional, so fetch it in full.
+                        None => fetch_and_store(data, proxy_url, req, body, query, &method, &url).await,
+                    }
+                }
+                Ok((status, ProxyBody::Json(body), headers)) => {
+                    data.proxy_cache.write().unwrap().store(req, &method, &url, status, &body, &headers);
+                    (Ok((status, ProxyBody::Json(body), headers)), Some("MISS".to_string()))
+                }
+                other => (other, Some("MISS".to_string())),
+            }
+        }
+        Lookup::Expired => {
+            data.proxy_cache.write().unwrap().entries.remove(&key);
+            fetch_and_store(data, proxy_url, req, body, query, &method, &url).await
+        }
+        Lookup::Miss => fetch_and_store(data, proxy_url, req, body, query, &method, &url).await,
+    }
+}
+
+/// Fetches `url` unconditionally and caches a buffered response.
+async fn fetch_and_store(
+    data: &AppState,
+    proxy_url: &str,
+    req: &HttpRequest,
+    body: &web::Bytes,
+    query: &str,
+    method: &str,
+    url: &str,
+) -> (ProxyResult, Option<String>) {
+    let result = fetch_from_proxy(proxy_url, req, body, query, &[]).await;
+    if let Ok((status, ProxyBody::Json(body), headers)) = &result {
+        data.proxy_cache.write().unwrap().store(req, method, url, *status, body, headers);
+    }
+    (result, Some("MISS".to_string()))
+}
+
+/// Logs the first `MAX_LOGGED_BODY` bytes of a streamed response once the stream ends.
+pub(crate) struct StreamCapture {
+    pub(crate) data: web::Data<AppState>,
+    pub(crate) log_id: u64,
+    pub(crate) prefix: Vec<u8>,
+    pub(crate) size: usize,
+}
+
+impl StreamCapture {
+    pub(crate) fn push(&mut self, chunk: &[u8]) {
+        let room = body::MAX_LOGGED_BODY.saturating_sub(self.prefix.len());
+        self.prefix.extend_from_slice(&chunk[..chunk.len().min(room)]);
+        self.size += chunk.len();
+    }
+}
+
+impl Drop for StreamCapture {
+    fn drop(&mut self) {
+        let capture = BodyCapture::new(&self.prefix, self.size);
+        self.data.logs.update(self.log_id, move |log| log.response_body_raw = Some(capture));
+    }
+}
+
+fn streamed_proxy_response(
+    data: web::Data<AppState>,
+    log_id: u64,
+    status: u16,
+    headers: HashMap<String, String>,
+    upstream: reqwest::Response,
+    cache_status: Option<&str>,
+) -> HttpResponse {
+    let mut builder = HttpResponse::build(
+        actix_web::http::StatusCode::from_u16(status).unwrap()
+    );
+    for (k, v) in headers {
+        if !matches!(k.as_str(), "content-length" | "transfer-encoding" | "connection") {
+            builder.insert_header((k.as_str(), v.as_str()));
+        }
+    }
+    if let Some(cache_status) = cache_status {
+        builder.insert_header(("x-mock-cache", cache_status));
+    }
+
+    let mut capture = StreamCapture { data, log_id, prefix: Vec::new(), size: 0 };
+    builder.streaming(upstream.bytes_stream().map(move |chunk| {
+        if let Ok(bytes) = &chunk {
+            capture.push(bytes);
+        }
+        chunk.map_err(actix_web::error::ErrorBadGateway)
+    }))
+}
+
+fn proxy_response(status: u16, headers: HashMap<String, String>, body: Option<Value>, cache_status: Option<&str>) -> HttpResponse {
+    let mut builder = HttpResponse::build(
+        actix_web::http::StatusCode::from_u16(status).unwrap()
+    );
+    for (k, v) in headers {
+        builder.insert_header((k.as_str(), v.as_str()));
+    }
+    if let Some(cache_status) = cache_status {
+        builder.insert_header(("x-mock-cache", cache_status));
+    }
+    if let Some(json_body) = body {
+        builder.json(json_body)
+    } else {
+        builder.finish()
+    }
+}
+
+fn shadow_diff(
+    served_by: ShadowMode,
+    upstream_url: &str,
+    stub: &ResponseSnapshot,
+    upstream: Result<ResponseSnapshot, String>,
+) -> ResponseDiff {
+    match upstream {
+        Ok(upstream) => ResponseDiff {
+            served_by,
+            upstream_url: upstream_url.to_string(),
+            upstream_status: Some(upstream.status),
+            differences: shadow::diff_responses(stub, &upstream),
+            error: None,
+        },
+        Err(e) => ResponseDiff {
+            served_by,
+            upstream_url: upstream_url.to_string(),
+            upstream_status: None,
+            differences: Vec::new(),
+            error: Some(e),
+        },
+    }
 }
 
 pub async fn dispatch(req: HttpRequest, body: web::Bytes, data: web::Data<AppState>) -> impl Responder {
+    let global_limit = data.rate_limit.read().unwrap().as_ref().map(|l| l.check(&req, "global"));
+    let accept_encoding = req.headers().get("accept-encoding").and_then(|v| v.to_str().ok()).map(str::to_string);
+    let mut response = respond(req, body, data.clone(), global_limit.clone()).await;
+    if let Some(limit) = global_limit.filter(|_| !response.headers().contains_key("x-ratelimit-limit")) {
+        limit.apply(response.headers_mut());
+    }
+    // An endpoint's own encoding and throttle travel on the response; see the end of `respond`.
+    // Compressing first lets the throttle pace the bytes actually sent.
+    let compressible = response.extensions().get::<negotiate::Compressible>().copied();
+    let response = match compressible {
+        Some(negotiate::Compressible(forced)) => negotiate::compress(response, accept_encoding.as_deref(), forced),
+        None => response,
+    };
+    let throttle = response.extensions().get::<throttle::ThrottleConfig>().cloned()
+        .or_else(|| data.throttle.read().unwrap().clone());
+    match throttle {
+        Some(throttle) => throttle.apply(response).await,
+        None => response,
+    }
+}
+
+async fn respond(
+    req: HttpRequest,
+    body: web::Bytes,
+    data: web::Data<AppState>,
+    global_limit: Option<ratelimit::RateLimitStatus>,
+) -> HttpResponse {
+    let log_id = data.next_log_id.fetch_add(1, Ordering::Relaxed);
     let method = req.method().as_str().to_uppercase();
     let path = req.path().to_string();
     let timestamp = Local::now().to_rfc3339();
     let request_headers = req.headers().iter().map(|(k,v)| (k.to_string(), v.to_str().unwrap_or("").to_string())).collect::<HashMap<_,_>>();
     let query = req.query_string().to_string();
-    let request_body = serde_json::from_slice::<Value>(&body).ok();
+    let content_type = req.headers().get("content-type").and_then(|v| v.to_str().ok()).unwrap_or("");
+    let request_body = serde_json::from_slice::<Value>(&body).ok()
+        .or_else(|| content_type.contains("xml").then(|| Value::String(String::from_utf8_lossy(&body).to_string())));
+    let request_body_raw = (!body.is_empty()).then(|| BodyCapture::new(&body, body.len()));
+    let request_form = body::parse_form(content_type, &body).await;
     info!("Request {} {} headers={:?} query={} body={:?}", method, path, request_headers, query, request_body);
 
+    if let Some(limit) = global_limit.as_ref().filter(|l| l.limited) {
+        warn!("Rate limited {} {} for {} (global)", method, path, limit.key);
+        data.logs.push(RequestLog {
+            id: log_id,
+            method,
+            path,
+            request_headers,
+            query,
+            request_body,
+            request_body_raw,
+            request_form,
+            status: 429,
+            response_body: Some(limit.rejection_body()),
+            response_headers: limit.headers().into_iter().map(|(k, v)| (k.to_string(), v)).collect(),
+            timestamp,
+            matched_endpoint: Some("rate limit (global)".to_string()),
+            rate_limit: Some(limit.clone()),
+            ..Default::default()
+        });
+        return limit.rejection();
+    }
+
+    let graphql_result = graphql::handle(&data.graphql.read().unwrap(), &method, &path, &body);
+    if let Some(result) = graphql_result {
+        info!("Responded {} {} -> {} ({})", method, path, result.status, result.matched);
+        let mut response = HttpResponse::build(actix_web::http::StatusCode::from_u16(result.status).unwrap()).json(&result.body);
+        response.extensions_mut().insert(negotiate::Compressible(None));
+        data.logs.push(RequestLog {
+            id: log_id,
+            method,
+            path,
+            request_headers,
+            query,
+            request_body,
+            request_body_raw,
+            request_form,
+            status: result.status,
+            response_body: Some(result.body),
+            response_headers: HashMap::from([("content-type".to_string(), "application/json".to_string())]),
+            timestamp,
+            matched_endpoint: Some(result.matched),
+            rate_limit: global_limit.clone(),
+            graphql: Some(result.operation),
+            ..Default::default()
+        });
+        return response;
+    }
+
+    let soap_result = soap::handle(&data.soap.read().unwrap(), &req, &body);
+    if let Some(result) = soap_result {
+        let mut response = HttpResponse::build(actix_web::http::StatusCode::from_u16(result.status).unwrap())
+            .content_type(result.content_type.clone())
+            .body(result.body.clone());
+        response.extensions_mut().insert(negotiate::Compressible(None));
+        data.logs.push(RequestLog {
+            id: log_id,
+            method,
+            path,
+            request_headers,
+            query,
+            request_body: Some(Value::String(String::from_utf8_lossy(&body).to_string())),
+            request_body_raw,
+            request_form,
+            status: result.status,
+            response_body: Some(Value::String(result.body)),
+            response_headers: HashMap::from([("content-type".to_string(), result.content_type)]),
+            timestamp,
+            matched_endpoint: Some(result.matched),
+            rate_limit: global_limit.clone(),
+            soap: Some(result.info),
+            ..Default::default()
+        });
+        return response;
+    }
+
     let mut matched_endpoint: Option<DynamicEndpoint> = None;
     let mut matched_pattern: Option<String> = None;
+    let mut endpoint_path = path.clone();
+    let mut path_params = router::Captures::new();
     {
-        let dyn_map = data.dynamic.lock().unwrap();
+        let dyn_map = data.dynamic.read().unwrap();
         if let Some(ep) = dyn_map.get(&(method.clone(), path.clone())) {
             matched_endpoint = Some(ep.clone());
             matched_pattern = Some(path.clone());
-        } else {
-            for ((m, p), ep) in dyn_map.iter() {
-                if m == &method && matches_path_template(p, &path) {
-                    matched_endpoint = Some(ep.clone());
-                    matched_pattern = Some(format!("{} (template)", p));
-                    info!("Matched path template: {} matches {}", p, path);
-                    break;
+        } else if let Some(route) = data.router.read().unwrap().find(&method, &path) {
+            if let Some(ep) = dyn_map.get(&(method.clone(), route.template.to_string())) {
+                matched_endpoint = Some(ep.clone());
+                matched_pattern = Some(format!("{} (template)", route.template));
+                endpoint_path = route.template.to_string();
+                info!("Matched path template: {} matches {} with {:?}", route.template, path, route.params);
+                path_params = route.params;
+            }
+        }
+    }
+
+    if matched_endpoint.is_none() {
+        let oauth_result = oauth::handle(&data.oauth.read().unwrap(), &req, &method, &path, &body);
+        if let Some(result) = oauth_result {
+            info!("Responded {} {} -> {} ({})", method, path, result.status, result.matched);
+            let mut builder = HttpResponse::build(actix_web::http::StatusCode::from_u16(result.status).unwrap());
+            for (name, value) in &result.headers {
+                builder.insert_header((name.as_str(), value.as_str()));
+            }
+            let mut response_headers: HashMap<String, String> = result.headers.into_iter().collect();
+            let mut response = match &result.body {
+                Some(body) => {
+                    response_headers.insert("content-type".to_string(), "application/json".to_string());
+                    builder.json(body)
                 }
+                None => builder.finish(),
+            };
+            response.extensions_mut().insert(negotiate::Compressible(None));
+            data.logs.push(RequestLog {
+                id: log_id,
+                method,
+                path,
+                request_headers,
+                query,
+                request_body,
+                request_body_raw,
+                request_form,
+                status: result.status,
+                response_body: result.body,
+                response_headers,
+                timestamp,
+                matched_endpoint: Some(result.matched),
+                rate_limit: global_limit.clone(),
+                ..Default::default()
+            });
+            return response;
+        }
+
+        let resource_result = resource::handle(&data.resources.read().unwrap(), &method, &path, &query, request_body.as_ref());
+        if let Some(result) = resource_result {
+            info!("Responded {} {} -> {} ({})", method, path, result.status, result.matched);
+            let mut builder = HttpResponse::build(actix_web::http::StatusCode::from_u16(result.status).unwrap());
+            let mut response_headers = HashMap::new();
+            if let Some(total) = result.total {
+                builder.insert_header(("x-total-count", total.to_string()));
+                response_headers.insert("x-total-count".to_string(), total.to_string());
             }
+            let mut response = match &result.body {
+                Some(body) => {
+                    response_headers.insert("content-type".to_string(), "application/json".to_string());
+                    builder.json(body)
+                }
+                None => builder.finish(),
+            };
+            response.extensions_mut().insert(negotiate::Compressible(None));
+            data.logs.push(RequestLog {
+                id: log_id,
+                method,
+                path,
+                request_headers,
+                query,
+                request_body,
+                request_body_raw,
+                request_form,
+                status: result.status,
+                response_body: result.body,
+                response_headers,
+                timestamp,
+                matched_endpoint: Some(result.matched),
+                rate_limit: global_limit.clone(),
+                ..Default::default()
+            });
+            return response;
         }
     }
 
+    let endpoint_key = format!("{} {}", method, endpoint_path);
+
     let mut response_body: Option<Value> = None;
     let mut response_headers = HashMap::new();
     let mut proxied_to: Option<String> = None;
+    let mut shadow_result: Option<ResponseDiff> = None;
+    let mut cache_status: Option<String> = None;
+    let mut pending_callbacks = Vec::new();
+    let mut variant_choice = None;
+    let mut rate_limit = global_limit;
+    let endpoint_throttle = matched_endpoint.as_ref().and_then(|ep| ep.throttle.clone());
+    let endpoint_encoding = matched_endpoint.as_ref().and_then(|ep| ep.encoding);
+    let accept = req.headers().get("accept").and_then(|v| v.to_str().ok());
+    let representation = matched_endpoint.as_ref().map(|ep| negotiate::representation(accept, &ep.representations));
     let status: u16;
 
-    let response = if let Some(ep) = matched_endpoint {
-        if let Some(proxy_url) = &ep.proxy_url {
-            match forward_to_proxy(proxy_url, &req, &body, &query).await {
+    let mut response = if let Some(ep) = matched_endpoint {
+        if let Some(limiter) = &ep.rate_limit {
+            rate_limit = Some(limiter.check(&req, "endpoint"));
+        }
+        let upstream_url = match (&ep.proxy_url, ep.shadow) {
+            (Some(url), _) => Some(url.clone()),
+            (None, Some(_)) => data.default_proxy_url.read().unwrap().clone(),
+            (None, None) => None,
+        };
+        let serve_from_upstream = ep.shadow != Some(ShadowMode::Stub);
+        let auth_error = match ep.require_auth {
+            true => data.oauth.read().unwrap().authenticate(&req).err(),
+            false => None,
+        };
+        let security_rejection = ep.security.as_ref().and_then(|s| s.check(&req, &data.oauth.read().unwrap()).err());
+
+        if let Some(limit) = rate_limit.as_ref().filter(|l| l.limited) {
+            warn!("Rate limited {} for {}", endpoint_key, limit.key);
+            status = 429;
+            response_body = Some(limit.rejection_body());
+            matched_pattern = Some(format!("rate limit ({})", endpoint_key));
+            limit.rejection()
+        } else if let Some(e) = auth_error {
+            warn!("Rejected unauthenticated request to {}: {}", endpoint_key, e);
+            status = 401;
+            response_body = Some(oauth::unauthorized_body(&e));
+            matched_pattern = Some(format!("auth ({})", endpoint_key));
+            oauth::unauthorized(&e)
+        } else if let Some(rejection) = security_rejection {
+            warn!("Rejected {} for missing OpenAPI security credentials ({})", endpoint_key, rejection.status);
+            status = rejection.status;
+            response_body = Some(rejection.body.clone());
+            if !rejection.challenges().is_empty() {
+                response_headers.insert("www-authenticate".to_string(), rejection.challenges().join(", "));
+            }
+            matched_pattern = Some(format!("security ({})", endpoint_key));
+            rejection.response()
+        } else if let Some(proxy_url) = upstream_url.clone().filter(|_| serve_from_upstream) {
+            let (result, cache_result) = forward_with_cache(&data, &proxy_url, &req, &body, &query).await;
+            cache_status = cache_result;
+            match result {
                 Ok((proxy_status, proxy_body, proxy_headers)) => {
                     status = proxy_status;
-                    response_body = proxy_body.clone();
                     response_headers = proxy_headers.clone();
                     proxied_to = Some(format!("{}{}", proxy_url, path));
                     matched_pattern = Some(format!("proxy to {}", proxy_url));
 
-                    let mut builder = HttpResponse::build(
-                        actix_web::http::StatusCode::from_u16(proxy_status).unwrap()
-                    );
-                    for (k, v) in proxy_headers {
-                        builder.insert_header((k.as_str(), v.as_str()));
+                    if ep.shadow == Some(ShadowMode::Proxy) {
+                        let mut stub_headers = ep.headers.clone().unwrap_or_default();
+                        stub_headers.insert("content-type".to_string(), "application/json".to_string());
+                        let stub = ResponseSnapshot { status: ep.status, headers: &stub_headers, body: Some(&ep.response) };
+                        let upstream = match &proxy_body {
+                            ProxyBody::Json(body) => Ok(ResponseSnapshot { status: proxy_status, headers: &proxy_headers, body: body.as_ref() }),
+                            ProxyBody::Stream(_) => Err("Upstream response was streamed and not compared".to_string()),
+                        };
+                        shadow_result = Some(shadow_diff(ShadowMode::Proxy, &proxy_url, &stub, upstream));
                     }
-                    if let Some(json_body) = proxy_body {
-                        builder.json(json_body)
-                    } else {
-                        builder.finish()
+
+                    match proxy_body {
+                        ProxyBody::Json(proxy_body) => {
+                            response_body = proxy_body.clone();
+                            proxy_response(proxy_status, proxy_headers, proxy_body, cache_status.as_deref())
+                        }
+                        ProxyBody::Stream(upstream) => {
+                            streamed_proxy_response(data.clone(), log_id, proxy_status, proxy_headers, upstream, cache_status.as_deref())
+                        }
                     }
                 }
                 Err(e) => {
@@ -518,38 +1319,106 @@ pub async fn dispatch(req: HttpRequest, body: web::Bytes, data: web::Data<AppSta
                     HttpResponse::BadGateway().json(json!({"error": "Proxy request failed", "details": e}))
                 }
             }
+        } else if let Some(sse_cfg) = ep.sse.clone() {
+            status = 200;
+            response_headers.insert("content-type".to_string(), "text/event-stream".to_string());
+            sse::open_stream(data.clone(), log_id, &path, &endpoint_path, sse_cfg)
+        } else if let Some(Err(available)) = &representation {
+            warn!("No representation of {} is acceptable: {:?}", endpoint_key, accept);
+            status = 406;
+            let error = json!({"error": "Not Acceptable", "available": available});
+            response_body = Some(error.clone());
+            HttpResponse::NotAcceptable().json(error)
         } else {
-            status = ep.status;
-            response_body = Some(ep.response.clone());
+            let media_type = representation.and_then(Result::ok).unwrap_or_else(|| negotiate::JSON.to_string());
+            let variant = ep.variants.as_ref().map(|v| v.choose());
+            status = variant.as_ref().map_or(ep.status, |(v, _)| v.status);
+            let template = match ep.representations.get(&media_type) {
+                Some(representation) => representation,
+                None => variant.as_ref().and_then(|(v, _)| v.response.as_ref()).unwrap_or(&ep.response),
+            };
+            let rendered = if ep.template {
+                template::Context {
+                    method: method.clone(),
+                    url: req.full_url().to_string(),
+                    path_params: path_params.clone(),
+                    query: serde_urlencoded::from_str(&query).unwrap_or_default(),
+                    headers: request_headers.clone(),
+                    request_body: request_body.clone(),
+                    response_body: None,
+                }.render(template)
+            } else {
+                template.clone()
+            };
+            response_body = Some(rendered.clone());
 
             if let Some(custom_headers) = &ep.headers {
                 response_headers.extend(custom_headers.clone());
             }
-            response_headers.insert("content-type".to_string(), "application/json".to_string());
+            if let Some((variant, choice)) = variant {
+                info!("Served variant {} ({}) of {}", choice.index, variant.name.as_deref().unwrap_or("unnamed"), endpoint_key);
+                response_headers.extend(variant.headers.clone());
+                variant_choice = Some(choice);
+            }
+            if !ep.representations.is_empty() {
+                response_headers.retain(|name, _| !name.eq_ignore_ascii_case("content-type"));
+                response_headers.insert("vary".to_string(), "Accept".to_string());
+            }
+            response_headers.insert("content-type".to_string(), media_type.clone());
+
+            if let (Some(ShadowMode::Stub), Some(upstream_url)) = (ep.shadow, upstream_url) {
+                let data = data.clone();
+                let req = req.clone();
+                let body = body.clone();
+                let query = query.clone();
+                let stub_headers = response_headers.clone();
+                let stub_body = rendered.clone();
+                let endpoint_key = endpoint_key.clone();
+                actix_web::rt::spawn(async move {
+                    let result = fetch_from_proxy(&upstream_url, &req, &body, &query, &[]).await;
+                    let stub = ResponseSnapshot { status, headers: &stub_headers, body: Some(&stub_body) };
+                    let upstream = match &result {
+                        Ok((s, ProxyBody::Json(b), h)) => Ok(ResponseSnapshot { status: *s, headers: h, body: b.as_ref() }),
+                        Ok((_, ProxyBody::Stream(_), _)) => Err("Upstream response was streamed and not compared".to_string()),
+                        Err(e) => Err(e.clone()),
+                    };
+                    let diff = shadow_diff(ShadowMode::Stub, &upstream_url, &stub, upstream);
+                    info!("Shadowed {} to {}: {} difference(s)", endpoint_key, upstream_url, diff.differences.len());
+                    shadow::record_diff(&data, log_id, endpoint_key, diff);
+                });
+            }
 
-            HttpResponse::build(actix_web::http::StatusCode::from_u16(ep.status).unwrap()).json(&ep.response)
+            pending_callbacks = ep.callbacks.clone();
+            let mut builder = HttpResponse::build(actix_web::http::StatusCode::from_u16(status).unwrap());
+            for (name, value) in &response_headers {
+                builder.insert_header((name.as_str(), value.as_str()));
+            }
+            match (media_type.as_str(), rendered) {
+                (negotiate::JSON, rendered) => builder.json(&rendered),
+                (_, Value::String(text)) => builder.body(text),
+                (_, rendered) => builder.body(rendered.to_string()),
+            }
         }
     } else {
-        let default_proxy = data.default_proxy_url.lock().unwrap().clone();
+        let default_proxy = data.default_proxy_url.read().unwrap().clone();
         if let Some(default_proxy) = default_proxy {
-            match forward_to_proxy(&default_proxy, &req, &body, &query).await {
+            let (result, cache_result) = forward_with_cache(&data, &default_proxy, &req, &body, &query).await;
+            cache_status = cache_result;
+            match result {
                 Ok((proxy_status, proxy_body, proxy_headers)) => {
                     status = proxy_status;
-                    response_body = proxy_body.clone();
                     response_headers = proxy_headers.clone();
                     proxied_to = Some(format!("{}{}", default_proxy, path));
                     matched_pattern = Some(format!("default proxy to {}", default_proxy));
 
-                    let mut builder = HttpResponse::build(
-                        actix_web::http::StatusCode::from_u16(proxy_status).unwrap()
-                    );
-                    for (k, v) in proxy_headers {
-                        builder.insert_header((k.as_str(), v.as_str()));
-                    }
-                    if let Some(json_body) = proxy_body {
-                        builder.json(json_body)
-                    } else {
-                        builder.finish()
+                    match proxy_body {
+                        ProxyBody::Json(proxy_body) => {
+                            response_body = proxy_body.clone();
+                            proxy_response(proxy_status, proxy_headers, proxy_body, cache_status.as_deref())
+                        }
+                        ProxyBody::Stream(upstream) => {
+                            streamed_proxy_response(data.clone(), log_id, proxy_status, proxy_headers, upstream, cache_status.as_deref())
+                        }
                     }
                 }
                 Err(e) => {
@@ -565,22 +1434,65 @@ pub async fn dispatch(req: HttpRequest, body: web::Bytes, data: web::Data<AppSta
         }
     };
 
+    if let Some(limit) = &rate_limit {
+        limit.apply(response.headers_mut());
+        response_headers.extend(limit.headers().into_iter().map(|(k, v)| (k.to_string(), v)));
+    }
+    if let Some(throttle) = endpoint_throttle {
+        response.extensions_mut().insert(throttle);
+    }
+    if proxied_to.is_none() {
+        response.extensions_mut().insert(negotiate::Compressible(endpoint_encoding));
+    }
+
     info!("Responded {} {} -> {}", method, path, status);
 
-    data.logs.lock().unwrap().push(RequestLog {
+    let callback_context = (!pending_callbacks.is_empty()).then(|| template::Context {
+        method: method.clone(),
+        url: req.full_url().to_string(),
+        path_params: path_params.clone(),
+        query: serde_urlencoded::from_str(&query).unwrap_or_default(),
+        headers: request_headers.clone(),
+        request_body: request_body.clone(),
+        response_body: response_body.clone(),
+    });
+
+    data.logs.push(RequestLog {
+        id: log_id,
         method,
         path,
         request_headers,
         query,
         request_body,
+        request_body_raw,
+        request_form,
         status,
         response_body,
         response_headers,
         timestamp,
         matched_endpoint: matched_pattern,
+        path_params: (!path_params.is_empty()).then_some(path_params),
+        variant: variant_choice,
+        rate_limit,
         proxied_to,
+        shadow_diff: None,
+        cache: cache_status,
+        response_body_raw: None,
+        ws_frames: None,
+        callbacks: callback_context.as_ref().map(|_| Vec::new()),
+        graphql: None,
+        grpc: None,
+        soap: None,
     });
 
+    if let Some(ctx) = callback_context {
+        webhook::schedule(data.clone(), log_id, pending_callbacks, ctx);
+    }
+
+    if let Some(diff) = shadow_result {
+        shadow::record_diff(&data, log_id, endpoint_key, diff);
+    }
+
     response
 }
 
@@ -588,25 +1500,149 @@ pub fn init_logger() {
     Builder::new().filter(None, LevelFilter::Info).init();
 }
 
+/// A comma-separated environment variable, empty when unset.
+fn env_list(name: &str) -> Vec<String> {
+    env::var(name)
+        .map(|v| v.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect())
+        .unwrap_or_default()
+}
+
 pub async fn start_server(cfg: ServerConfig) -> std::io::Result<()> {
     let mut cfg = cfg;
 
     if cfg.default_proxy_url.is_none() {
         cfg.default_proxy_url = env::var("DEFAULT_PROXY_URL").ok();
     }
+    if cfg.proxy_cache_ttl.is_none() {
+        cfg.proxy_cache_ttl = env::var("PROXY_CACHE_TTL").ok().and_then(|s| s.parse().ok());
+    }
+    if cfg.grpc_port.is_none() {
+        cfg.grpc_port = env::var("GRPC_PORT").ok().and_then(|p| p.parse().ok());
+    }
+    if cfg.proto_files.is_empty() {
+        if let Ok(files) = env::var("PROTO_FILES") {
+            cfg.proto_files = files.split(',').map(|f| f.trim().to_string()).filter(|f| !f.is_empty()).collect();
+        }
+    }
+
+    if cfg.openapi_file.is_none() {
+        cfg.openapi_file = env::var("OPENAPI_FILE").ok();
+    }
+    if !cfg.enforce_security {
+        cfg.enforce_security = env::var("OPENAPI_ENFORCE_SECURITY").is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true"));
+    }
+    if !cfg.oauth {
+        cfg.oauth = env::var("OAUTH_ENABLED").is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true"));
+    }
+
+    if cfg.admin_token.is_none() {
+        cfg.admin_token = env::var("ADMIN_TOKEN").ok().filter(|t| !t.is_empty());
+    }
+    if cfg.admin_basic_auth.is_none() {
+        cfg.admin_basic_auth = env::var("ADMIN_BASIC_AUTH").ok().filter(|c| !c.is_empty());
+    }
+    if cfg.admin_host.is_none() {
+        cfg.admin_host = env::var("ADMIN_HOST").ok();
+    }
+    if cfg.admin_port.is_none() {
+        cfg.admin_port = env::var("ADMIN_PORT").ok().and_then(|p| p.parse().ok());
+    }
+    if cfg.admin_prefix.is_none() {
+        cfg.admin_prefix = env::var("ADMIN_PREFIX").ok();
+    }
+    if cfg.dashboard_path.is_none() {
+        cfg.dashboard_path = env::var("DASHBOARD_PATH").ok();
+    }
+    if !cfg.no_dashboard {
+        cfg.no_dashboard = env::var("DISABLE_DASHBOARD").is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true"));
+    }
+    if cfg.ui_dir.is_none() {
+        cfg.ui_dir = env::var("UI_DIR").ok();
+    }
+    if cfg.max_logs.is_none() {
+        cfg.max_logs = env::var("MAX_LOGS").ok().and_then(|n| n.parse().ok());
+    }
+    if cfg.https_port.is_none() {
+        cfg.https_port = env::var("HTTPS_PORT").ok().and_then(|p| p.parse().ok());
+    }
+    if cfg.tls_cert.is_none() {
+        cfg.tls_cert = env::var("TLS_CERT").ok();
+    }
+    if cfg.tls_key.is_none() {
+        cfg.tls_key = env::var("TLS_KEY").ok();
+    }
+    if cfg.tls_hostnames.is_empty() {
+        cfg.tls_hostnames = match env::var("TLS_HOSTNAMES") {
+            Ok(names) => names.split(',').map(|n| n.trim().to_string()).filter(|n| !n.is_empty()).collect(),
+            Err(_) => tls::DEFAULT_HOSTNAMES.iter().map(|n| n.to_string()).collect(),
+        };
+    }
+    if cfg.tls_dir.is_none() {
+        cfg.tls_dir = env::var("TLS_DIR").ok();
+    }
+    if cfg.cors_origins.is_empty() {
+        cfg.cors_origins = env_list("CORS_ORIGINS");
+    }
+    if !cfg.dev {
+        cfg.dev = env::var("DEV_MODE").is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true"));
+    }
+    if cfg.dev && cfg.cors_origins.is_empty() {
+        info!("Development mode: allowing CORS from localhost origins");
+        cfg.cors_origins = cors::DEV_ORIGINS.iter().map(|o| o.to_string()).collect();
+    }
+    if cfg.cors_methods.is_empty() {
+        cfg.cors_methods = env_list("CORS_METHODS");
+    }
+    if cfg.cors_headers.is_empty() {
+        cfg.cors_headers = env_list("CORS_HEADERS");
+    }
+    if !cfg.cors_credentials {
+        cfg.cors_credentials = env::var("CORS_CREDENTIALS").is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true"));
+    }
+    if cfg.cors_max_age.is_none() {
+        cfg.cors_max_age = env::var("CORS_MAX_AGE").ok().and_then(|s| s.parse().ok());
+    }
+    if cfg.admin_basic_auth.as_ref().is_some_and(|c| !c.contains(':')) {
+        return Err(std::io::Error::other("Admin basic auth must be given as user:password"));
+    }
+
+    let admin_prefix = dashboard::normalize_path(cfg.admin_prefix.as_deref().unwrap_or(dashboard::DEFAULT_ADMIN_PREFIX))
+        .map_err(std::io::Error::other)?;
+    if admin_prefix.is_empty() {
+        return Err(std::io::Error::other("Admin prefix cannot be the root path"));
+    }
+    let ui_source = match cfg.no_dashboard {
+        true => None,
+        false => dashboard::UiSource::resolve(cfg.ui_dir.as_deref()),
+    };
+    if !cfg.no_dashboard && ui_source.is_none() {
+        warn!("Dashboard is not built into this binary or ./ui/dist; serving mocks only");
+    }
+    let dashboard = match ui_source {
+        None => None,
+        Some(source) => Some(dashboard::Dashboard {
+            mount: dashboard::normalize_path(cfg.dashboard_path.as_deref().unwrap_or("/")).map_err(std::io::Error::other)?,
+            admin_prefix: admin_prefix.clone(),
+            source,
+        }),
+    };
 
     info!("Starting server host={} port={}", cfg.host, cfg.port);
+    info!("Admin API at {}, dashboard {}", admin_prefix, match &dashboard {
+        Some(d) => format!("at {}/", d.mount),
+        None => "disabled".to_string(),
+    });
 
     let mut dynamic_endpoints = HashMap::new();
 
-    if let Ok(openapi_path) = env::var("OPENAPI_FILE") {
+    if let Some(openapi_path) = cfg.openapi_file.clone() {
         match fs::read_to_string(&openapi_path) {
             Ok(content) => {
                 match serde_json::from_str::<Value>(&content) {
                     Ok(raw_spec) => {
                         match serde_json::from_value::<OpenAPI>(raw_spec) {
                             Ok(spec) => {
-                                let (count, _) = import_openapi_spec(&spec, &mut dynamic_endpoints);
+                                let (count, _) = import_openapi_spec(&spec, &mut dynamic_endpoints, cfg.enforce_security);
                                 info!("Auto-imported {} endpoints from OPENAPI_FILE: {}", count, openapi_path);
                             }
                             Err(e) => {
@@ -632,33 +1668,179 @@ pub async fn start_server(cfg: ServerConfig) -> std::io::Result<()> {
     }
 
     let state = web::Data::new(AppState {
-        dynamic: Mutex::new(dynamic_endpoints),
-        logs: Mutex::new(vec![]),
-        default_proxy_url: Mutex::new(cfg.default_proxy_url),
+        router: RwLock::new(endpoint_router(&dynamic_endpoints)),
+        dynamic: RwLock::new(dynamic_endpoints),
+        logs: logs::LogStore::new(cfg.max_logs.unwrap_or(logs::DEFAULT_MAX_LOGS)),
+        default_proxy_url: RwLock::new(cfg.default_proxy_url),
+        next_log_id: AtomicU64::new(1),
+        drift: Mutex::new(HashMap::new()),
+        proxy_cache: RwLock::new(ProxyCache::new(CacheConfig {
+            enabled: cfg.proxy_cache_ttl.is_some(),
+            ttl_secs: cfg.proxy_cache_ttl,
+            ..Default::default()
+        })),
+        ws_endpoints: Mutex::new(HashMap::new()),
+        ws_router: RwLock::new(router::Router::default()),
+        sse_clients: Mutex::new(Vec::new()),
+        graphql: RwLock::new(graphql::GraphqlState::default()),
+        grpc: RwLock::new(grpc::GrpcState::default()),
+        soap: RwLock::new(Vec::new()),
+        resources: RwLock::new(HashMap::new()),
+        rate_limit: RwLock::new(None),
+        throttle: RwLock::new(None),
+        oauth: RwLock::new(oauth::OAuthProvider::new(oauth::OAuthConfig { enabled: cfg.oauth, ..Default::default() })),
+        admin_auth: auth::AdminAuth {
+            token: cfg.admin_token.clone(),
+            basic: cfg.admin_basic_auth.clone(),
+        },
+        cors: (!cfg.cors_origins.is_empty()).then(|| cors::CorsPolicy {
+            origins: cfg.cors_origins.clone(),
+            methods: cfg.cors_methods.clone(),
+            headers: cfg.cors_headers.clone(),
+            credentials: cfg.cors_credentials,
+            max_age: cfg.cors_max_age,
+        }),
     });
+    if state.admin_auth.is_enabled() {
+        info!("Admin API authentication enabled");
+    }
+    match &state.cors {
+        Some(policy) => info!("CORS enabled for {:?}", policy.origins),
+        None => info!("CORS handling disabled"),
+    }
+
+    if !cfg.proto_files.is_empty() {
+        let mut grpc_state = state.grpc.write().unwrap();
+        grpc_state.pool = grpc::load_protos(&grpc_state.pool, &cfg.proto_files, &[])
+            .map_err(std::io::Error::other)?;
+        let services = grpc_state.pool.services().len();
+        info!("Loaded {} gRPC service(s) from {:?}", services, cfg.proto_files);
+    }
+    if let Some(grpc_port) = cfg.grpc_port {
+        grpc::listen(state.clone(), &cfg.host, grpc_port).await?;
+    }
+
+    let https = match cfg.https_port {
+        Some(https_port) => {
+            let cert_dir = std::path::Path::new(cfg.tls_dir.as_deref().unwrap_or(tls::DEFAULT_CERT_DIR));
+            let tls_config = tls::server_config(cfg.tls_cert.as_deref(), cfg.tls_key.as_deref(), &cfg.tls_hostnames, cert_dir)?;
+            info!("Serving HTTPS on host={} port={}", cfg.host, https_port);
+            Some(((cfg.host.clone(), https_port), tls_config))
+        }
+        None => None,
+    };
+
+    let Some(admin_port) = cfg.admin_port else {
+        let server = HttpServer::new(move || {
+            App::new()
+                .app_data(state.clone())
+                .wrap(Logger::default())
+                .configure(|c| admin_api(c, &admin_prefix))
+                .configure(|c| mock_routes(c, dashboard.as_ref()))
+        })
+            .bind((cfg.host, cfg.port))?;
+        let server = match https {
+            Some((addr, tls_config)) => server.bind_rustls_0_23(addr, tls_config)?,
+            None => server,
+        };
+        return server.run().await;
+    };
 
-    HttpServer::new(move || {
+    let admin_host = cfg.admin_host.clone().unwrap_or_else(|| cfg.host.clone());
+    info!("Admin API and dashboard on host={} port={}", admin_host, admin_port);
+    let admin_state = state.clone();
+    let admin = HttpServer::new(move || {
+        App::new()
+            .app_data(admin_state.clone())
+            .wrap(Logger::default())
+            .configure(|c| admin_api(c, &admin_prefix))
+            .configure(|c| if let Some(d) = &dashboard { dashboard::configure(c, d, false) })
+    })
+        .bind((admin_host, admin_port))?
+        .run();
+    let mocks = HttpServer::new(move || {
         App::new()
             .app_data(state.clone())
             .wrap(Logger::default())
-            .service(web::scope("/__mock")
-                .route("/endpoints", web::post().to(add_endpoint))
-                .route("/endpoints", web::delete().to(remove_endpoint))
-                .route("/endpoints", web::put().to(update_endpoint))
-                .route("/config", web::get().to(get_config))
-                .route("/logs", web::get().to(get_logs))
-                .route("/logs", web::delete().to(clear_logs))
-                .route("/import", web::post().to(import_openapi))
-                .route("/export", web::get().to(export_openapi))
-                .route("/proxy", web::get().to(get_proxy))
-                .route("/proxy", web::post().to(set_proxy))
-                .route("/proxy", web::delete().to(delete_proxy)))
-            .service(web::scope("")
-                .guard(guard::Get())
-                .service(Files::new("/", "./ui/dist").index_file("index.html").default_handler(web::route().to(dispatch))))
-            .default_service(web::route().to(dispatch))
+            .configure(|c| mock_routes(c, None))
     })
-        .bind((cfg.host, cfg.port))?
-        .run()
-        .await
+        .bind((cfg.host, cfg.port))?;
+    let mocks = match https {
+        Some((addr, tls_config)) => mocks.bind_rustls_0_23(addr, tls_config)?,
+        None => mocks,
+    }
+        .run();
+    futures_util::future::try_join(admin, mocks).await.map(|_| ())
+}
+
+/// The admin API under `prefix` (`/__mock` by default), guarded by [`auth::require_admin`].
+fn admin_api(cfg: &mut web::ServiceConfig, prefix: &str) {
+    cfg.service(web::scope(prefix)
+        .wrap(middleware::from_fn(auth::require_admin))
+        .route("/endpoints", web::post().to(add_endpoint))
+        .route("/endpoints", web::delete().to(remove_endpoint))
+        .route("/endpoints", web::put().to(update_endpoint))
+        .route("/config", web::get().to(get_config))
+        .route("/logs", web::get().to(get_logs))
+        .route("/logs", web::delete().to(clear_logs))
+        .route("/import", web::post().to(import_openapi))
+        .route("/export", web::get().to(export_openapi))
+        .route("/proxy", web::get().to(get_proxy))
+        .route("/proxy", web::post().to(set_proxy))
+        .route("/proxy", web::delete().to(delete_proxy))
+        .route("/drift", web::get().to(shadow::get_drift))
+        .route("/drift", web::delete().to(shadow::clear_drift))
+        .route("/cache", web::get().to(cache::get_cache))
+        .route("/cache", web::post().to(cache::set_cache))
+        .route("/cache", web::delete().to(cache::purge_cache))
+        .route("/ws", web::get().to(ws::get_ws_endpoints))
+        .route("/ws", web::post().to(ws::add_ws_endpoint))
+        .route("/ws", web::delete().to(ws::remove_ws_endpoint))
+        .route("/sse/push", web::post().to(sse::push_event))
+        .route("/sse/clients", web::get().to(sse::get_clients))
+        .route("/graphql", web::get().to(graphql::get_graphql))
+        .route("/graphql", web::delete().to(graphql::clear_graphql))
+        .route("/graphql/schema", web::post().to(graphql::set_schema))
+        .route("/graphql/operations", web::post().to(graphql::add_override))
+        .route("/graphql/operations", web::delete().to(graphql::clear_overrides))
+        .route("/grpc", web::get().to(grpc::get_grpc))
+        .route("/grpc/protos", web::post().to(grpc::load_protos_handler))
+        .route("/grpc/stubs", web::post().to(grpc::add_stub))
+        .route("/grpc/stubs", web::delete().to(grpc::clear_stubs))
+        .route("/soap", web::get().to(soap::get_soap_stubs))
+        .route("/soap", web::post().to(soap::add_soap_stub))
+        .route("/soap", web::delete().to(soap::remove_soap_stubs))
+        .route("/soap/wsdl", web::post().to(soap::import_wsdl_handler))
+        .route("/resources", web::get().to(resource::get_resources))
+        .route("/resources", web::post().to(resource::add_resource))
+        .route("/resources", web::delete().to(resource::remove_resource))
+        .route("/resources/reset", web::post().to(resource::reset_resources))
+        .route("/rate-limit", web::get().to(ratelimit::get_rate_limit))
+        .route("/rate-limit", web::post().to(ratelimit::set_rate_limit))
+        .route("/rate-limit", web::delete().to(ratelimit::clear_rate_limit))
+        .route("/throttle", web::get().to(throttle::get_throttle))
+        .route("/throttle", web::post().to(throttle::set_throttle))
+        .route("/throttle", web::delete().to(throttle::clear_throttle))
+        .route("/oauth", web::get().to(oauth::get_oauth))
+        .route("/oauth", web::post().to(oauth::set_oauth))
+        .route("/oauth/token", web::post().to(oauth::issue_token)));
+}
+
+/// Mocked traffic: WebSocket upgrades and every stubbed or proxied request, with the
+/// dashboard in front when it shares the listener. A dashboard at the root only claims
+/// its own files; one under a mount point owns that whole subtree.
+///
+/// Only the catch-all for stubbed and proxied requests gets CORS handling, so pages on other
+/// origins can never reach the admin API or the dashboard.
+fn mock_routes(cfg: &mut web::ServiceConfig, dashboard: Option<&dashboard::Dashboard>) {
+    cfg.service(web::resource("/{tail:.*}")
+        .guard(guard::fn_guard(|ctx| ws::is_upgrade(ctx.head().headers())))
+        .to(ws::handle_upgrade));
+    if let Some(dashboard) = dashboard {
+        dashboard::configure(cfg, dashboard, dashboard.mount.is_empty());
+    }
+    cfg.service(web::scope("")
+        .app_data(web::PayloadConfig::new(MAX_PAYLOAD_SIZE))
+        .wrap(middleware::from_fn(cors::handle_cors))
+        .default_service(web::route().to(dispatch)));
 }
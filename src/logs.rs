@@ -0,0 +1,115 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+
+use tokio::sync::oneshot;
+
+use crate::RequestLog;
+
+pub const DEFAULT_MAX_LOGS: usize = 10_000;
+
+/// How long the writer rests after draining the channel, letting commands pile up so that
+/// senders under load rarely have to wake it. Snapshots may wait this long.
+const BATCH_INTERVAL: Duration = Duration::from_millis(10);
+
+type Update = Box<dyn FnOnce(&mut RequestLog) + Send>;
+
+enum Command {
+    Push(Arc<RequestLog>),
+    Update(u64, Update),
+    Snapshot(oneshot::Sender<Vec<Arc<RequestLog>>>),
+    Clear,
+}
+
+/// The ring buffer, owned by the writer thread.
+struct Buffer {
+    logs: VecDeque<Arc<RequestLog>>,
+    /// Position of each entry by id, counted from the first entry ever pushed.
+    positions: HashMap<u64, u64>,
+    /// Position of `logs[0]`.
+    first: u64,
+    capacity: usize,
+}
+
+impl Buffer {
+    fn apply(&mut self, command: Command) {
+        match command {
+            Command::Push(log) => {
+                if self.logs.len() == self.capacity {
+                    if let Some(evicted) = self.logs.pop_front() {
+                        self.positions.remove(&evicted.id);
+                    }
+                    self.first += 1;
+                }
+                self.positions.insert(log.id, self.first + self.logs.len() as u64);
+                self.logs.push_back(log);
+            }
+            Command::Update(id, f) => {
+                if let Some(position) = self.positions.get(&id) {
+                    // Copies the entry only if a snapshot still holds it.
+                    f(Arc::make_mut(&mut self.logs[(position - self.first) as usize]));
+                }
+            }
+            Command::Snapshot(reply) => {
+                let _ = reply.send(self.logs.iter().cloned().collect());
+            }
+            Command::Clear => {
+                self.first += self.logs.len() as u64;
+                self.logs.clear();
+                self.positions.clear();
+            }
+        }
+    }
+}
+
+/// Request log kept as a ring buffer of the most recent `capacity` entries.
+///
+/// Handlers send pushes and updates over a channel to a writer thread that owns the buffer,
+/// so they never wait on a lock or on readers. Readers get a snapshot of shared entries,
+/// taken in order after everything sent before it; copying it out costs one pointer per entry.
+pub struct LogStore {
+    sender: mpsc::SyncSender<Command>,
+}
+
+impl LogStore {
+    pub fn new(capacity: usize) -> LogStore {
+        let capacity = capacity.max(1);
+        // Bounded too, so a writer that falls behind slows handlers down instead of growing memory.
+        let (sender, commands) = mpsc::sync_channel(capacity);
+        let mut buffer = Buffer { logs: VecDeque::new(), positions: HashMap::new(), first: 0, capacity };
+        thread::Builder::new()
+            .name("request-log".to_string())
+            .spawn(move || {
+                while let Ok(command) = commands.recv() {
+                    buffer.apply(command);
+                    commands.try_iter().for_each(|command| buffer.apply(command));
+                    thread::sleep(BATCH_INTERVAL);
+                }
+            })
+            .expect("Failed to start the request log writer");
+        LogStore { sender }
+    }
+
+    pub fn push(&self, log: RequestLog) {
+        let _ = self.sender.send(Command::Push(Arc::new(log)));
+    }
+
+    /// Applies `f` to the entry with `id`, if it is still in the buffer.
+    pub fn update(&self, id: u64, f: impl FnOnce(&mut RequestLog) + Send + 'static) {
+        let _ = self.sender.send(Command::Update(id, Box::new(f)));
+    }
+
+    /// The entries, oldest first.
+    pub async fn snapshot(&self) -> Vec<Arc<RequestLog>> {
+        let (reply, snapshot) = oneshot::channel();
+        if self.sender.send(Command::Snapshot(reply)).is_err() {
+            return Vec::new();
+        }
+        snapshot.await.unwrap_or_default()
+    }
+
+    pub fn clear(&self) {
+        let _ = self.sender.send(Command::Clear);
+    }
+}
@@ -0,0 +1,199 @@
+use actix_web::body::{BoxBody, MessageBody};
+use actix_web::http::header::{self, HeaderValue};
+use actix_web::HttpResponse;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::Write;
+
+/// The media type of a stub's `response`.
+pub const JSON: &str = "application/json";
+
+/// Content codings the server can produce, in the order preferred when a client rates them equally.
+const SUPPORTED: [Encoding; 3] = [Encoding::Br, Encoding::Zstd, Encoding::Gzip];
+
+/// Marks a mocked response as open to compression, with the endpoint's forced coding if any.
+///
+/// Proxied responses don't carry it, so they go out as the upstream sent them.
+#[derive(Clone, Copy, Debug)]
+pub struct Compressible(pub Option<Encoding>);
+
+/// How a response body is encoded on the wire.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Encoding {
+    Gzip,
+    Br,
+    Zstd,
+    Identity,
+    /// Claims `gzip` but sends a corrupt stream, to test how clients cope with undecodable bodies.
+    Broken,
+}
+
+impl Encoding {
+    fn token(self) -> &'static str {
+        match self {
+            Encoding::Gzip | Encoding::Broken => "gzip",
+            Encoding::Br => "br",
+            Encoding::Zstd => "zstd",
+            Encoding::Identity => "identity",
+        }
+    }
+
+    fn encode(self, body: &[u8]) -> std::io::Result<Vec<u8>> {
+        match self {
+            Encoding::Gzip => {
+                let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(body)?;
+                encoder.finish()
+            }
+            Encoding::Br => {
+                let mut encoded = Vec::new();
+                let mut encoder = brotli::CompressorWriter::new(&mut encoded, 4096, 5, 22);
+                encoder.write_all(body)?;
+                drop(encoder);
+                Ok(encoded)
+            }
+            Encoding::Zstd => zstd::encode_all(body, 3),
+            Encoding::Identity => Ok(body.to_vec()),
+            Encoding::Broken => {
+                // Keep the gzip header but mark the first deflate block with the reserved type,
+                // which every decoder rejects (a truncated stream is quietly accepted by some).
+                let mut encoded = Encoding::Gzip.encode(body)?;
+                encoded[10] |= 0b110;
+                Ok(encoded)
+            }
+        }
+    }
+}
+
+/// Splits an `Accept` or `Accept-Encoding` header into its values and their quality.
+fn weighted(header: &str) -> Vec<(String, f32)> {
+    header.split(',')
+        .filter_map(|item| {
+            let mut parts = item.split(';');
+            let value = parts.next()?.trim().to_ascii_lowercase();
+            let q = parts
+                .find_map(|p| p.trim().to_ascii_lowercase().strip_prefix("q=")?.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            (!value.is_empty()).then_some((value, q))
+        })
+        .collect()
+}
+
+/// The best coding the client accepts, or `Identity` without an `Accept-Encoding` header.
+pub fn encoding(accept_encoding: Option<&str>) -> Encoding {
+    let Some(accepted) = accept_encoding.map(weighted) else { return Encoding::Identity };
+    let quality = |coding: Encoding| accepted.iter()
+        .find(|(value, _)| value == coding.token())
+        .or_else(|| accepted.iter().find(|(value, _)| value == "*"))
+        .map_or(0.0, |(_, q)| *q);
+    let mut best = (Encoding::Identity, 0.0);
+    for coding in SUPPORTED {
+        let q = quality(coding);
+        if q > best.1 {
+            best = (coding, q);
+        }
+    }
+    best.0
+}
+
+/// Picks which of `available` media types to send for an `Accept` header.
+///
+/// Each type is rated by the most specific range matching it; ties go to the earlier type.
+/// Without an `Accept` header the first type is sent, and `None` means nothing is acceptable.
+pub fn media_type<'a>(accept: Option<&str>, available: &[&'a str]) -> Option<&'a str> {
+    let Some(ranges) = accept.map(weighted).filter(|r| !r.is_empty()) else { return available.first().copied() };
+    let quality = |media_type: &str| {
+        let media_type = media_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+        let major = media_type.split('/').next().unwrap_or_default();
+        ranges.iter()
+            .filter_map(|(range, q)| {
+                let range = range.as_str();
+                let specificity = match range.split_once('/') {
+                    _ if range == media_type => 3,
+                    Some((r_major, "*")) if r_major == major => 2,
+                    Some(("*", "*")) => 1,
+                    _ => return None,
+                };
+                Some((specificity, *q))
+            })
+            .max_by_key(|(specificity, _)| *specificity)
+            .map_or(0.0, |(_, q)| q)
+    };
+    let mut best = None;
+    for media_type in available {
+        let q = quality(media_type);
+        if q > 0.0 && best.is_none_or(|(_, best_q)| q > best_q) {
+            best = Some((*media_type, q));
+        }
+    }
+    best.map(|(media_type, _)| media_type)
+}
+
+/// Compresses a buffered body with `forced`, or else with the best coding the client accepts.
+///
+/// Streamed bodies, bodies already carrying a `Content-Encoding` and bodiless statuses are
+/// sent as they are.
+pub fn compress(response: HttpResponse, accept_encoding: Option<&str>, forced: Option<Encoding>) -> HttpResponse {
+    let status = response.status().as_u16();
+    if response.headers().contains_key(header::CONTENT_ENCODING) || status == 204 || status == 304 || status < 200 {
+        return response;
+    }
+    let (mut response, body) = response.into_parts();
+    let bytes = match body.try_into_bytes() {
+        Ok(bytes) => bytes,
+        Err(body) => return response.set_body(body),
+    };
+
+    if forced.is_none() {
+        response.headers_mut().append(header::VARY, HeaderValue::from_static("Accept-Encoding"));
+    }
+    let coding = forced.unwrap_or_else(|| encoding(accept_encoding));
+    if coding == Encoding::Identity {
+        return response.set_body(BoxBody::new(bytes));
+    }
+    let encoded = match coding.encode(&bytes) {
+        Ok(encoded) => encoded,
+        Err(e) => {
+            warn!("Failed to {}-encode response: {}", coding.token(), e);
+            return response.set_body(BoxBody::new(bytes));
+        }
+    };
+    response.headers_mut().insert(header::CONTENT_ENCODING, HeaderValue::from_static(coding.token()));
+    response.set_body(BoxBody::new(encoded))
+}
+
+/// Whether `media_type` names one type rather than a range like `text/*`.
+pub fn is_concrete(media_type: &str) -> bool {
+    media_type.split_once('/').is_some_and(|(major, minor)| !major.is_empty() && !minor.is_empty() && !media_type.contains('*'))
+}
+
+/// Checks that a stub's representations are keyed by concrete media types other than JSON.
+pub fn validate_representations(representations: &HashMap<String, Value>) -> Result<(), String> {
+    for media_type in representations.keys() {
+        if media_type.eq_ignore_ascii_case(JSON) {
+            return Err("representations can't replace application/json, which is the stub's response".to_string());
+        }
+        if !is_concrete(media_type) {
+            return Err(format!("representations key {:?} is not a media type like application/xml", media_type));
+        }
+    }
+    Ok(())
+}
+
+/// The media type a stub answers `accept` with: JSON first, then its representations by name.
+///
+/// `Err` lists every type on offer when none of them is acceptable.
+pub fn representation(accept: Option<&str>, representations: &HashMap<String, Value>) -> Result<String, Vec<String>> {
+    if representations.is_empty() {
+        return Ok(JSON.to_string());
+    }
+    let mut available: Vec<&str> = representations.keys().map(String::as_str).collect();
+    available.sort_unstable();
+    available.insert(0, JSON);
+    media_type(accept, &available)
+        .map(str::to_string)
+        .ok_or_else(|| available.into_iter().map(str::to_string).collect())
+}
@@ -0,0 +1,633 @@
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use chrono::Utc;
+use log::info;
+use ring::digest::{digest, SHA256};
+use ring::rand::{SecureRandom, SystemRandom};
+use ring::signature::{EcdsaKeyPair, KeyPair, UnparsedPublicKey, ECDSA_P256_SHA256_FIXED, ECDSA_P256_SHA256_FIXED_SIGNING};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::AppState;
+
+pub const DISCOVERY_PATH: &str = "/.well-known/openid-configuration";
+
+/// How long an authorization code can be exchanged for tokens.
+const CODE_TTL: Duration = Duration::from_secs(600);
+
+/// How long an unused refresh token can be redeemed.
+const REFRESH_TOKEN_TTL: Duration = Duration::from_secs(24 * 3600);
+
+/// Outstanding refresh tokens kept; the oldest is dropped to make room.
+const MAX_REFRESH_TOKENS: usize = 10_000;
+
+/// Claims describing the token itself, left out of `/userinfo`.
+const TOKEN_CLAIMS: &[&str] = &["iss", "aud", "exp", "iat", "jti", "client_id", "scope"];
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct OAuthConfig {
+    /// Off at startup unless `--oauth` is given; posting a configuration turns it on.
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Defaults to the scheme and host a request came in on, followed by `prefix`.
+    #[serde(default)]
+    pub issuer: Option<String>,
+    /// Where the authorize, token, userinfo and JWKS endpoints are served.
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+    /// With none configured, any client id is accepted.
+    #[serde(default)]
+    pub clients: Vec<OAuthClient>,
+    /// With none configured, any username and password are accepted.
+    #[serde(default)]
+    pub users: Vec<OAuthUser>,
+    /// Added to every access and ID token.
+    #[serde(default)]
+    pub claims: Map<String, Value>,
+    #[serde(default = "default_token_ttl")]
+    pub token_ttl_secs: u64,
+}
+
+impl Default for OAuthConfig {
+    fn default() -> Self {
+        OAuthConfig {
+            enabled: false,
+            issuer: None,
+            prefix: default_prefix(),
+            clients: Vec::new(),
+            users: Vec::new(),
+            claims: Map::new(),
+            token_ttl_secs: default_token_ttl(),
+        }
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_prefix() -> String {
+    "/oauth".to_string()
+}
+
+fn default_token_ttl() -> u64 {
+    3600
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct OAuthClient {
+    pub client_id: String,
+    /// Public clients (PKCE) have none.
+    #[serde(default)]
+    pub client_secret: Option<String>,
+    /// With none configured, any redirect URI is accepted.
+    #[serde(default)]
+    pub redirect_uris: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct OAuthUser {
+    pub username: String,
+    pub password: String,
+    /// Added to this user's tokens and `/userinfo`, e.g. `email` or `roles`.
+    #[serde(default)]
+    pub claims: Map<String, Value>,
+}
+
+/// Who a token is for, carried from an authorization code or refresh token to the tokens.
+#[derive(Clone, Debug)]
+struct Grant {
+    client_id: String,
+    sub: String,
+    scope: String,
+    user_claims: Map<String, Value>,
+    /// Client credentials grants get no ID or refresh token.
+    user: bool,
+}
+
+struct RefreshToken {
+    grant: Grant,
+    issued: Instant,
+}
+
+struct AuthCode {
+    grant: Grant,
+    redirect_uri: String,
+    /// `code_challenge` and its method.
+    challenge: Option<(String, String)>,
+    nonce: Option<String>,
+    issued: Instant,
+}
+
+/// A handled request to one of the provider's endpoints.
+pub struct OAuthResponse {
+    pub status: u16,
+    pub body: Option<Value>,
+    pub headers: Vec<(String, String)>,
+    pub matched: String,
+}
+
+impl OAuthResponse {
+    fn json(status: u16, body: Value, matched: &str) -> OAuthResponse {
+        OAuthResponse { status, body: Some(body), headers: Vec::new(), matched: format!("oauth {}", matched) }
+    }
+
+    fn error(status: u16, error: &str, description: &str, matched: &str) -> OAuthResponse {
+        let mut response = OAuthResponse::json(status, json!({"error": error, "error_description": description}), matched);
+        response.headers.push(("cache-control".to_string(), "no-store".to_string()));
+        if error == "invalid_client" {
+            response.headers.push(("www-authenticate".to_string(), "Basic realm=\"RustMock OAuth\"".to_string()));
+        }
+        response
+    }
+
+    fn redirect(location: String, matched: &str) -> OAuthResponse {
+        OAuthResponse { status: 302, body: None, headers: vec![("location".to_string(), location)], matched: format!("oauth {}", matched) }
+    }
+}
+
+/// Mock OAuth2 / OpenID Connect provider signing ES256 JWTs with a key generated at startup.
+pub struct OAuthProvider {
+    pub config: OAuthConfig,
+    key: EcdsaKeyPair,
+    kid: String,
+    rng: SystemRandom,
+    codes: Mutex<HashMap<String, AuthCode>>,
+    refresh_tokens: Mutex<HashMap<String, RefreshToken>>,
+}
+
+/// Whether a client without registered redirect URIs may be sent to `redirect_uri`: only
+/// loopback addresses and the issuer's own host, so the mock can't bounce users anywhere.
+fn unregistered_redirect_allowed(redirect_uri: &str, issuer: &str) -> bool {
+    let Some(host) = reqwest::Url::parse(redirect_uri).ok().and_then(|u| u.host_str().map(str::to_string)) else {
+        return false;
+    };
+    let issuer_host = reqwest::Url::parse(issuer).ok().and_then(|u| u.host_str().map(str::to_string));
+    host.eq_ignore_ascii_case("localhost")
+        || host.trim_start_matches('[').trim_end_matches(']').parse::<std::net::IpAddr>().is_ok_and(|ip| ip.is_loopback())
+        || issuer_host.is_some_and(|issuer_host| host.eq_ignore_ascii_case(&issuer_host))
+}
+
+impl OAuthProvider {
+    pub fn new(config: OAuthConfig) -> OAuthProvider {
+        let rng = SystemRandom::new();
+        let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &rng).expect("Failed to generate signing key");
+        let key = EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, pkcs8.as_ref(), &rng).expect("Failed to load signing key");
+        let kid = URL_SAFE_NO_PAD.encode(&digest(&SHA256, key.public_key().as_ref()).as_ref()[..8]);
+        OAuthProvider {
+            config,
+            key,
+            kid,
+            rng,
+            codes: Mutex::new(HashMap::new()),
+            refresh_tokens: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Replaces the configuration, keeping the signing key so tokens already issued stay valid.
+    pub fn configure(&mut self, mut config: OAuthConfig) -> Result<(), String> {
+        config.prefix = format!("/{}", config.prefix.trim_matches('/'));
+        if config.prefix == "/" {
+            return Err("oauth.prefix cannot be the root path".to_string());
+        }
+        self.config = config;
+        self.codes.lock().unwrap().clear();
+        self.refresh_tokens.lock().unwrap().clear();
+        Ok(())
+    }
+
+    fn random_token(&self) -> String {
+        let mut bytes = [0u8; 32];
+        self.rng.fill(&mut bytes).expect("Failed to generate random bytes");
+        URL_SAFE_NO_PAD.encode(bytes)
+    }
+
+    fn issuer(&self, req: &HttpRequest) -> String {
+        self.config.issuer.clone().unwrap_or_else(|| {
+            let info = req.connection_info();
+            format!("{}://{}{}", info.scheme(), info.host(), self.config.prefix)
+        })
+    }
+
+    fn jwk(&self) -> Value {
+        // An uncompressed P-256 point: 0x04, then 32 bytes each of x and y.
+        let point = self.key.public_key().as_ref();
+        json!({
+            "kty": "EC",
+            "crv": "P-256",
+            "x": URL_SAFE_NO_PAD.encode(&point[1..33]),
+            "y": URL_SAFE_NO_PAD.encode(&point[33..65]),
+            "kid": self.kid,
+            "use": "sig",
+            "alg": "ES256"
+        })
+    }
+
+    fn sign(&self, typ: &str, claims: &Map<String, Value>) -> String {
+        let header = json!({"alg": "ES256", "typ": typ, "kid": self.kid});
+        let input = format!("{}.{}", URL_SAFE_NO_PAD.encode(header.to_string()), URL_SAFE_NO_PAD.encode(Value::from(claims.clone()).to_string()));
+        let signature = self.key.sign(&self.rng, input.as_bytes()).expect("Failed to sign token");
+        format!("{}.{}", input, URL_SAFE_NO_PAD.encode(signature.as_ref()))
+    }
+
+    /// Checks that `token` is an unexpired access token signed by this provider and returns its claims.
+    pub fn verify(&self, token: &str) -> Result<Map<String, Value>, String> {
+        let (input, signature) = token.rsplit_once('.').ok_or("Malformed token")?;
+        let signature = URL_SAFE_NO_PAD.decode(signature).map_err(|_| "Malformed token signature")?;
+        UnparsedPublicKey::new(&ECDSA_P256_SHA256_FIXED, self.key.public_key().as_ref())
+            .verify(input.as_bytes(), &signature)
+            .map_err(|_| "Token signature is not valid")?;
+        let (header, payload) = input.split_once('.').ok_or("Malformed token")?;
+        let decode = |part: &str| -> Result<Map<String, Value>, String> {
+            let bytes = URL_SAFE_NO_PAD.decode(part).map_err(|_| "Malformed token")?;
+            serde_json::from_slice(&bytes).map_err(|_| "Malformed token".to_string())
+        };
+        if decode(header)?.get("typ").and_then(Value::as_str) != Some("at+jwt") {
+            return Err("Not an access token".to_string());
+        }
+        let claims = decode(payload)?;
+        if claims.get("exp").and_then(Value::as_i64).is_none_or(|exp| exp <= Utc::now().timestamp()) {
+            return Err("Token has expired".to_string());
+        }
+        Ok(claims)
+    }
+
+    /// Verifies the bearer token in a request's `Authorization` header.
+    pub fn authenticate(&self, req: &HttpRequest) -> Result<Map<String, Value>, String> {
+        let header = req.headers().get("authorization").and_then(|v| v.to_str().ok()).ok_or("Missing bearer token")?;
+        match header.trim().split_once(' ') {
+            Some((scheme, token)) if scheme.eq_ignore_ascii_case("bearer") => self.verify(token.trim()),
+            _ => Err("Missing bearer token".to_string()),
+        }
+    }
+
+    /// The claims for `username`, or `None` when users are configured and it isn't one of them.
+    fn user(&self, username: &str, password: Option<&str>) -> Option<Map<String, Value>> {
+        if self.config.users.is_empty() {
+            return Some(Map::new());
+        }
+        self.config.users.iter()
+            .find(|u| u.username == username && password.is_none_or(|p| p == u.password))
+            .map(|u| u.claims.clone())
+    }
+
+    fn find_client(&self, client_id: &str) -> Result<Option<&OAuthClient>, ()> {
+        if self.config.clients.is_empty() {
+            return Ok(None);
+        }
+        self.config.clients.iter().find(|c| c.client_id == client_id).map(Some).ok_or(())
+    }
+
+    /// The client a token request authenticates as, via basic auth or `client_id`/`client_secret`.
+    fn client(&self, req: &HttpRequest, params: &HashMap<String, String>) -> Result<String, OAuthResponse> {
+        let basic = req.headers().get("authorization")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|h| h.strip_prefix("Basic ").or_else(|| h.strip_prefix("basic ")))
+            .and_then(|c| STANDARD.decode(c.trim()).ok())
+            .and_then(|c| String::from_utf8(c).ok())
+            .and_then(|c| c.split_once(':').map(|(id, secret)| (id.to_string(), Some(secret.to_string()))));
+        let (client_id, secret) = match basic {
+            Some(credentials) => credentials,
+            None => match params.get("client_id") {
+                Some(id) => (id.clone(), params.get("client_secret").cloned()),
+                None => return Err(OAuthResponse::error(401, "invalid_client", "Client authentication is required", "token")),
+            },
+        };
+        match self.find_client(&client_id) {
+            Err(()) => Err(OAuthResponse::error(401, "invalid_client", "Unknown client", "token")),
+            Ok(Some(client)) if client.client_secret.is_some() && client.client_secret != secret => {
+                Err(OAuthResponse::error(401, "invalid_client", "Client secret does not match", "token"))
+            }
+            Ok(_) => Ok(client_id),
+        }
+    }
+
+    fn discovery(&self, req: &HttpRequest) -> OAuthResponse {
+        let issuer = self.issuer(req);
+        OAuthResponse::json(200, json!({
+            "issuer": issuer,
+            "authorization_endpoint": format!("{}/authorize", issuer),
+            "token_endpoint": format!("{}/token", issuer),
+            "userinfo_endpoint": format!("{}/userinfo", issuer),
+            "jwks_uri": format!("{}/jwks", issuer),
+            "response_types_supported": ["code"],
+            "grant_types_supported": ["authorization_code", "client_credentials", "password", "refresh_token"],
+            "subject_types_supported": ["public"],
+            "id_token_signing_alg_values_supported": ["ES256"],
+            "code_challenge_methods_supported": ["S256", "plain"],
+            "token_endpoint_auth_methods_supported": ["client_secret_basic", "client_secret_post", "none"],
+            "scopes_supported": ["openid", "profile", "email", "offline_access"]
+        }), "discovery")
+    }
+
+    /// Approves every request straight away, as `login_hint` or else the first configured user.
+    fn authorize(&self, req: &HttpRequest) -> OAuthResponse {
+        let params: HashMap<String, String> = serde_urlencoded::from_str(req.query_string()).unwrap_or_default();
+        let Some(client_id) = params.get("client_id") else {
+            return OAuthResponse::error(400, "invalid_request", "client_id is required", "authorize");
+        };
+        let Ok(client) = self.find_client(client_id) else {
+            return OAuthResponse::error(400, "invalid_client", "Unknown client", "authorize");
+        };
+        let allowed = client.map(|c| c.redirect_uris.as_slice()).unwrap_or_default();
+        let Some(redirect_uri) = params.get("redirect_uri").or(allowed.first()) else {
+            return OAuthResponse::error(400, "invalid_request", "redirect_uri is required", "authorize");
+        };
+        if !allowed.is_empty() && !allowed.contains(redirect_uri) {
+            return OAuthResponse::error(400, "invalid_request", "redirect_uri is not registered for this client", "authorize");
+        }
+        if allowed.is_empty() && !unregistered_redirect_allowed(redirect_uri, &self.issuer(req)) {
+            return OAuthResponse::error(400, "invalid_request", "redirect_uri must point at a loopback address or the issuer's host unless the client registers redirect_uris", "authorize");
+        }
+
+        let mut reply = vec![];
+        if let Some(state) = params.get("state") {
+            reply.push(("state", state.clone()));
+        }
+        let challenge = params.get("code_challenge").map(|c| {
+            (c.clone(), params.get("code_challenge_method").cloned().unwrap_or_else(|| "plain".to_string()))
+        });
+        let username = params.get("login_hint").cloned()
+            .or_else(|| self.config.users.first().map(|u| u.username.clone()))
+            .unwrap_or_else(|| "user".to_string());
+        let user_claims = self.user(&username, None);
+        let error = if params.get("response_type").map(String::as_str) != Some("code") {
+            Some("unsupported_response_type")
+        } else if challenge.as_ref().is_some_and(|(_, method)| method != "S256" && method != "plain") {
+            Some("invalid_request")
+        } else if user_claims.is_none() {
+            Some("access_denied")
+        } else {
+            None
+        };
+
+        match error {
+            Some(error) => reply.insert(0, ("error", error.to_string())),
+            None => {
+                let code = self.random_token();
+                let grant = Grant {
+                    client_id: client_id.clone(),
+                    sub: username,
+                    scope: params.get("scope").cloned().unwrap_or_default(),
+                    user_claims: user_claims.unwrap_or_default(),
+                    user: true,
+                };
+                let mut codes = self.codes.lock().unwrap();
+                codes.retain(|_, c| c.issued.elapsed() < CODE_TTL);
+                codes.insert(code.clone(), AuthCode {
+                    grant,
+                    redirect_uri: redirect_uri.clone(),
+                    challenge,
+                    nonce: params.get("nonce").cloned(),
+                    issued: Instant::now(),
+                });
+                reply.insert(0, ("code", code));
+            }
+        }
+        let separator = if redirect_uri.contains('?') { '&' } else { '?' };
+        let query = serde_urlencoded::to_string(&reply).unwrap_or_default();
+        OAuthResponse::redirect(format!("{}{}{}", redirect_uri, separator, query), "authorize")
+    }
+
+    fn token(&self, req: &HttpRequest, body: &[u8]) -> OAuthResponse {
+        let params: HashMap<String, String> = match serde_json::from_slice::<Map<String, Value>>(body) {
+            Ok(json) => json.into_iter().filter_map(|(k, v)| Some((k, v.as_str()?.to_string()))).collect(),
+            Err(_) => serde_urlencoded::from_bytes(body).unwrap_or_default(),
+        };
+        let client_id = match self.client(req, &params) {
+            Ok(client_id) => client_id,
+            Err(response) => return response,
+        };
+        let invalid_grant = |description: &str| OAuthResponse::error(400, "invalid_grant", description, "token");
+        let param = |name: &str| params.get(name).map(String::as_str);
+
+        let (grant, nonce) = match param("grant_type") {
+            Some("client_credentials") => {
+                let grant = Grant {
+                    sub: client_id.clone(),
+                    client_id,
+                    scope: param("scope").unwrap_or_default().to_string(),
+                    user_claims: Map::new(),
+                    user: false,
+                };
+                (grant, None)
+            }
+            Some("password") => {
+                let (Some(username), Some(password)) = (param("username"), param("password")) else {
+                    return OAuthResponse::error(400, "invalid_request", "username and password are required", "token");
+                };
+                let Some(user_claims) = self.user(username, Some(password)) else {
+                    return invalid_grant("Invalid username or password");
+                };
+                let grant = Grant {
+                    client_id,
+                    sub: username.to_string(),
+                    scope: param("scope").unwrap_or_default().to_string(),
+                    user_claims,
+                    user: true,
+                };
+                (grant, None)
+            }
+            Some("authorization_code") => {
+                let Some(code) = param("code").and_then(|c| self.codes.lock().unwrap().remove(c)) else {
+                    return invalid_grant("Unknown or already used authorization code");
+                };
+                if code.issued.elapsed() >= CODE_TTL {
+                    return invalid_grant("Authorization code has expired");
+                }
+                if code.grant.client_id != client_id {
+                    return invalid_grant("Authorization code was issued to another client");
+                }
+                if param("redirect_uri").is_some_and(|uri| uri != code.redirect_uri) {
+                    return invalid_grant("redirect_uri does not match the authorization request");
+                }
+                if let Some((challenge, method)) = &code.challenge {
+                    let Some(verifier) = param("code_verifier") else {
+                        return invalid_grant("code_verifier is required");
+                    };
+                    let computed = match method.as_str() {
+                        "S256" => URL_SAFE_NO_PAD.encode(digest(&SHA256, verifier.as_bytes())),
+                        _ => verifier.to_string(),
+                    };
+                    if &computed != challenge {
+                        return invalid_grant("code_verifier does not match code_challenge");
+                    }
+                }
+                (code.grant, code.nonce)
+            }
+            Some("refresh_token") => {
+                let Some(RefreshToken { grant, issued }) = param("refresh_token").and_then(|t| self.refresh_tokens.lock().unwrap().remove(t)) else {
+                    return invalid_grant("Unknown or already used refresh token");
+                };
+                if issued.elapsed() >= REFRESH_TOKEN_TTL {
+                    return invalid_grant("Refresh token has expired");
+                }
+                if grant.client_id != client_id {
+                    return invalid_grant("Refresh token was issued to another client");
+                }
+                (grant, None)
+            }
+            Some(other) => return OAuthResponse::error(400, "unsupported_grant_type", &format!("Unsupported grant_type {}", other), "token"),
+            None => return OAuthResponse::error(400, "invalid_request", "grant_type is required", "token"),
+        };
+
+        info!("Issuing tokens for {} to client {}", grant.sub, grant.client_id);
+        let mut response = OAuthResponse::json(200, self.issue(req, &grant, nonce.as_deref()), "token");
+        response.headers.push(("cache-control".to_string(), "no-store".to_string()));
+        response
+    }
+
+    fn issue(&self, req: &HttpRequest, grant: &Grant, nonce: Option<&str>) -> Value {
+        let now = Utc::now().timestamp();
+        let ttl = self.config.token_ttl_secs;
+        let issuer = self.issuer(req);
+        let with_custom_claims = |mut claims: Map<String, Value>| {
+            claims.extend(self.config.claims.clone());
+            claims.extend(grant.user_claims.clone());
+            claims
+        };
+
+        let access = with_custom_claims(Map::from_iter([
+            ("iss".to_string(), json!(issuer)),
+            ("sub".to_string(), json!(grant.sub)),
+            ("aud".to_string(), json!(grant.client_id)),
+            ("client_id".to_string(), json!(grant.client_id)),
+            ("scope".to_string(), json!(grant.scope)),
+            ("iat".to_string(), json!(now)),
+            ("exp".to_string(), json!(now + ttl as i64)),
+            ("jti".to_string(), json!(self.random_token())),
+        ]));
+        let mut tokens = json!({
+            "access_token": self.sign("at+jwt", &access),
+            "token_type": "Bearer",
+            "expires_in": ttl,
+            "scope": grant.scope
+        });
+        if !grant.user {
+            return tokens;
+        }
+
+        if grant.scope.split(' ').any(|s| s == "openid") {
+            let mut id = Map::from_iter([
+                ("iss".to_string(), json!(issuer)),
+                ("sub".to_string(), json!(grant.sub)),
+                ("aud".to_string(), json!(grant.client_id)),
+                ("iat".to_string(), json!(now)),
+                ("exp".to_string(), json!(now + ttl as i64)),
+                ("auth_time".to_string(), json!(now)),
+            ]);
+            if let Some(nonce) = nonce {
+                id.insert("nonce".to_string(), json!(nonce));
+            }
+            tokens["id_token"] = json!(self.sign("JWT", &with_custom_claims(id)));
+        }
+        let refresh_token = self.random_token();
+        {
+            let mut refresh_tokens = self.refresh_tokens.lock().unwrap();
+            refresh_tokens.retain(|_, t| t.issued.elapsed() < REFRESH_TOKEN_TTL);
+            if refresh_tokens.len() >= MAX_REFRESH_TOKENS {
+                if let Some(oldest) = refresh_tokens.iter().min_by_key(|(_, t)| t.issued).map(|(k, _)| k.clone()) {
+                    refresh_tokens.remove(&oldest);
+                }
+            }
+            refresh_tokens.insert(refresh_token.clone(), RefreshToken { grant: grant.clone(), issued: Instant::now() });
+        }
+        tokens["refresh_token"] = json!(refresh_token);
+        tokens
+    }
+
+    fn userinfo(&self, req: &HttpRequest) -> OAuthResponse {
+        match self.authenticate(req) {
+            Ok(mut claims) => {
+                claims.retain(|name, _| !TOKEN_CLAIMS.contains(&name.as_str()));
+                OAuthResponse::json(200, Value::Object(claims), "userinfo")
+            }
+            Err(e) => {
+                let mut response = OAuthResponse::json(401, unauthorized_body(&e), "userinfo");
+                response.headers.push(("www-authenticate".to_string(), challenge(&e)));
+                response
+            }
+        }
+    }
+}
+
+/// Answers requests to the provider's endpoints; `None` for anything else.
+pub fn handle(provider: &OAuthProvider, req: &HttpRequest, method: &str, path: &str, body: &[u8]) -> Option<OAuthResponse> {
+    if !provider.config.enabled {
+        return None;
+    }
+    if method == "GET" && path == DISCOVERY_PATH {
+        return Some(provider.discovery(req));
+    }
+    let route = path.strip_prefix(provider.config.prefix.as_str())?;
+    match (method, route) {
+        ("GET", DISCOVERY_PATH) => Some(provider.discovery(req)),
+        ("GET", "/jwks") => Some(OAuthResponse::json(200, json!({"keys": [provider.jwk()]}), "jwks")),
+        ("GET", "/authorize") => Some(provider.authorize(req)),
+        ("POST", "/token") => Some(provider.token(req, body)),
+        ("GET" | "POST", "/userinfo") => Some(provider.userinfo(req)),
+        _ => None,
+    }
+}
+
+fn challenge(error: &str) -> String {
+    format!("Bearer realm=\"RustMock\", error=\"invalid_token\", error_description=\"{}\"", error)
+}
+
+pub fn unauthorized_body(error: &str) -> Value {
+    json!({"error": "invalid_token", "error_description": error})
+}
+
+/// The 401 sent by endpoints with `require_auth` when the bearer token doesn't verify.
+pub fn unauthorized(error: &str) -> HttpResponse {
+    HttpResponse::Unauthorized()
+        .insert_header(("www-authenticate", challenge(error)))
+        .json(unauthorized_body(error))
+}
+
+pub async fn get_oauth(data: web::Data<AppState>) -> impl Responder {
+    let provider = data.oauth.read().unwrap();
+    HttpResponse::Ok().json(json!({"oauth": provider.config, "jwks": {"keys": [provider.jwk()]}}))
+}
+
+/// Replaces the provider's clients, users and claims; outstanding codes and refresh tokens are dropped.
+pub async fn set_oauth(data: web::Data<AppState>, cfg: web::Json<OAuthConfig>) -> impl Responder {
+    let mut provider = data.oauth.write().unwrap();
+    match provider.configure(cfg.into_inner()) {
+        Ok(()) => {
+            info!("OAuth provider {} at {}", if provider.config.enabled { "enabled" } else { "disabled" }, provider.config.prefix);
+            HttpResponse::Ok().json(json!({"oauth": provider.config}))
+        }
+        Err(e) => HttpResponse::BadRequest().json(json!({"error": e})),
+    }
+}
+
+/// Issues an access token directly, for tests that don't want to go through a grant.
+pub async fn issue_token(req: HttpRequest, data: web::Data<AppState>, body: web::Json<TokenRequest>) -> impl Responder {
+    let provider = data.oauth.read().unwrap();
+    let body = body.into_inner();
+    let grant = Grant {
+        client_id: body.client_id.unwrap_or_else(|| "rustmock".to_string()),
+        sub: body.sub.unwrap_or_else(|| "user".to_string()),
+        scope: body.scope.unwrap_or_default(),
+        user_claims: body.claims,
+        user: true,
+    };
+    HttpResponse::Ok().json(provider.issue(&req, &grant, None))
+}
+
+#[derive(Deserialize)]
+pub struct TokenRequest {
+    #[serde(default)]
+    pub sub: Option<String>,
+    #[serde(default)]
+    pub client_id: Option<String>,
+    #[serde(default)]
+    pub scope: Option<String>,
+    #[serde(default)]
+    pub claims: Map<String, Value>,
+}
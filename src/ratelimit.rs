@@ -0,0 +1,220 @@
+use actix_web::http::header::{HeaderMap, HeaderName, HeaderValue};
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use log::info;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::AppState;
+
+/// Clients tracked per policy before idle ones are dropped.
+const MAX_TRACKED_KEYS: usize = 10_000;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Algorithm {
+    /// At most `limit` requests per `window_secs`, counted from a client's first request.
+    #[default]
+    FixedWindow,
+    /// Bursts of up to `limit`, refilled continuously at `limit` per `window_secs`.
+    TokenBucket,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RateLimitConfig {
+    #[serde(default)]
+    pub algorithm: Algorithm,
+    pub limit: u32,
+    #[serde(default = "default_window")]
+    pub window_secs: u64,
+    /// `global`, `ip`, `api_key` or `header:<name>`.
+    #[serde(default = "default_key")]
+    pub key: String,
+}
+
+fn default_window() -> u64 {
+    60
+}
+
+fn default_key() -> String {
+    "ip".to_string()
+}
+
+/// The outcome of a rate-limit check, recorded on the request log.
+#[derive(Serialize, Clone, Debug)]
+pub struct RateLimitStatus {
+    /// `global` or `endpoint`.
+    pub scope: String,
+    pub key: String,
+    pub limit: u32,
+    pub remaining: u32,
+    /// Seconds until the full limit is available again.
+    pub reset_secs: u64,
+    pub limited: bool,
+    /// Seconds until the next request would be allowed.
+    #[serde(skip)]
+    retry_after_secs: u64,
+}
+
+impl RateLimitStatus {
+    fn retry_after(&self) -> u64 {
+        self.retry_after_secs.max(1)
+    }
+
+    pub fn headers(&self) -> Vec<(&'static str, String)> {
+        let mut headers = vec![
+            ("x-ratelimit-limit", self.limit.to_string()),
+            ("x-ratelimit-remaining", self.remaining.to_string()),
+            ("x-ratelimit-reset", self.reset_secs.to_string()),
+        ];
+        if self.limited {
+            headers.push(("retry-after", self.retry_after().to_string()));
+        }
+        headers
+    }
+
+    pub fn apply(&self, headers: &mut HeaderMap) {
+        for (name, value) in self.headers() {
+            if let Ok(value) = HeaderValue::from_str(&value) {
+                headers.insert(HeaderName::from_static(name), value);
+            }
+        }
+    }
+
+    pub fn rejection_body(&self) -> Value {
+        json!({"error": "Rate limit exceeded", "retry_after": self.retry_after()})
+    }
+
+    /// The 429 sent once the limit is exceeded.
+    pub fn rejection(&self) -> HttpResponse {
+        let mut response = HttpResponse::TooManyRequests().json(self.rejection_body());
+        self.apply(response.headers_mut());
+        response
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Bucket {
+    /// Requests counted in the window, or tokens left for a token bucket.
+    level: f64,
+    since: Instant,
+}
+
+/// A policy with the per-client state shared by every clone of its endpoint.
+#[derive(Clone, Debug)]
+pub struct RateLimiter {
+    pub config: RateLimitConfig,
+    buckets: Arc<Mutex<HashMap<String, Bucket>>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Result<RateLimiter, String> {
+        if config.limit == 0 || config.window_secs == 0 {
+            return Err("rate_limit.limit and rate_limit.window_secs must be above 0".to_string());
+        }
+        let valid_key = matches!(config.key.as_str(), "global" | "ip" | "api_key")
+            || config.key.strip_prefix("header:").is_some_and(|h| HeaderName::from_bytes(h.as_bytes()).is_ok());
+        if !valid_key {
+            return Err(format!("Invalid rate_limit.key {:?}: use global, ip, api_key or header:<name>", config.key));
+        }
+        Ok(RateLimiter { config, buckets: Arc::new(Mutex::new(HashMap::new())) })
+    }
+
+    /// Which client a request counts against.
+    fn client_key(&self, req: &HttpRequest) -> String {
+        let header = |name: &str| req.headers().get(name).and_then(|v| v.to_str().ok()).map(str::to_string);
+        match self.config.key.as_str() {
+            "global" => "*".to_string(),
+            "api_key" => header("x-api-key")
+                .or_else(|| header("authorization"))
+                .or_else(|| {
+                    let query: HashMap<String, String> = serde_urlencoded::from_str(req.query_string()).unwrap_or_default();
+                    query.get("api_key").cloned()
+                })
+                .unwrap_or_default(),
+            key => match key.strip_prefix("header:") {
+                Some(name) => header(name).unwrap_or_default(),
+                None => req.connection_info().realip_remote_addr().unwrap_or("unknown").to_string(),
+            },
+        }
+    }
+
+    /// Counts the request against its client and reports whether it is over the limit.
+    pub fn check(&self, req: &HttpRequest, scope: &str) -> RateLimitStatus {
+        let key = self.client_key(req);
+        let limit = self.config.limit as f64;
+        let window = Duration::from_secs(self.config.window_secs);
+        let now = Instant::now();
+
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() >= MAX_TRACKED_KEYS && !buckets.contains_key(&key) {
+            buckets.retain(|_, b| now.duration_since(b.since) < window);
+        }
+        let fresh = match self.config.algorithm {
+            Algorithm::FixedWindow => Bucket { level: 0.0, since: now },
+            Algorithm::TokenBucket => Bucket { level: limit, since: now },
+        };
+        let bucket = buckets.entry(key.clone()).or_insert(fresh);
+
+        let (limited, remaining, reset, retry_after) = match self.config.algorithm {
+            Algorithm::FixedWindow => {
+                if now.duration_since(bucket.since) >= window {
+                    *bucket = fresh;
+                }
+                let limited = bucket.level >= limit;
+                if !limited {
+                    bucket.level += 1.0;
+                }
+                let reset = window.saturating_sub(now.duration_since(bucket.since));
+                (limited, limit - bucket.level, reset, reset)
+            }
+            Algorithm::TokenBucket => {
+                let per_sec = limit / window.as_secs_f64();
+                bucket.level = (bucket.level + now.duration_since(bucket.since).as_secs_f64() * per_sec).min(limit);
+                bucket.since = now;
+                let limited = bucket.level < 1.0;
+                if !limited {
+                    bucket.level -= 1.0;
+                }
+                let reset = Duration::from_secs_f64((limit - bucket.level) / per_sec);
+                let retry_after = Duration::from_secs_f64((1.0 - bucket.level).max(0.0) / per_sec);
+                (limited, bucket.level, reset, retry_after)
+            }
+        };
+
+        RateLimitStatus {
+            scope: scope.to_string(),
+            key,
+            limit: self.config.limit,
+            remaining: remaining.floor() as u32,
+            reset_secs: reset.as_secs_f64().ceil() as u64,
+            limited,
+            retry_after_secs: retry_after.as_secs_f64().ceil() as u64,
+        }
+    }
+}
+
+pub async fn get_rate_limit(data: web::Data<AppState>) -> impl Responder {
+    let limiter = data.rate_limit.read().unwrap();
+    HttpResponse::Ok().json(json!({"rate_limit": limiter.as_ref().map(|l| &l.config)}))
+}
+
+/// Sets the policy applied to all mocked traffic; clients' counts start over.
+pub async fn set_rate_limit(data: web::Data<AppState>, cfg: web::Json<RateLimitConfig>) -> impl Responder {
+    match RateLimiter::new(cfg.into_inner()) {
+        Ok(limiter) => {
+            info!("Global rate limit: {} per {}s by {}", limiter.config.limit, limiter.config.window_secs, limiter.config.key);
+            let config = limiter.config.clone();
+            *data.rate_limit.write().unwrap() = Some(limiter);
+            HttpResponse::Ok().json(json!({"rate_limit": config}))
+        }
+        Err(e) => HttpResponse::BadRequest().json(json!({"error": e})),
+    }
+}
+
+pub async fn clear_rate_limit(data: web::Data<AppState>) -> impl Responder {
+    let removed = data.rate_limit.write().unwrap().take().is_some();
+    HttpResponse::Ok().json(json!({"removed": removed}))
+}
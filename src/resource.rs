@@ -0,0 +1,328 @@
+use actix_web::{web, HttpResponse, Responder};
+use log::info;
+use openapiv3::{OpenAPI, ReferenceOr};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::AppState;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum IdType {
+    #[default]
+    Int,
+    Uuid,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ResourceConfig {
+    /// Collection path, e.g. `/users`; items live at `/users/{id}`.
+    pub path: String,
+    #[serde(default = "default_id_field")]
+    pub id_field: String,
+    #[serde(default)]
+    pub id_type: IdType,
+    /// Items the store starts with and returns to on reset.
+    #[serde(default)]
+    pub seed: Vec<Value>,
+}
+
+fn default_id_field() -> String {
+    "id".to_string()
+}
+
+pub struct Resource {
+    pub config: ResourceConfig,
+    pub items: Vec<Value>,
+    /// `None` once an item holds `u64::MAX`, when no integer id is left to hand out.
+    next_id: Option<u64>,
+}
+
+impl Resource {
+    pub fn new(config: ResourceConfig) -> Self {
+        let mut resource = Self { config, items: Vec::new(), next_id: Some(1) };
+        resource.reset();
+        resource
+    }
+
+    pub fn reset(&mut self) {
+        self.items = self.config.seed.clone();
+        self.next_id = self.items.iter()
+            .filter_map(|item| item.get(&self.config.id_field).and_then(Value::as_u64))
+            .max()
+            .map_or(Some(1), |max| max.checked_add(1));
+    }
+
+    fn id_matches(&self, item: &Value, id: &str) -> bool {
+        item.get(&self.config.id_field).is_some_and(|value| field_as_string(value) == id)
+    }
+
+    fn position(&self, id: &str) -> Option<usize> {
+        self.items.iter().position(|item| self.id_matches(item, id))
+    }
+
+    fn generate_id(&mut self) -> Option<Value> {
+        match self.config.id_type {
+            IdType::Int => {
+                let id = self.next_id?;
+                self.next_id = id.checked_add(1);
+                Some(json!(id))
+            }
+            IdType::Uuid => Some(json!(uuid::Uuid::new_v4().to_string())),
+        }
+    }
+}
+
+pub struct ResourceResponse {
+    pub status: u16,
+    pub body: Option<Value>,
+    pub total: Option<usize>,
+    pub matched: String,
+}
+
+fn respond(status: u16, body: Option<Value>, path: &str) -> ResourceResponse {
+    ResourceResponse { status, body, total: None, matched: format!("resource {}", path) }
+}
+
+fn field_as_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// RFC 7396 merge patch.
+fn merge_patch(target: &mut Value, patch: &Value) {
+    match (target, patch) {
+        (Value::Object(target), Value::Object(patch)) => {
+            for (key, value) in patch {
+                if value.is_null() {
+                    target.remove(key);
+                } else {
+                    merge_patch(target.entry(key.clone()).or_insert(Value::Null), value);
+                }
+            }
+        }
+        (target, patch) => *target = patch.clone(),
+    }
+}
+
+fn list(resource: &Resource, query: &str) -> ResourceResponse {
+    let params: Vec<(String, String)> = serde_urlencoded::from_str(query).unwrap_or_default();
+    let mut page = None;
+    let mut per_page = None;
+    let mut filters = Vec::new();
+    for (key, value) in params {
+        match key.as_str() {
+            "page" => page = value.parse::<usize>().ok(),
+            "per_page" | "limit" => per_page = value.parse::<usize>().ok(),
+            _ => filters.push((key, value)),
+        }
+    }
+
+    let matching: Vec<&Value> = resource.items.iter()
+        .filter(|item| filters.iter().all(|(key, value)| {
+            item.get(key).is_some_and(|field| field_as_string(field) == *value)
+        }))
+        .collect();
+    let total = matching.len();
+    let items: Vec<Value> = match per_page {
+        Some(per_page) => {
+            let page = page.unwrap_or(1).max(1);
+            matching.into_iter().skip((page - 1) * per_page).take(per_page).cloned().collect()
+        }
+        None => matching.into_iter().cloned().collect(),
+    };
+
+    ResourceResponse { total: Some(total), ..respond(200, Some(Value::Array(items)), &resource.config.path) }
+}
+
+/// Serves the request from a declared resource when the path is its collection or one of its items,
+/// locking only that resource.
+pub fn handle(
+    resources: &HashMap<String, Mutex<Resource>>,
+    method: &str,
+    path: &str,
+    query: &str,
+    body: Option<&Value>,
+) -> Option<ResourceResponse> {
+    let trimmed = path.trim_end_matches('/');
+    let (resource, id) = match resources.get(trimmed) {
+        Some(resource) => (resource, None),
+        None => {
+            let (collection, id) = trimmed.rsplit_once('/')?;
+            (resources.get(collection)?, Some(id.to_string()))
+        }
+    };
+    let resource = &mut *resource.lock().unwrap();
+    let collection = resource.config.path.clone();
+    let not_found = |id: &str| respond(404, Some(json!({"error": format!("{} {} not found", collection, id)})), &collection);
+
+    let response = match (method, id) {
+        ("GET", None) => list(resource, query),
+        ("POST", None) => {
+            let Some(Value::Object(fields)) = body else {
+                return Some(respond(400, Some(json!({"error": "Request body must be a JSON object"})), &collection));
+            };
+            let mut item = fields.clone();
+            let id_field = resource.config.id_field.clone();
+            match item.get(&id_field) {
+                Some(id) if resource.position(&field_as_string(id)).is_some() => {
+                    return Some(respond(409, Some(json!({"error": format!("{} {} already exists", collection, field_as_string(id))})), &collection));
+                }
+                Some(id) => {
+                    if let Some(id) = id.as_u64().filter(|id| resource.next_id.is_some_and(|next| *id >= next)) {
+                        resource.next_id = id.checked_add(1);
+                    }
+                }
+                None => {
+                    let Some(id) = resource.generate_id() else {
+                        return Some(respond(409, Some(json!({"error": format!("{} has run out of integer ids", collection)})), &collection));
+                    };
+                    let mut with_id = Map::new();
+                    with_id.insert(id_field, id);
+                    with_id.extend(item);
+                    item = with_id;
+                }
+            }
+            let item = Value::Object(item);
+            resource.items.push(item.clone());
+            respond(201, Some(item), &collection)
+        }
+        ("GET", Some(id)) => match resource.position(&id) {
+            Some(index) => respond(200, Some(resource.items[index].clone()), &collection),
+            None => not_found(&id),
+        },
+        ("PUT", Some(id)) | ("PATCH", Some(id)) => {
+            let Some(index) = resource.position(&id) else {
+                return Some(not_found(&id));
+            };
+            let Some(patch @ Value::Object(_)) = body else {
+                return Some(respond(400, Some(json!({"error": "Request body must be a JSON object"})), &collection));
+            };
+            let id_value = resource.items[index][&resource.config.id_field].clone();
+            let mut item = if method == "PUT" { patch.clone() } else {
+                let mut item = resource.items[index].clone();
+                merge_patch(&mut item, patch);
+                item
+            };
+            item[&resource.config.id_field] = id_value;
+            resource.items[index] = item.clone();
+            respond(200, Some(item), &collection)
+        }
+        ("DELETE", Some(id)) => match resource.position(&id) {
+            Some(index) => {
+                resource.items.remove(index);
+                respond(204, None, &collection)
+            }
+            None => not_found(&id),
+        },
+        _ => respond(405, Some(json!({"error": format!("{} not allowed on {}", method, path)})), &collection),
+    };
+    Some(response)
+}
+
+/// Collections with list/create operations and an item path with a single parameter.
+pub fn infer_from_openapi(spec: &OpenAPI) -> Vec<(ResourceConfig, String)> {
+    let mut resources = Vec::new();
+    for (path, item) in &spec.paths.paths {
+        let ReferenceOr::Item(collection) = item else { continue };
+        if path.contains('{') || collection.get.is_none() || collection.post.is_none() {
+            continue;
+        }
+        let item_path = spec.paths.paths.iter().find_map(|(candidate, item)| {
+            let param = candidate.strip_prefix(path.trim_end_matches('/'))?
+                .strip_prefix("/{")?
+                .strip_suffix('}')?;
+            match item {
+                ReferenceOr::Item(i) if !param.contains(['/', '{']) && i.get.is_some() => Some(candidate.clone()),
+                _ => None,
+            }
+        });
+        let Some(item_path) = item_path else { continue };
+
+        let seed = collection.get.as_ref()
+            .and_then(|op| crate::extract_example_response_for_status(op, 200))
+            .and_then(|example| match example {
+                Value::Array(items) => Some(items),
+                // Wrapped lists such as `{"users": [...], "total": 2}`.
+                Value::Object(fields) => fields.into_iter().find_map(|(_, v)| match v {
+                    Value::Array(items) => Some(items),
+                    _ => None,
+                }),
+                _ => None,
+            })
+            .map(|items| items.into_iter().filter(Value::is_object).collect())
+            .unwrap_or_default();
+        resources.push((ResourceConfig {
+            path: path.clone(),
+            id_field: default_id_field(),
+            id_type: IdType::Int,
+            seed,
+        }, item_path));
+    }
+    resources
+}
+
+#[derive(Deserialize)]
+pub struct ResourceQuery {
+    pub path: Option<String>,
+}
+
+pub async fn get_resources(data: web::Data<AppState>) -> impl Responder {
+    let resources = data.resources.read().unwrap();
+    let mut list: Vec<Value> = resources.values().map(|r| {
+        let r = r.lock().unwrap();
+        json!({
+            "path": r.config.path,
+            "id_field": r.config.id_field,
+            "id_type": r.config.id_type,
+            "count": r.items.len(),
+            "items": r.items
+        })
+    }).collect();
+    list.sort_by(|a, b| a["path"].as_str().cmp(&b["path"].as_str()));
+    HttpResponse::Ok().json(list)
+}
+
+pub async fn add_resource(data: web::Data<AppState>, cfg: web::Json<ResourceConfig>) -> impl Responder {
+    let mut cfg = cfg.into_inner();
+    cfg.path = cfg.path.trim_end_matches('/').to_string();
+    if cfg.path.is_empty() || cfg.path.contains('{') {
+        return HttpResponse::BadRequest().json(json!({"error": "Resource path must be a plain collection path like /users"}));
+    }
+    info!("Added resource {} with {} seed item(s)", cfg.path, cfg.seed.len());
+    let path = cfg.path.clone();
+    data.resources.write().unwrap().insert(path.clone(), Mutex::new(Resource::new(cfg)));
+    HttpResponse::Ok().json(json!({"added": true, "path": path}))
+}
+
+pub async fn remove_resource(data: web::Data<AppState>, query: web::Query<ResourceQuery>) -> impl Responder {
+    let mut resources = data.resources.write().unwrap();
+    match &query.path {
+        Some(path) => {
+            let removed = resources.remove(path.trim_end_matches('/')).is_some();
+            HttpResponse::Ok().json(json!({"removed": removed}))
+        }
+        None => {
+            resources.clear();
+            HttpResponse::Ok().json(json!({"removed": true}))
+        }
+    }
+}
+
+pub async fn reset_resources(data: web::Data<AppState>, query: web::Query<ResourceQuery>) -> impl Responder {
+    let resources = data.resources.read().unwrap();
+    let mut reset = 0;
+    for resource in resources.values() {
+        let mut resource = resource.lock().unwrap();
+        if query.path.as_deref().is_none_or(|p| p.trim_end_matches('/') == resource.config.path) {
+            resource.reset();
+            reset += 1;
+        }
+    }
+    info!("Reset {} resource(s)", reset);
+    HttpResponse::Ok().json(json!({"reset": reset}))
+}
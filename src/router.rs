@@ -0,0 +1,358 @@
+use log::warn;
+use regex::Regex;
+use std::collections::HashMap;
+
+const UUID: &str = "[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}";
+
+/// Values captured from the request path, by placeholder or named group.
+pub type Captures = HashMap<String, String>;
+
+/// Templates starting with `^` are regular expressions matched against the whole path.
+pub fn is_regex_path(template: &str) -> bool {
+    template.starts_with('^')
+}
+
+/// Checks that a template parses and its regexes compile.
+pub fn validate(template: &str) -> Result<(), String> {
+    if is_regex_path(template) {
+        return Regex::new(template).map(|_| ()).map_err(|e| format!("Invalid path regex {}: {}", template, e));
+    }
+    for segment in split_segments(template) {
+        Segment::parse(segment)?;
+    }
+    Ok(())
+}
+
+/// Splits on `/` outside of `{...}`, so constraints like `{id:[0-9]{3}}` stay in one piece.
+fn split_segments(template: &str) -> Vec<&str> {
+    let mut segments = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in template.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            '/' if depth == 0 => {
+                segments.push(&template[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    segments.push(&template[start..]);
+    segments
+}
+
+enum Piece<'a> {
+    Text(&'a str),
+    /// `{name}` or `{name:constraint}`.
+    Placeholder { name: &'a str, constraint: &'a str },
+}
+
+/// Breaks a segment into text and placeholders; `None` when its braces don't balance.
+fn pieces(segment: &str) -> Option<Vec<Piece<'_>>> {
+    let mut pieces = Vec::new();
+    let mut rest = segment;
+    while let Some(start) = rest.find('{') {
+        let mut depth = 0usize;
+        let end = rest[start..].char_indices().find_map(|(i, c)| {
+            match c {
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                _ => {}
+            }
+            (depth == 0).then_some(start + i)
+        })?;
+        if start > 0 {
+            pieces.push(Piece::Text(&rest[..start]));
+        }
+        let inner = &rest[start + 1..end];
+        let (name, constraint) = inner.split_once(':').unwrap_or((inner, ""));
+        pieces.push(Piece::Placeholder { name, constraint });
+        rest = &rest[end + 1..];
+    }
+    if !rest.is_empty() {
+        pieces.push(Piece::Text(rest));
+    }
+    Some(pieces)
+}
+
+/// The regex a placeholder's value must match: a type name (`int`, `uuid`) or a regex.
+fn constraint_regex(constraint: &str) -> &str {
+    match constraint {
+        "" => "[^/]+",
+        "int" => "-?[0-9]+",
+        "uuid" => UUID,
+        regex => regex,
+    }
+}
+
+/// One `/`-separated piece of a path template.
+#[derive(Clone, Debug)]
+enum Segment {
+    Literal(String),
+    Param(Param),
+    /// `*`, any single segment.
+    Wildcard,
+    /// `**` or `{name:**}`, any number of segments including none.
+    CatchAll(Option<String>),
+}
+
+/// A segment with placeholders, e.g. `{id}`, `{id:int}`, `{id:[0-9]+}` or `{name}.json`.
+#[derive(Clone, Debug)]
+struct Param {
+    template: String,
+    names: Vec<String>,
+    /// `None` for a bare, unconstrained `{name}`; otherwise the segment with group `pN` per name.
+    pattern: Option<Regex>,
+}
+
+impl Param {
+    /// Constrained or mixed segments are tried before bare placeholders, ties broken by text.
+    fn rank(&self) -> (bool, &str) {
+        (self.pattern.is_none(), &self.template)
+    }
+
+    fn captures(&self, segment: &str) -> Option<Vec<(String, String)>> {
+        if segment.is_empty() {
+            return None;
+        }
+        let Some(re) = &self.pattern else {
+            return Some(vec![(self.names[0].clone(), segment.to_string())]);
+        };
+        let caps = re.captures(segment)?;
+        Some(self.names.iter().enumerate()
+            .filter_map(|(i, name)| Some((name.clone(), caps.name(&format!("p{}", i))?.as_str().to_string())))
+            .collect())
+    }
+}
+
+impl Segment {
+    /// The segment and whether it may be left out (`{name?}`, `{name:int?}`).
+    fn parse(segment: &str) -> Result<(Segment, bool), String> {
+        match segment {
+            "*" => return Ok((Segment::Wildcard, false)),
+            "**" => return Ok((Segment::CatchAll(None), false)),
+            _ => {}
+        }
+        let Some(mut pieces) = pieces(segment).filter(|p| p.iter().any(|p| matches!(p, Piece::Placeholder { .. }))) else {
+            return Ok((Segment::Literal(segment.to_string()), false));
+        };
+
+        let mut optional = false;
+        if let [Piece::Placeholder { name, constraint }] = pieces.as_mut_slice() {
+            if *constraint == "**" {
+                return Ok((Segment::CatchAll(Some(name.to_string())), false));
+            }
+            if let Some(n) = name.strip_suffix('?').filter(|_| constraint.is_empty()) {
+                *name = n;
+                optional = true;
+            } else if let Some(c) = constraint.strip_suffix('?').filter(|c| matches!(*c, "int" | "uuid")) {
+                *constraint = c;
+                optional = true;
+            }
+            if constraint.is_empty() {
+                let param = Param { template: segment.to_string(), names: vec![name.to_string()], pattern: None };
+                return Ok((Segment::Param(param), optional));
+            }
+        }
+
+        let mut names = Vec::new();
+        let mut pattern = String::from("^");
+        for piece in &pieces {
+            match piece {
+                Piece::Text(text) => pattern.push_str(&regex::escape(text)),
+                Piece::Placeholder { name, constraint } => {
+                    pattern.push_str(&format!("(?P<p{}>{})", names.len(), constraint_regex(constraint)));
+                    names.push(name.to_string());
+                }
+            }
+        }
+        pattern.push('$');
+        let re = Regex::new(&pattern).map_err(|e| format!("Invalid constraint in {}: {}", segment, e))?;
+        Ok((Segment::Param(Param { template: segment.to_string(), names, pattern: Some(re) }), optional))
+    }
+}
+
+/// Every concrete form of a template, with each optional segment present and absent.
+fn expand(template: &str) -> Result<Vec<Vec<Segment>>, String> {
+    let mut variants = vec![Vec::new()];
+    for segment in split_segments(template) {
+        let (segment, optional) = Segment::parse(segment)?;
+        if optional {
+            let without = variants.clone();
+            variants.iter_mut().for_each(|v| v.push(segment.clone()));
+            variants.extend(without);
+        } else {
+            variants.iter_mut().for_each(|v| v.push(segment.clone()));
+        }
+    }
+    Ok(variants)
+}
+
+#[derive(Default, Debug)]
+struct Node {
+    literals: HashMap<String, Node>,
+    /// Kept sorted by [`Param::rank`].
+    params: Vec<(Param, Node)>,
+    wildcard: Option<Box<Node>>,
+    catch_all: Option<(Option<String>, Box<Node>)>,
+    /// Template of the route ending here.
+    template: Option<String>,
+}
+
+impl Node {
+    fn insert(&mut self, segments: &[Segment], template: &str) {
+        let Some((first, rest)) = segments.split_first() else {
+            self.template.get_or_insert_with(|| template.to_string());
+            return;
+        };
+        let child = match first {
+            Segment::Literal(literal) => self.literals.entry(literal.clone()).or_default(),
+            Segment::Wildcard => self.wildcard.get_or_insert_with(Box::default),
+            Segment::CatchAll(name) => &mut self.catch_all.get_or_insert_with(|| (name.clone(), Box::default())).1,
+            Segment::Param(param) => {
+                let i = match self.params.binary_search_by(|(p, _)| p.rank().cmp(&param.rank())) {
+                    Ok(i) => i,
+                    Err(i) => {
+                        self.params.insert(i, (param.clone(), Node::default()));
+                        i
+                    }
+                };
+                &mut self.params[i].1
+            }
+        };
+        child.insert(rest, template);
+    }
+
+    /// Depth-first, trying literals, then placeholders, then `*`, then `**`, backtracking on a miss.
+    fn find<'a>(&'a self, parts: &[&str], captures: &mut Vec<(String, String)>) -> Option<&'a str> {
+        if let Some((first, rest)) = parts.split_first() {
+            if let Some(found) = self.literals.get(*first).and_then(|child| child.find(rest, captures)) {
+                return Some(found);
+            }
+            let mark = captures.len();
+            for (param, child) in &self.params {
+                if let Some(values) = param.captures(first) {
+                    captures.extend(values);
+                    if let Some(found) = child.find(rest, captures) {
+                        return Some(found);
+                    }
+                    captures.truncate(mark);
+                }
+            }
+            if let Some(found) = self.wildcard.as_ref().filter(|_| !first.is_empty()).and_then(|child| child.find(rest, captures)) {
+                return Some(found);
+            }
+        } else if let Some(template) = &self.template {
+            return Some(template);
+        }
+
+        let (name, child) = self.catch_all.as_ref()?;
+        let mark = captures.len();
+        for taken in 0..=parts.len() {
+            captures.extend(name.clone().map(|name| (name, parts[..taken].join("/"))));
+            if let Some(found) = child.find(&parts[taken..], captures) {
+                return Some(found);
+            }
+            captures.truncate(mark);
+        }
+        None
+    }
+}
+
+/// Templates and regex paths registered for one method.
+#[derive(Default, Debug)]
+struct Table {
+    tree: Node,
+    /// Sorted by pattern, tried only when no template matches.
+    regexes: Vec<(String, Regex)>,
+}
+
+impl Table {
+    fn insert(&mut self, template: &str) -> Result<(), String> {
+        if is_regex_path(template) {
+            let re = Regex::new(template).map_err(|e| e.to_string())?;
+            let i = self.regexes.binary_search_by(|(t, _)| t.as_str().cmp(template)).unwrap_or_else(|i| i);
+            self.regexes.insert(i, (template.to_string(), re));
+            return Ok(());
+        }
+        for segments in expand(template)? {
+            self.tree.insert(&segments, template);
+        }
+        Ok(())
+    }
+
+    fn find(&self, path: &str) -> Option<RouteMatch<'_>> {
+        let parts: Vec<&str> = path.split('/').collect();
+        let mut captures = Vec::new();
+        if let Some(template) = self.tree.find(&parts, &mut captures) {
+            return Some(RouteMatch { template, params: captures.into_iter().collect() });
+        }
+        self.regexes.iter().find_map(|(template, re)| {
+            let caps = re.captures(path)?;
+            let params = re.capture_names().flatten()
+                .filter_map(|name| Some((name.to_string(), caps.name(name)?.as_str().to_string())))
+                .collect();
+            Some(RouteMatch { template, params })
+        })
+    }
+}
+
+/// The registered template a request matched and the values it captured.
+#[derive(Debug)]
+pub struct RouteMatch<'a> {
+    pub template: &'a str,
+    pub params: Captures,
+}
+
+/// A path template compiled once, for one-off matching outside the router.
+#[derive(Debug)]
+pub struct PathPattern {
+    table: Table,
+}
+
+impl PathPattern {
+    pub fn parse(template: &str) -> PathPattern {
+        let mut table = Table::default();
+        if let Err(e) = table.insert(template) {
+            warn!("Ignoring path template {}: {}", template, e);
+        }
+        PathPattern { table }
+    }
+
+    pub fn matches(&self, path: &str) -> bool {
+        self.table.find(path).is_some()
+    }
+}
+
+/// Compiled lookup from a request's method and path to the most specific registered template.
+///
+/// Built from the endpoint map and rebuilt whenever it changes, so matching a request never
+/// compiles a pattern. Where templates overlap, at each segment a literal beats a placeholder
+/// (constrained ones first), which beats `*`, which beats `**`; regex paths come last.
+#[derive(Default, Debug)]
+pub struct Router {
+    methods: HashMap<String, Table>,
+}
+
+impl Router {
+    pub fn build<'a>(routes: impl IntoIterator<Item = (&'a str, &'a str)>) -> Router {
+        let mut router = Router::default();
+        for (method, template) in routes {
+            router.insert(method, template);
+        }
+        router
+    }
+
+    pub fn insert(&mut self, method: &str, template: &str) {
+        if let Err(e) = self.methods.entry(method.to_uppercase()).or_default().insert(template) {
+            warn!("Ignoring path template {} {}: {}", method, template, e);
+        }
+    }
+
+    /// The template registered for `method` that best matches `path`.
+    pub fn find(&self, method: &str, path: &str) -> Option<RouteMatch<'_>> {
+        self.methods.get(method)?.find(path)
+    }
+}
@@ -0,0 +1,252 @@
+use actix_web::{HttpRequest, HttpResponse};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use log::warn;
+use openapiv3::{APIKeyLocation, Components, OpenAPI, Operation, ReferenceOr, SecurityScheme};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::oauth::OAuthProvider;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyLocation {
+    Header,
+    Query,
+    Cookie,
+}
+
+/// The credential a security scheme expects on a request.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Credential {
+    ApiKey {
+        key: String,
+        #[serde(rename = "in")]
+        location: KeyLocation,
+    },
+    Bearer,
+    Basic,
+    /// Any other `Authorization` scheme, e.g. `Digest`.
+    Http { auth_scheme: String },
+    /// A bearer token; when it was issued by the mock OAuth provider its scopes are checked too.
+    Oauth2,
+    /// A scheme the spec names but doesn't define, which no request can satisfy.
+    #[serde(skip_deserializing)]
+    Undefined,
+}
+
+/// One scheme of a security requirement, with the scopes it needs.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SchemeCheck {
+    /// The scheme's name in `components.securitySchemes`.
+    pub name: String,
+    #[serde(flatten)]
+    pub credential: Credential,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scopes: Vec<String>,
+}
+
+/// Security requirements enforced on an endpoint.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SecurityConfig {
+    /// Alternatives: a request passes when it satisfies every check of any one of them.
+    pub requirements: Vec<Vec<SchemeCheck>>,
+    /// Bodies sent with a 401 or 403, taken from the operation's examples when it has them.
+    #[serde(default)]
+    pub unauthorized: Option<Value>,
+    #[serde(default)]
+    pub forbidden: Option<Value>,
+}
+
+/// Why a request was turned away.
+#[derive(Debug)]
+pub struct Rejection {
+    pub status: u16,
+    pub body: Value,
+    challenges: Vec<String>,
+}
+
+impl Rejection {
+    pub fn challenges(&self) -> &[String] {
+        &self.challenges
+    }
+
+    pub fn response(&self) -> HttpResponse {
+        let mut builder = HttpResponse::build(actix_web::http::StatusCode::from_u16(self.status).unwrap());
+        for challenge in &self.challenges {
+            builder.append_header(("www-authenticate", challenge.as_str()));
+        }
+        builder.json(&self.body)
+    }
+}
+
+enum Outcome {
+    Passed,
+    Missing,
+    Forbidden(String),
+}
+
+impl SchemeCheck {
+    fn bearer_token(req: &HttpRequest) -> Option<&str> {
+        authorization(req, "bearer").filter(|t| !t.is_empty())
+    }
+
+    fn check(&self, req: &HttpRequest, oauth: &OAuthProvider) -> Outcome {
+        let present = match &self.credential {
+            Credential::ApiKey { key, location: KeyLocation::Header } => req.headers().get(key).is_some_and(|v| !v.is_empty()),
+            Credential::ApiKey { key, location: KeyLocation::Query } => {
+                serde_urlencoded::from_str::<Vec<(String, String)>>(req.query_string())
+                    .unwrap_or_default()
+                    .iter()
+                    .any(|(name, value)| name == key && !value.is_empty())
+            }
+            Credential::ApiKey { key, location: KeyLocation::Cookie } => req.cookie(key).is_some_and(|c| !c.value().is_empty()),
+            Credential::Bearer => Self::bearer_token(req).is_some(),
+            Credential::Basic => authorization(req, "basic")
+                .and_then(|c| STANDARD.decode(c).ok())
+                .is_some_and(|c| c.contains(&b':')),
+            Credential::Http { auth_scheme } => authorization(req, auth_scheme).is_some_and(|c| !c.is_empty()),
+            Credential::Oauth2 => {
+                let Some(token) = Self::bearer_token(req) else { return Outcome::Missing };
+                // Tokens from elsewhere can't be inspected, so only the mock's own are held to their scopes.
+                if let Ok(claims) = oauth.verify(token) {
+                    let granted: Vec<&str> = claims.get("scope").and_then(Value::as_str).unwrap_or_default().split(' ').collect();
+                    let missing: Vec<&str> = self.scopes.iter().map(String::as_str).filter(|s| !granted.contains(s)).collect();
+                    if !missing.is_empty() {
+                        return Outcome::Forbidden(format!("Token lacks scope {}", missing.join(" ")));
+                    }
+                }
+                true
+            }
+            Credential::Undefined => false,
+        };
+        if present { Outcome::Passed } else { Outcome::Missing }
+    }
+
+    fn challenge(&self) -> Option<String> {
+        match &self.credential {
+            Credential::Bearer | Credential::Oauth2 => Some("Bearer realm=\"RustMock\"".to_string()),
+            Credential::Basic => Some("Basic realm=\"RustMock\"".to_string()),
+            Credential::Http { auth_scheme } => Some(format!("{} realm=\"RustMock\"", auth_scheme)),
+            Credential::ApiKey { .. } | Credential::Undefined => None,
+        }
+    }
+}
+
+/// The credentials of the `Authorization` header if it uses `scheme`.
+fn authorization<'a>(req: &'a HttpRequest, scheme: &str) -> Option<&'a str> {
+    let header = req.headers().get("authorization")?.to_str().ok()?.trim();
+    let (given, credentials) = header.split_once(' ').unwrap_or((header, ""));
+    given.eq_ignore_ascii_case(scheme).then(|| credentials.trim())
+}
+
+/// Follows `#/components/securitySchemes/...` references to the scheme `name` stands for.
+fn resolve_scheme<'a>(components: &'a Components, name: &str) -> Option<&'a SecurityScheme> {
+    let schemes = &components.security_schemes;
+    let mut scheme = schemes.get(name)?;
+    // A chain longer than the map must loop.
+    for _ in 0..=schemes.len() {
+        match scheme {
+            ReferenceOr::Item(scheme) => return Some(scheme),
+            ReferenceOr::Reference { reference } => {
+                scheme = schemes.get(reference.strip_prefix("#/components/securitySchemes/")?)?;
+            }
+        }
+    }
+    None
+}
+
+impl SecurityConfig {
+    /// Builds the checks for `op`, or `None` when it (or the whole spec) needs no credentials.
+    ///
+    /// A requirement naming a scheme that can't be resolved keeps the operation closed rather
+    /// than letting every request through.
+    pub fn from_openapi(spec: &OpenAPI, op: &Operation) -> Option<SecurityConfig> {
+        let requirements = op.security.as_ref().or(spec.security.as_ref())?;
+        let requirements: Vec<Vec<SchemeCheck>> = requirements.iter()
+            .map(|requirement| requirement.iter()
+                .map(|(name, scopes)| {
+                    let Some(scheme) = spec.components.as_ref().and_then(|c| resolve_scheme(c, name)) else {
+                        warn!("Security scheme {:?} is not defined in components.securitySchemes; no request will satisfy it", name);
+                        return SchemeCheck { name: name.clone(), credential: Credential::Undefined, scopes: scopes.clone() };
+                    };
+                    let credential = match scheme {
+                        SecurityScheme::APIKey { location, name, .. } => Credential::ApiKey {
+                            key: name.clone(),
+                            location: match location {
+                                APIKeyLocation::Header => KeyLocation::Header,
+                                APIKeyLocation::Query => KeyLocation::Query,
+                                APIKeyLocation::Cookie => KeyLocation::Cookie,
+                            },
+                        },
+                        SecurityScheme::HTTP { scheme, .. } if scheme.eq_ignore_ascii_case("bearer") => Credential::Bearer,
+                        SecurityScheme::HTTP { scheme, .. } if scheme.eq_ignore_ascii_case("basic") => Credential::Basic,
+                        SecurityScheme::HTTP { scheme, .. } => Credential::Http { auth_scheme: scheme.clone() },
+                        SecurityScheme::OAuth2 { .. } | SecurityScheme::OpenIDConnect { .. } => Credential::Oauth2,
+                    };
+                    SchemeCheck { name: name.clone(), credential, scopes: scopes.clone() }
+                })
+                .collect())
+            .collect();
+        // An empty requirement (`{}`) makes credentials optional.
+        if requirements.is_empty() || requirements.iter().any(Vec::is_empty) {
+            return None;
+        }
+        Some(SecurityConfig {
+            requirements,
+            unauthorized: crate::extract_example_response_for_status(op, 401),
+            forbidden: crate::extract_example_response_for_status(op, 403),
+        })
+    }
+
+    pub fn validate(self) -> Result<SecurityConfig, String> {
+        if self.requirements.is_empty() || self.requirements.iter().any(Vec::is_empty) {
+            return Err("security.requirements needs at least one non-empty requirement".to_string());
+        }
+        Ok(self)
+    }
+
+    /// Passes requests satisfying any requirement; otherwise 403 when one was only short of
+    /// scopes, else 401 with a challenge for each HTTP scheme.
+    pub fn check(&self, req: &HttpRequest, oauth: &OAuthProvider) -> Result<(), Rejection> {
+        let mut forbidden = None;
+        for requirement in &self.requirements {
+            let mut missing = false;
+            let mut denied = None;
+            for scheme in requirement {
+                match scheme.check(req, oauth) {
+                    Outcome::Passed => {}
+                    Outcome::Missing => missing = true,
+                    Outcome::Forbidden(reason) => denied = Some(reason),
+                }
+            }
+            match (missing, denied) {
+                (false, None) => return Ok(()),
+                (false, Some(reason)) => forbidden = forbidden.or(Some(reason)),
+                (true, _) => {}
+            }
+        }
+
+        if let Some(reason) = forbidden {
+            return Err(Rejection {
+                status: 403,
+                body: self.forbidden.clone().unwrap_or_else(|| json!({"error": "Forbidden", "message": reason})),
+                challenges: Vec::new(),
+            });
+        }
+        let mut challenges = Vec::new();
+        for challenge in self.requirements.iter().flatten().filter_map(SchemeCheck::challenge) {
+            if !challenges.contains(&challenge) {
+                challenges.push(challenge);
+            }
+        }
+        let expected: Vec<&str> = self.requirements.iter().flatten().map(|s| s.name.as_str()).collect();
+        Err(Rejection {
+            status: 401,
+            body: self.unauthorized.clone().unwrap_or_else(|| {
+                json!({"error": "Unauthorized", "message": format!("Missing credentials for {}", expected.join(" or "))})
+            }),
+            challenges,
+        })
+    }
+}
@@ -0,0 +1,178 @@
+use actix_web::{web, HttpResponse, Responder};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+use crate::AppState;
+
+/// Which side of a shadowed endpoint answers the client; the other one is
+/// called in the background and compared against it.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ShadowMode {
+    Stub,
+    Proxy,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct Difference {
+    pub kind: String,
+    pub path: String,
+    pub stub: Option<Value>,
+    pub upstream: Option<Value>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ResponseDiff {
+    pub served_by: ShadowMode,
+    pub upstream_url: String,
+    pub upstream_status: Option<u16>,
+    pub differences: Vec<Difference>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Clone, Default)]
+pub struct DriftSummary {
+    pub comparisons: u64,
+    pub mismatches: u64,
+    pub errors: u64,
+    pub last_checked: Option<String>,
+    pub differing_paths: HashMap<String, u64>,
+}
+
+pub struct ResponseSnapshot<'a> {
+    pub status: u16,
+    pub headers: &'a HashMap<String, String>,
+    pub body: Option<&'a Value>,
+}
+
+/// Compares the stub and upstream responses by status, the headers the stub
+/// declares, and the shape of the JSON body (keys and value types, not values).
+pub fn diff_responses(stub: &ResponseSnapshot, upstream: &ResponseSnapshot) -> Vec<Difference> {
+    let mut differences = Vec::new();
+
+    if stub.status != upstream.status {
+        differences.push(Difference {
+            kind: "status".to_string(),
+            path: "status".to_string(),
+            stub: Some(json!(stub.status)),
+            upstream: Some(json!(upstream.status)),
+        });
+    }
+
+    let upstream_headers: HashMap<String, &String> = upstream.headers.iter()
+        .map(|(k, v)| (k.to_lowercase(), v))
+        .collect();
+    let mut header_names: Vec<&String> = stub.headers.keys().collect();
+    header_names.sort();
+    for name in header_names {
+        let stub_value = &stub.headers[name];
+        let upstream_value = upstream_headers.get(&name.to_lowercase());
+        let same = match upstream_value {
+            Some(v) if name.eq_ignore_ascii_case("content-type") => media_type(v) == media_type(stub_value),
+            Some(v) => *v == stub_value,
+            None => false,
+        };
+        if !same {
+            differences.push(Difference {
+                kind: "header".to_string(),
+                path: name.to_lowercase(),
+                stub: Some(json!(stub_value)),
+                upstream: upstream_value.map(|v| json!(v)),
+            });
+        }
+    }
+
+    match (stub.body, upstream.body) {
+        (Some(a), Some(b)) => diff_shape("", a, b, &mut differences),
+        (None, None) => {}
+        (a, b) => differences.push(Difference {
+            kind: "body".to_string(),
+            path: "".to_string(),
+            stub: a.map(|v| json!(type_name(v))),
+            upstream: b.map(|v| json!(type_name(v))),
+        }),
+    }
+
+    differences
+}
+
+fn media_type(content_type: &str) -> String {
+    content_type.split(';').next().unwrap_or("").trim().to_lowercase()
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn diff_shape(path: &str, stub: &Value, upstream: &Value, out: &mut Vec<Difference>) {
+    match (stub, upstream) {
+        (Value::Object(a), Value::Object(b)) => {
+            let mut keys: Vec<&String> = a.keys().chain(b.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let child = format!("{}/{}", path, key.replace('~', "~0").replace('/', "~1"));
+                match (a.get(key), b.get(key)) {
+                    (Some(x), Some(y)) => diff_shape(&child, x, y, out),
+                    (x, y) => out.push(Difference {
+                        kind: "body".to_string(),
+                        path: child,
+                        stub: x.map(|v| json!(type_name(v))),
+                        upstream: y.map(|v| json!(type_name(v))),
+                    }),
+                }
+            }
+        }
+        (Value::Array(a), Value::Array(b)) => {
+            for (i, (x, y)) in a.iter().zip(b.iter()).enumerate() {
+                diff_shape(&format!("{}/{}", path, i), x, y, out);
+            }
+        }
+        (a, b) if type_name(a) != type_name(b) => out.push(Difference {
+            kind: "body".to_string(),
+            path: path.to_string(),
+            stub: Some(json!(type_name(a))),
+            upstream: Some(json!(type_name(b))),
+        }),
+        _ => {}
+    }
+}
+
+/// Attaches a finished diff to its log entry and folds it into the per-endpoint summary.
+pub fn record_diff(data: &AppState, log_id: u64, endpoint_key: String, diff: ResponseDiff) {
+    {
+        let mut drift = data.drift.lock().unwrap();
+        let summary = drift.entry(endpoint_key).or_default();
+        summary.comparisons += 1;
+        summary.last_checked = Some(Local::now().to_rfc3339());
+        if diff.error.is_some() {
+            summary.errors += 1;
+        } else if !diff.differences.is_empty() {
+            summary.mismatches += 1;
+            for d in &diff.differences {
+                *summary.differing_paths.entry(format!("{} {}", d.kind, d.path)).or_insert(0) += 1;
+            }
+        }
+    }
+
+    data.logs.update(log_id, move |log| log.shadow_diff = Some(diff));
+}
+
+pub async fn get_drift(data: web::Data<AppState>) -> impl Responder {
+    let drift = data.drift.lock().unwrap();
+    HttpResponse::Ok().json(&*drift)
+}
+
+pub async fn clear_drift(data: web::Data<AppState>) -> impl Responder {
+    data.drift.lock().unwrap().clear();
+    HttpResponse::Ok().json(json!({"cleared": true}))
+}
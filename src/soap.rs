@@ -0,0 +1,358 @@
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use log::info;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use sxd_document::dom::Element;
+use sxd_document::parser;
+use sxd_xpath::{Context, Factory, XPath};
+
+use crate::AppState;
+
+const SOAP11_NS: &str = "http://schemas.xmlsoap.org/soap/envelope/";
+const SOAP12_NS: &str = "http://www.w3.org/2003/05/soap-envelope";
+
+/// Distinct expressions each worker keeps compiled before starting over.
+const MAX_COMPILED_XPATHS: usize = 256;
+
+thread_local! {
+    // sxd_xpath's compiled expressions can't be shared between threads, so each worker
+    // compiles an expression the first time it evaluates it.
+    static COMPILED_XPATHS: RefCell<HashMap<String, XPath>> = RefCell::new(HashMap::new());
+}
+
+fn compile(expr: &str) -> Result<XPath, String> {
+    match Factory::new().build(expr) {
+        Ok(Some(xpath)) => Ok(xpath),
+        _ => Err(format!("Invalid XPath: {}", expr)),
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct XPathMatch {
+    #[serde(rename = "xpath")]
+    pub expr: String,
+    /// Compare the string value of the expression; without it the expression must be true / non-empty.
+    #[serde(default)]
+    pub equals: Option<String>,
+}
+
+impl XPathMatch {
+    fn validate(&self) -> Result<(), String> {
+        compile(&self.expr).map(drop)
+    }
+
+    fn matches(&self, context: &Context, document: &sxd_document::dom::Document) -> bool {
+        COMPILED_XPATHS.with(|compiled| {
+            let mut compiled = compiled.borrow_mut();
+            if !compiled.contains_key(&self.expr) {
+                let Ok(xpath) = compile(&self.expr) else { return false };
+                if compiled.len() >= MAX_COMPILED_XPATHS {
+                    compiled.clear();
+                }
+                compiled.insert(self.expr.clone(), xpath);
+            }
+            match compiled[&self.expr].evaluate(context, document.root()) {
+                Ok(value) => match &self.equals {
+                    Some(expected) => value.string() == *expected,
+                    None => value.boolean(),
+                },
+                Err(_) => false,
+            }
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SoapFault {
+    /// `Client`/`Server` (SOAP 1.1) or `Sender`/`Receiver` (SOAP 1.2).
+    pub code: String,
+    pub reason: String,
+    #[serde(default)]
+    pub detail: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SoapStub {
+    pub path: String,
+    #[serde(default)]
+    pub soap_action: Option<String>,
+    #[serde(default, rename = "match")]
+    pub matchers: Vec<XPathMatch>,
+    /// Prefix to namespace URI bindings available to the XPath expressions.
+    #[serde(default)]
+    pub namespaces: HashMap<String, String>,
+    /// XML returned inside the SOAP Body, or as-is when `envelope` is false.
+    #[serde(default)]
+    pub response: String,
+    #[serde(default)]
+    pub fault: Option<SoapFault>,
+    #[serde(default = "default_envelope")]
+    pub envelope: bool,
+    #[serde(default)]
+    pub status: Option<u16>,
+}
+
+fn default_envelope() -> bool {
+    true
+}
+
+#[derive(Deserialize)]
+pub struct WsdlImport {
+    pub wsdl: String,
+    /// Overrides the path taken from the WSDL's `soap:address`.
+    #[serde(default)]
+    pub path: Option<String>,
+}
+
+pub struct SoapResult {
+    pub status: u16,
+    pub content_type: String,
+    pub body: String,
+    pub info: Value,
+    pub matched: String,
+}
+
+/// The SOAPAction from the 1.1 header or the 1.2 `action` content-type parameter.
+fn soap_action(req: &HttpRequest) -> Option<String> {
+    if let Some(action) = req.headers().get("soapaction").and_then(|v| v.to_str().ok()) {
+        return Some(action.trim().trim_matches('"').to_string());
+    }
+    let content_type = req.headers().get("content-type").and_then(|v| v.to_str().ok())?;
+    content_type.split(';')
+        .filter_map(|param| param.trim().strip_prefix("action="))
+        .map(|action| action.trim_matches('"').to_string())
+        .next()
+}
+
+fn first_child_element<'d>(element: Element<'d>) -> Option<Element<'d>> {
+    element.children().into_iter().find_map(|c| c.element())
+}
+
+/// Local name of the first element inside the SOAP Body, i.e. the operation being called.
+fn body_operation(root: Element) -> Option<String> {
+    if root.name().local_part() != "Envelope" {
+        return Some(root.name().local_part().to_string());
+    }
+    let body = root.children().into_iter()
+        .filter_map(|c| c.element())
+        .find(|e| e.name().local_part() == "Body")?;
+    first_child_element(body).map(|e| e.name().local_part().to_string())
+}
+
+fn xpath_matches(stub: &SoapStub, document: &sxd_document::dom::Document) -> bool {
+    let mut context = Context::new();
+    for (prefix, uri) in &stub.namespaces {
+        context.set_namespace(prefix, uri);
+    }
+    stub.matchers.iter().all(|m| m.matches(&context, document))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn fault_xml(fault: &SoapFault, soap12: bool) -> String {
+    let detail = fault.detail.as_deref().unwrap_or("");
+    if soap12 {
+        format!(
+            "<soap:Fault><soap:Code><soap:Value>soap:{}</soap:Value></soap:Code><soap:Reason><soap:Text xml:lang=\"en\">{}</soap:Text></soap:Reason>{}</soap:Fault>",
+            fault.code, escape(&fault.reason),
+            if detail.is_empty() { String::new() } else { format!("<soap:Detail>{}</soap:Detail>", detail) }
+        )
+    } else {
+        format!(
+            "<soap:Fault><faultcode>soap:{}</faultcode><faultstring>{}</faultstring>{}</soap:Fault>",
+            fault.code, escape(&fault.reason),
+            if detail.is_empty() { String::new() } else { format!("<detail>{}</detail>", detail) }
+        )
+    }
+}
+
+fn envelope(body: &str, soap12: bool) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?><soap:Envelope xmlns:soap=\"{}\"><soap:Body>{}</soap:Body></soap:Envelope>",
+        if soap12 { SOAP12_NS } else { SOAP11_NS },
+        body
+    )
+}
+
+/// Answers an XML request when SOAP stubs are registered for its path.
+pub fn handle(stubs: &[SoapStub], req: &HttpRequest, body: &[u8]) -> Option<SoapResult> {
+    if req.method() != actix_web::http::Method::POST || !stubs.iter().any(|s| s.path == req.path()) {
+        return None;
+    }
+
+    let content_type = req.headers().get("content-type").and_then(|v| v.to_str().ok()).unwrap_or("");
+    let soap12 = content_type.starts_with("application/soap+xml");
+    let response_type = if soap12 { "application/soap+xml; charset=utf-8" } else { "text/xml; charset=utf-8" };
+    let action = soap_action(req);
+
+    let xml = String::from_utf8_lossy(body);
+    let package = parser::parse(&xml);
+    let (operation, stub) = match &package {
+        Ok(package) => {
+            let document = package.as_document();
+            let operation = document.root().children().into_iter().find_map(|c| c.element()).and_then(body_operation);
+            let stub = stubs.iter().find(|s| {
+                s.path == req.path()
+                    && s.soap_action.as_ref().is_none_or(|a| Some(a) == action.as_ref())
+                    && xpath_matches(s, &document)
+            });
+            (operation, stub)
+        }
+        Err(_) => (None, None),
+    };
+
+    let info = json!({"action": action, "operation": operation, "soap_version": if soap12 { "1.2" } else { "1.1" }});
+    let client_code = if soap12 { "Sender" } else { "Client" };
+
+    let Some(stub) = stub else {
+        let reason = match &package {
+            Err(e) => format!("Malformed XML request: {:?}", e),
+            Ok(_) => format!("No stub matches SOAPAction {:?}", action.as_deref().unwrap_or("")),
+        };
+        let fault = SoapFault { code: client_code.to_string(), reason, detail: None };
+        return Some(SoapResult {
+            status: 500,
+            content_type: response_type.to_string(),
+            body: envelope(&fault_xml(&fault, soap12), soap12),
+            info,
+            matched: "SOAP (no match)".to_string(),
+        });
+    };
+
+    let (status, content) = match &stub.fault {
+        Some(fault) => (stub.status.unwrap_or(500), fault_xml(fault, soap12)),
+        None => (stub.status.unwrap_or(200), stub.response.clone()),
+    };
+    let (body, content_type) = if stub.envelope {
+        (envelope(&content, soap12), response_type.to_string())
+    } else {
+        (content, "application/xml".to_string())
+    };
+    info!("SOAP {} action={:?} operation={:?} -> {}", stub.path, action, operation, status);
+
+    Some(SoapResult {
+        status,
+        content_type,
+        body,
+        info,
+        matched: format!("SOAP {}", stub.soap_action.as_deref().or(operation.as_deref()).unwrap_or(&stub.path)),
+    })
+}
+
+/// Builds one stub per SOAP binding operation, matched by SOAPAction or by the Body element name.
+///
+/// An operation offered by both a SOAP 1.1 and a SOAP 1.2 binding gets a single stub, which
+/// answers each request in its own version.
+pub fn import_wsdl(wsdl: &str, path_override: Option<&str>) -> Result<Vec<SoapStub>, String> {
+    let package = parser::parse(wsdl).map_err(|e| format!("Invalid WSDL: {:?}", e))?;
+    let document = package.as_document();
+    let root = document.root().children().into_iter().find_map(|c| c.element())
+        .ok_or_else(|| "Invalid WSDL: no root element".to_string())?;
+    let target_ns = root.attribute_value("targetNamespace").unwrap_or("").to_string();
+
+    let path = match path_override {
+        Some(path) => path.to_string(),
+        None => {
+            let location = sxd_xpath::evaluate_xpath(&document, "string(//*[local-name()='service']//*[local-name()='address']/@location)")
+                .map(|v| v.string())
+                .unwrap_or_default();
+            match location.split_once("://").map(|(_, rest)| rest) {
+                Some(rest) => rest.find('/').map(|i| rest[i..].to_string()).unwrap_or_else(|| "/".to_string()),
+                None => return Err("WSDL has no soap:address; pass a path".to_string()),
+            }
+        }
+    };
+
+    let operations = sxd_xpath::evaluate_xpath(&document, "//*[local-name()='binding']/*[local-name()='operation']")
+        .map_err(|e| e.to_string())?;
+    let sxd_xpath::Value::Nodeset(nodes) = operations else {
+        return Ok(Vec::new());
+    };
+
+    let mut stubs = Vec::new();
+    let mut seen = HashSet::new();
+    for node in nodes.document_order() {
+        let Some(operation) = node.element() else { continue };
+        let Some(name) = operation.attribute_value("name") else { continue };
+        let action = operation.children().into_iter()
+            .filter_map(|c| c.element())
+            .find(|e| e.name().local_part() == "operation")
+            .and_then(|e| e.attribute_value("soapAction"))
+            .filter(|a| !a.is_empty())
+            .map(str::to_string);
+        if !seen.insert((name.to_string(), action.clone())) {
+            continue;
+        }
+        let matchers = match action {
+            Some(_) => Vec::new(),
+            None => vec![XPathMatch {
+                expr: format!("local-name(/*[local-name()='Envelope']/*[local-name()='Body']/*[1]) = '{}'", name),
+                equals: None,
+            }],
+        };
+        stubs.push(SoapStub {
+            path: path.clone(),
+            soap_action: action,
+            matchers,
+            namespaces: HashMap::new(),
+            response: format!("<tns:{}Response xmlns:tns=\"{}\"/>", name, target_ns),
+            fault: None,
+            envelope: true,
+            status: None,
+        });
+    }
+    Ok(stubs)
+}
+
+pub async fn get_soap_stubs(data: web::Data<AppState>) -> impl Responder {
+    let stubs = data.soap.read().unwrap();
+    HttpResponse::Ok().json(&*stubs)
+}
+
+pub async fn add_soap_stub(data: web::Data<AppState>, stub: web::Json<SoapStub>) -> impl Responder {
+    let stub = stub.into_inner();
+    for m in &stub.matchers {
+        if let Err(e) = m.validate() {
+            return HttpResponse::BadRequest().json(json!({"error": e}));
+        }
+    }
+    info!("Added SOAP stub {} action={:?}", stub.path, stub.soap_action);
+    data.soap.write().unwrap().push(stub);
+    HttpResponse::Ok().json(json!({"added": true}))
+}
+
+#[derive(Deserialize)]
+pub struct SoapStubQuery {
+    pub path: Option<String>,
+    pub soap_action: Option<String>,
+}
+
+/// Removes the stubs for `path` (and `soap_action`, when given), or every stub without a query.
+pub async fn remove_soap_stubs(data: web::Data<AppState>, query: web::Query<SoapStubQuery>) -> impl Responder {
+    let mut stubs = data.soap.write().unwrap();
+    let Some(path) = &query.path else {
+        stubs.clear();
+        return HttpResponse::Ok().json(json!({"cleared": true}));
+    };
+    let before = stubs.len();
+    stubs.retain(|s| s.path != *path || query.soap_action.as_ref().is_some_and(|a| s.soap_action.as_ref() != Some(a)));
+    let removed = before - stubs.len();
+    info!("Removed {} SOAP stub(s) for {} action={:?}", removed, path, query.soap_action);
+    HttpResponse::Ok().json(json!({"removed": removed}))
+}
+
+pub async fn import_wsdl_handler(data: web::Data<AppState>, req: web::Json<WsdlImport>) -> impl Responder {
+    match import_wsdl(&req.wsdl, req.path.as_deref()) {
+        Ok(stubs) => {
+            info!("Imported {} SOAP operation(s) from WSDL", stubs.len());
+            let body = json!({"imported": stubs.len(), "stubs": stubs});
+            data.soap.write().unwrap().extend(stubs);
+            HttpResponse::Ok().json(body)
+        }
+        Err(e) => HttpResponse::BadRequest().json(json!({"error": e})),
+    }
+}
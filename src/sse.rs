@@ -0,0 +1,142 @@
+use actix_web::{web, HttpResponse, Responder};
+use log::info;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+use crate::{matches_path_template, AppState, StreamCapture};
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct SseConfig {
+    #[serde(default)]
+    pub events: Vec<SseEvent>,
+    /// Replay `events` from the start once the last one has been sent.
+    #[serde(default, rename = "loop")]
+    pub repeat: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct SseEvent {
+    #[serde(default)]
+    pub event: Option<String>,
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub data: Value,
+    #[serde(default)]
+    pub retry: Option<u64>,
+    /// Wait before sending this event.
+    #[serde(default)]
+    pub delay_ms: u64,
+}
+
+#[derive(Deserialize)]
+pub struct PushRequest {
+    pub path: String,
+    #[serde(flatten)]
+    pub event: SseEvent,
+}
+
+pub struct SseClient {
+    pub path: String,
+    pub endpoint_path: String,
+    pub sender: mpsc::UnboundedSender<web::Bytes>,
+}
+
+impl SseEvent {
+    pub fn encode(&self) -> web::Bytes {
+        let mut out = String::new();
+        if let Some(id) = &self.id {
+            out.push_str(&format!("id: {}\n", id));
+        }
+        if let Some(event) = &self.event {
+            out.push_str(&format!("event: {}\n", event));
+        }
+        if let Some(retry) = self.retry {
+            out.push_str(&format!("retry: {}\n", retry));
+        }
+        let data = match &self.data {
+            Value::String(s) => s.clone(),
+            Value::Null => String::new(),
+            other => other.to_string(),
+        };
+        for line in data.split('\n') {
+            out.push_str(&format!("data: {}\n", line));
+        }
+        out.push('\n');
+        web::Bytes::from(out)
+    }
+}
+
+/// Opens an event stream that plays the scripted events and stays open for pushed ones.
+pub fn open_stream(
+    data: web::Data<AppState>,
+    log_id: u64,
+    path: &str,
+    endpoint_path: &str,
+    cfg: SseConfig,
+) -> HttpResponse {
+    let (sender, mut receiver) = mpsc::unbounded_channel::<web::Bytes>();
+    {
+        let mut clients = data.sse_clients.lock().unwrap();
+        clients.retain(|c| !c.sender.is_closed());
+        clients.push(SseClient {
+            path: path.to_string(),
+            endpoint_path: endpoint_path.to_string(),
+            sender: sender.clone(),
+        });
+    }
+
+    actix_web::rt::spawn(async move {
+        loop {
+            for event in &cfg.events {
+                if event.delay_ms > 0 {
+                    actix_web::rt::time::sleep(Duration::from_millis(event.delay_ms)).await;
+                }
+                if sender.send(event.encode()).is_err() {
+                    return;
+                }
+            }
+            if !cfg.repeat || cfg.events.is_empty() {
+                return;
+            }
+        }
+    });
+
+    let mut capture = StreamCapture { data, log_id, prefix: Vec::new(), size: 0 };
+    let stream = futures_util::stream::poll_fn(move |cx| {
+        receiver.poll_recv(cx).map(|chunk| chunk.map(|bytes| {
+            capture.push(&bytes);
+            Ok::<_, actix_web::Error>(bytes)
+        }))
+    });
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("cache-control", "no-cache"))
+        .streaming(stream)
+}
+
+pub async fn push_event(data: web::Data<AppState>, req: web::Json<PushRequest>) -> impl Responder {
+    let bytes = req.event.encode();
+    let mut clients = data.sse_clients.lock().unwrap();
+    clients.retain(|c| !c.sender.is_closed());
+    let delivered = clients.iter()
+        .filter(|c| c.path == req.path || c.endpoint_path == req.path || matches_path_template(&req.path, &c.path))
+        .filter(|c| c.sender.send(bytes.clone()).is_ok())
+        .count();
+    info!("Pushed SSE event to {} client(s) of {}", delivered, req.path);
+    HttpResponse::Ok().json(json!({"delivered": delivered}))
+}
+
+pub async fn get_clients(data: web::Data<AppState>) -> impl Responder {
+    let mut clients = data.sse_clients.lock().unwrap();
+    clients.retain(|c| !c.sender.is_closed());
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for client in clients.iter() {
+        *counts.entry(client.path.as_str()).or_insert(0) += 1;
+    }
+    HttpResponse::Ok().json(counts)
+}
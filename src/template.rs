@@ -0,0 +1,77 @@
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// What runtime expressions can refer to.
+pub struct Context {
+    pub method: String,
+    pub url: String,
+    pub path_params: HashMap<String, String>,
+    pub query: HashMap<String, String>,
+    pub headers: HashMap<String, String>,
+    pub request_body: Option<Value>,
+    pub response_body: Option<Value>,
+}
+
+impl Context {
+    /// Resolves an OpenAPI runtime expression (without braces).
+    fn resolve(&self, expr: &str) -> Option<Value> {
+        let from_body = |body: &Option<Value>, rest: &str| {
+            let pointer = rest.strip_prefix('#').unwrap_or("");
+            body.as_ref().and_then(|b| b.pointer(pointer)).cloned()
+        };
+        match expr {
+            "$url" => Some(Value::String(self.url.clone())),
+            "$method" => Some(Value::String(self.method.clone())),
+            _ => {
+                if let Some(rest) = expr.strip_prefix("$request.body") {
+                    from_body(&self.request_body, rest)
+                } else if let Some(rest) = expr.strip_prefix("$response.body") {
+                    from_body(&self.response_body, rest)
+                } else if let Some(name) = expr.strip_prefix("$request.query.") {
+                    self.query.get(name).cloned().map(Value::String)
+                } else if let Some(name) = expr.strip_prefix("$request.path.") {
+                    self.path_params.get(name).cloned().map(Value::String)
+                } else if let Some(name) = expr.strip_prefix("$request.header.") {
+                    self.headers.get(&name.to_lowercase()).cloned().map(Value::String)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    /// Replaces every `{$...}` in `text`; unknown expressions become empty.
+    pub fn render_str(&self, text: &str) -> String {
+        let mut out = String::new();
+        let mut rest = text;
+        while let Some(start) = rest.find("{$") {
+            let Some(len) = rest[start..].find('}') else { break };
+            out.push_str(&rest[..start]);
+            match self.resolve(&rest[start + 1..start + len]) {
+                Some(Value::String(s)) => out.push_str(&s),
+                Some(Value::Null) | None => {}
+                Some(other) => out.push_str(&other.to_string()),
+            }
+            rest = &rest[start + len + 1..];
+        }
+        out.push_str(rest);
+        out
+    }
+
+    /// Strings that are a single expression keep the referenced value's JSON type.
+    pub fn render(&self, value: &Value) -> Value {
+        match value {
+            Value::String(s) => {
+                let whole = s.strip_prefix('{').and_then(|s| s.strip_suffix('}'))
+                    .filter(|expr| expr.starts_with('$') && !expr.contains('}'));
+                match whole {
+                    Some(expr) => self.resolve(expr).unwrap_or(Value::Null),
+                    None => Value::String(self.render_str(s)),
+                }
+            }
+            Value::Array(items) => Value::Array(items.iter().map(|v| self.render(v)).collect()),
+            Value::Object(fields) => Value::Object(fields.iter().map(|(k, v)| (k.clone(), self.render(v))).collect::<Map<_, _>>()),
+            other => other.clone(),
+        }
+    }
+}
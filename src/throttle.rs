@@ -0,0 +1,131 @@
+use actix_web::body::{BodySize, BodyStream, BoxBody, MessageBody, SizedStream};
+use actix_web::{web, HttpResponse, Responder};
+use bytes::Bytes;
+use log::info;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::pin::Pin;
+use std::time::Duration;
+
+use crate::AppState;
+
+/// Chunks per second sent when only `bytes_per_sec` is given.
+const CHUNKS_PER_SEC: u64 = 10;
+
+/// How a response is slowed down on its way to the client.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ThrottleConfig {
+    /// Wait before the status line and headers are sent.
+    #[serde(default)]
+    pub ttfb_ms: u64,
+    /// Caps body throughput.
+    #[serde(default)]
+    pub bytes_per_sec: Option<u64>,
+    /// Sends the body in pieces of this many bytes.
+    #[serde(default)]
+    pub chunk_size: Option<usize>,
+    /// Pause between body chunks.
+    #[serde(default)]
+    pub chunk_delay_ms: u64,
+}
+
+impl ThrottleConfig {
+    pub fn validate(self) -> Result<ThrottleConfig, String> {
+        if self.bytes_per_sec == Some(0) || self.chunk_size == Some(0) {
+            return Err("throttle.bytes_per_sec and throttle.chunk_size must be above 0".to_string());
+        }
+        Ok(self)
+    }
+
+    fn slows_body(&self) -> bool {
+        self.bytes_per_sec.is_some() || self.chunk_size.is_some() || self.chunk_delay_ms > 0
+    }
+
+    fn chunk_len(&self) -> usize {
+        match (self.chunk_size, self.bytes_per_sec) {
+            (Some(size), _) => size,
+            (None, Some(rate)) => (rate / CHUNKS_PER_SEC).max(1) as usize,
+            (None, None) => usize::MAX,
+        }
+    }
+
+    /// Waits out the time to first byte, then hands back `response` with its body dripped out.
+    ///
+    /// Works on any body, so stubbed and proxied responses (streamed or not) are slowed alike;
+    /// a known `Content-Length` is kept.
+    pub async fn apply(&self, response: HttpResponse) -> HttpResponse {
+        if self.ttfb_ms > 0 {
+            actix_web::rt::time::sleep(Duration::from_millis(self.ttfb_ms)).await;
+        }
+        if !self.slows_body() {
+            return response;
+        }
+        let (response, body) = response.into_parts();
+        let size = body.size();
+        let drip = Drip { body, pending: Bytes::new(), config: self.clone(), started: false };
+        let stream = futures_util::stream::unfold(drip, Drip::next);
+        let body = match size {
+            BodySize::None => return response.set_body(BoxBody::new(())),
+            BodySize::Sized(0) => return response.set_body(BoxBody::new(Bytes::new())),
+            BodySize::Sized(len) => BoxBody::new(SizedStream::new(len, stream)),
+            BodySize::Stream => BoxBody::new(BodyStream::new(stream)),
+        };
+        response.set_body(body)
+    }
+}
+
+/// A response body being re-chunked and paced.
+struct Drip {
+    body: BoxBody,
+    pending: Bytes,
+    config: ThrottleConfig,
+    started: bool,
+}
+
+type DripItem = Result<Bytes, Box<dyn std::error::Error>>;
+
+impl Drip {
+    async fn next(mut self) -> Option<(DripItem, Drip)> {
+        while self.pending.is_empty() {
+            match std::future::poll_fn(|cx| Pin::new(&mut self.body).poll_next(cx)).await? {
+                Ok(bytes) => self.pending = bytes,
+                Err(e) => return Some((Err(e), self)),
+            }
+        }
+        let chunk = self.pending.split_to(self.config.chunk_len().min(self.pending.len()));
+
+        let mut wait = Duration::ZERO;
+        if self.started {
+            wait += Duration::from_millis(self.config.chunk_delay_ms);
+        }
+        if let Some(rate) = self.config.bytes_per_sec {
+            wait += Duration::from_secs_f64(chunk.len() as f64 / rate as f64);
+        }
+        self.started = true;
+        if !wait.is_zero() {
+            actix_web::rt::time::sleep(wait).await;
+        }
+        Some((Ok(chunk), self))
+    }
+}
+
+pub async fn get_throttle(data: web::Data<AppState>) -> impl Responder {
+    HttpResponse::Ok().json(json!({"throttle": *data.throttle.read().unwrap()}))
+}
+
+/// Sets the throttle for all mocked traffic without one of its own.
+pub async fn set_throttle(data: web::Data<AppState>, cfg: web::Json<ThrottleConfig>) -> impl Responder {
+    match cfg.into_inner().validate() {
+        Ok(throttle) => {
+            info!("Global throttle: {:?}", throttle);
+            *data.throttle.write().unwrap() = Some(throttle.clone());
+            HttpResponse::Ok().json(json!({"throttle": throttle}))
+        }
+        Err(e) => HttpResponse::BadRequest().json(json!({"error": e})),
+    }
+}
+
+pub async fn clear_throttle(data: web::Data<AppState>) -> impl Responder {
+    let removed = data.throttle.write().unwrap().take().is_some();
+    HttpResponse::Ok().json(json!({"removed": removed}))
+}
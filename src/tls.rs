@@ -0,0 +1,120 @@
+use chrono::{Datelike, Duration, Local, NaiveDate};
+use log::info;
+use rcgen::{
+    date_time_ymd, BasicConstraints, CertificateParams, DnType, ExtendedKeyUsagePurpose, IsCa, Issuer, KeyPair,
+    KeyUsagePurpose,
+};
+use rustls::pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer};
+use std::{fs, io, path::{Path, PathBuf}, sync::Arc};
+
+pub const DEFAULT_CERT_DIR: &str = ".rustmock/certs";
+pub const DEFAULT_HOSTNAMES: &[&str] = &["localhost", "127.0.0.1", "::1"];
+
+const CA_CERT: &str = "rustmock-ca.pem";
+const CA_KEY: &str = "rustmock-ca-key.pem";
+const LEAF_CERT: &str = "cert.pem";
+const LEAF_KEY: &str = "key.pem";
+/// Browsers reject leaf certificates valid for longer than this.
+const LEAF_VALIDITY_DAYS: i64 = 397;
+
+fn rcgen_error(e: rcgen::Error) -> io::Error {
+    io::Error::other(format!("Certificate generation failed: {}", e))
+}
+
+/// rustls configuration from a PEM cert chain and key, or from certificates generated in
+/// `cert_dir` for `hostnames` when neither is given.
+pub fn server_config(
+    cert: Option<&str>,
+    key: Option<&str>,
+    hostnames: &[String],
+    cert_dir: &Path,
+) -> io::Result<rustls::ServerConfig> {
+    let (cert, key) = match (cert, key) {
+        (Some(cert), Some(key)) => (PathBuf::from(cert), PathBuf::from(key)),
+        (None, None) => generate(hostnames, cert_dir)?,
+        _ => return Err(io::Error::other("TLS certificate and key must be given together")),
+    };
+    load(&cert, &key)
+}
+
+fn load(cert: &Path, key: &Path) -> io::Result<rustls::ServerConfig> {
+    let invalid = |path: &Path, e: rustls::pki_types::pem::Error| {
+        io::Error::other(format!("Cannot read {}: {}", path.display(), e))
+    };
+    let certs = CertificateDer::pem_file_iter(cert)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| invalid(cert, e))?;
+    let key = PrivateKeyDer::from_pem_file(key).map_err(|e| invalid(key, e))?;
+
+    rustls::ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(io::Error::other)?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(io::Error::other)
+}
+
+/// Loads the local CA from `dir`, creating it on first use so it only has to be trusted once.
+fn local_ca(dir: &Path) -> io::Result<(Issuer<'static, KeyPair>, String)> {
+    let cert_path = dir.join(CA_CERT);
+    let key_path = dir.join(CA_KEY);
+    if cert_path.is_file() && key_path.is_file() {
+        let cert_pem = fs::read_to_string(&cert_path)?;
+        let key = KeyPair::from_pem(&fs::read_to_string(&key_path)?).map_err(rcgen_error)?;
+        let issuer = Issuer::from_ca_cert_pem(&cert_pem, key).map_err(rcgen_error)?;
+        return Ok((issuer, cert_pem));
+    }
+
+    let key = KeyPair::generate().map_err(rcgen_error)?;
+    let mut params = CertificateParams::default();
+    params.is_ca = IsCa::Ca(BasicConstraints::Constrained(0));
+    params.distinguished_name.push(DnType::CommonName, "RustMock Local CA");
+    params.distinguished_name.push(DnType::OrganizationName, "RustMock");
+    params.key_usages = vec![KeyUsagePurpose::KeyCertSign, KeyUsagePurpose::CrlSign, KeyUsagePurpose::DigitalSignature];
+    let cert_pem = params.self_signed(&key).map_err(rcgen_error)?.pem();
+
+    fs::write(&cert_path, &cert_pem)?;
+    write_private(&key_path, &key.serialize_pem())?;
+    info!("Generated local CA {}; add it to your trust store to trust RustMock's HTTPS", cert_path.display());
+    Ok((Issuer::new(params, key), cert_pem))
+}
+
+/// Issues a leaf certificate for `hostnames` from the local CA and writes it next to the CA.
+fn generate(hostnames: &[String], dir: &Path) -> io::Result<(PathBuf, PathBuf)> {
+    fs::create_dir_all(dir)?;
+    let (issuer, ca_pem) = local_ca(dir)?;
+
+    let key = KeyPair::generate().map_err(rcgen_error)?;
+    let mut params = CertificateParams::new(hostnames.to_vec()).map_err(rcgen_error)?;
+    let common_name = hostnames.first().map(String::as_str).unwrap_or("localhost");
+    params.distinguished_name.push(DnType::CommonName, common_name);
+    params.key_usages = vec![KeyUsagePurpose::DigitalSignature, KeyUsagePurpose::KeyEncipherment];
+    params.extended_key_usages = vec![ExtendedKeyUsagePurpose::ServerAuth];
+    params.use_authority_key_identifier_extension = true;
+    let ymd = |date: NaiveDate| date_time_ymd(date.year(), date.month() as u8, date.day() as u8);
+    let today = Local::now().date_naive();
+    params.not_before = ymd(today - Duration::days(1));
+    params.not_after = ymd(today + Duration::days(LEAF_VALIDITY_DAYS));
+    let cert = params.signed_by(&key, &issuer).map_err(rcgen_error)?;
+
+    let cert_path = dir.join(LEAF_CERT);
+    let key_path = dir.join(LEAF_KEY);
+    fs::write(&cert_path, format!("{}{}", cert.pem(), ca_pem))?;
+    write_private(&key_path, &key.serialize_pem())?;
+    info!("Issued TLS certificate for {} in {}", hostnames.join(", "), dir.display());
+    Ok((cert_path, key_path))
+}
+
+fn write_private(path: &Path, contents: &str) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::io::Write;
+        use std::os::unix::fs::OpenOptionsExt;
+        let mut file = fs::OpenOptions::new().write(true).create(true).truncate(true).mode(0o600).open(path)?;
+        file.write_all(contents.as_bytes())
+    }
+    #[cfg(not(unix))]
+    {
+        fs::write(path, contents)
+    }
+}
@@ -0,0 +1,120 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum VariantPolicy {
+    /// Pick each response at random in proportion to its weight.
+    #[default]
+    Weighted,
+    /// Cycle through the responses in order.
+    RoundRobin,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct VariantsConfig {
+    #[serde(default)]
+    pub policy: VariantPolicy,
+    /// Makes weighted picks repeatable; a random seed is chosen and reported when omitted.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    pub responses: Vec<ResponseVariant>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ResponseVariant {
+    /// Label shown in the request log.
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default = "default_status")]
+    pub status: u16,
+    /// Falls back to the endpoint's response.
+    #[serde(default)]
+    pub response: Option<Value>,
+    /// Added to the endpoint's headers.
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+}
+
+fn default_status() -> u16 {
+    200
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+/// Which variant served a request; with the seed, `sequence` is enough to replay the pick.
+#[derive(Serialize, Clone, Debug)]
+pub struct VariantChoice {
+    pub index: usize,
+    pub name: Option<String>,
+    pub sequence: u64,
+    pub seed: u64,
+    pub policy: VariantPolicy,
+}
+
+/// An endpoint's variants with the counter shared by every clone of the endpoint.
+#[derive(Clone, Debug)]
+pub struct Variants {
+    pub config: VariantsConfig,
+    counter: Arc<AtomicU64>,
+}
+
+impl Variants {
+    pub fn new(mut config: VariantsConfig) -> Result<Variants, String> {
+        if config.responses.is_empty() {
+            return Err("variants.responses must not be empty".to_string());
+        }
+        if config.policy == VariantPolicy::Weighted && config.responses.iter().all(|v| v.weight == 0) {
+            return Err("At least one variant needs a weight above 0".to_string());
+        }
+        config.seed.get_or_insert_with(|| RandomState::new().hash_one(0u8));
+        Ok(Variants { config, counter: Arc::new(AtomicU64::new(0)) })
+    }
+
+    /// Picks the variant for the next request.
+    pub fn choose(&self) -> (&ResponseVariant, VariantChoice) {
+        let sequence = self.counter.fetch_add(1, Ordering::Relaxed);
+        let seed = self.config.seed.unwrap_or_default();
+        let responses = &self.config.responses;
+        let index = match self.config.policy {
+            VariantPolicy::RoundRobin => (sequence % responses.len() as u64) as usize,
+            VariantPolicy::Weighted => {
+                let total: u64 = responses.iter().map(|v| v.weight as u64).sum();
+                let mut draw = splitmix64(seed.wrapping_add(sequence)) % total;
+                responses.iter()
+                    .position(|v| {
+                        let hit = draw < v.weight as u64;
+                        draw = draw.saturating_sub(v.weight as u64);
+                        hit
+                    })
+                    .unwrap_or(0)
+            }
+        };
+        let variant = &responses[index];
+        let choice = VariantChoice {
+            index,
+            name: variant.name.clone(),
+            sequence,
+            seed,
+            policy: self.config.policy,
+        };
+        (variant, choice)
+    }
+}
+
+/// A well-mixed 64-bit hash, so consecutive sequence numbers give independent draws.
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
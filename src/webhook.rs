@@ -0,0 +1,157 @@
+use actix_web::web;
+use chrono::Local;
+use log::{info, warn};
+use openapiv3::Operation;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::template::Context;
+use crate::AppState;
+
+const CALLBACK_TIMEOUT: Duration = Duration::from_secs(10);
+/// Most retries a callback may ask for.
+pub const MAX_RETRIES: u32 = 10;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct WebhookConfig {
+    /// May contain runtime expressions such as `{$request.body#/callback_url}`.
+    pub url: String,
+    #[serde(default = "default_method")]
+    pub method: String,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    #[serde(default)]
+    pub body: Option<Value>,
+    /// Wait after the stub has responded before the first attempt.
+    #[serde(default)]
+    pub delay_ms: u64,
+    /// Extra attempts after a failed (non-2xx or unreachable) one.
+    #[serde(default)]
+    pub retries: u32,
+    #[serde(default = "default_retry_delay")]
+    pub retry_delay_ms: u64,
+}
+
+/// Checks that no callback asks for more than [`MAX_RETRIES`] retries.
+pub fn validate(callbacks: &[WebhookConfig]) -> Result<(), String> {
+    match callbacks.iter().find(|c| c.retries > MAX_RETRIES) {
+        Some(callback) => Err(format!("Callback to {} asks for {} retries; at most {} are allowed", callback.url, callback.retries, MAX_RETRIES)),
+        None => Ok(()),
+    }
+}
+
+fn default_method() -> String {
+    "POST".to_string()
+}
+
+fn default_retry_delay() -> u64 {
+    1000
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct CallbackAttempt {
+    pub url: String,
+    pub method: String,
+    pub attempt: u32,
+    pub request_body: Option<Value>,
+    pub status: Option<u16>,
+    pub error: Option<String>,
+    pub timestamp: String,
+}
+
+fn record_attempt(data: &AppState, log_id: u64, attempt: CallbackAttempt) {
+    data.logs.update(log_id, move |log| log.callbacks.get_or_insert_with(Vec::new).push(attempt));
+}
+
+async fn send(client: &reqwest::Client, method: &str, url: &str, headers: &HashMap<String, String>, body: &Option<Value>) -> Result<u16, String> {
+    let method = reqwest::Method::from_bytes(method.to_uppercase().as_bytes()).map_err(|e| e.to_string())?;
+    let mut request = client.request(method, url).timeout(CALLBACK_TIMEOUT);
+    for (name, value) in headers {
+        request = request.header(name, value);
+    }
+    if let Some(body) = body {
+        request = request.json(body);
+    }
+    request.send().await.map(|r| r.status().as_u16()).map_err(|e| e.to_string())
+}
+
+/// Runs each callback in the background, recording every attempt on the stub's log entry.
+pub fn schedule(data: web::Data<AppState>, log_id: u64, callbacks: Vec<WebhookConfig>, ctx: Context) {
+    for callback in callbacks {
+        let data = data.clone();
+        let url = ctx.render_str(&callback.url);
+        let headers: HashMap<String, String> = callback.headers.iter()
+            .map(|(k, v)| (k.clone(), ctx.render_str(v)))
+            .collect();
+        let body = callback.body.as_ref().map(|b| ctx.render(b));
+
+        actix_web::rt::spawn(async move {
+            if callback.delay_ms > 0 {
+                actix_web::rt::time::sleep(Duration::from_millis(callback.delay_ms)).await;
+            }
+            let client = reqwest::Client::new();
+            for attempt in 1..=callback.retries.saturating_add(1) {
+                let result = send(&client, &callback.method, &url, &headers, &body).await;
+                let success = matches!(result, Ok(status) if (200..300).contains(&status));
+                match &result {
+                    Ok(status) => info!("Callback {} {} attempt {} -> {}", callback.method, url, attempt, status),
+                    Err(e) => warn!("Callback {} {} attempt {} failed: {}", callback.method, url, attempt, e),
+                }
+                record_attempt(&data, log_id, CallbackAttempt {
+                    url: url.clone(),
+                    method: callback.method.clone(),
+                    attempt,
+                    request_body: body.clone(),
+                    status: result.as_ref().ok().copied(),
+                    error: result.err(),
+                    timestamp: Local::now().to_rfc3339(),
+                });
+                if success {
+                    break;
+                }
+                if attempt <= callback.retries {
+                    actix_web::rt::time::sleep(Duration::from_millis(callback.retry_delay_ms)).await;
+                }
+            }
+        });
+    }
+}
+
+/// Callbacks declared on an OpenAPI operation, using the request body example when there is one.
+pub fn from_openapi(op: &Operation) -> Vec<WebhookConfig> {
+    let methods = |item: &openapiv3::PathItem| {
+        [
+            ("GET", item.get.clone()),
+            ("POST", item.post.clone()),
+            ("PUT", item.put.clone()),
+            ("PATCH", item.patch.clone()),
+            ("DELETE", item.delete.clone()),
+        ]
+    };
+    let mut callbacks = Vec::new();
+    for callback in op.callbacks.values() {
+        for (expression, item) in callback {
+            for (method, callback_op) in methods(item) {
+                let Some(callback_op) = callback_op else { continue };
+                let body = callback_op.request_body.as_ref()
+                    .and_then(|rb| match rb {
+                        openapiv3::ReferenceOr::Item(rb) => rb.content.get("application/json"),
+                        _ => None,
+                    })
+                    .and_then(|media| media.example.clone());
+                callbacks.push(WebhookConfig {
+                    url: expression.clone(),
+                    method: method.to_string(),
+                    headers: HashMap::new(),
+                    body,
+                    delay_ms: 0,
+                    retries: 0,
+                    retry_delay_ms: default_retry_delay(),
+                });
+            }
+        }
+    }
+    callbacks
+}
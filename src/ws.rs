@@ -0,0 +1,447 @@
+use actix_web::{http::header::HeaderMap, web, HttpRequest, HttpResponse, Responder};
+use actix_ws::{AggregatedMessage, CloseCode, Item};
+use awc::ws::Frame;
+use base64::Engine;
+use bytes::{Bytes, BytesMut};
+use chrono::Local;
+use futures_util::{SinkExt, StreamExt};
+use log::{info, warn};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::{router, AppState, RequestLog};
+
+/// Frames beyond this count are still relayed but no longer logged.
+const MAX_LOGGED_FRAMES: usize = 1000;
+
+/// Largest fragmented upstream message reassembled before the connection is closed.
+const MAX_MESSAGE_SIZE: usize = 1024 * 1024;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct WsEndpointConfig {
+    pub path: String,
+    #[serde(default)]
+    pub on_connect: Vec<Value>,
+    #[serde(default)]
+    pub interval: Option<WsInterval>,
+    #[serde(default)]
+    pub replies: Vec<WsReply>,
+    #[serde(default)]
+    pub proxy_url: Option<String>,
+}
+
+/// Sends `messages` in turn, one every `every_ms`, for as long as the client is connected.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct WsInterval {
+    pub every_ms: u64,
+    pub messages: Vec<Value>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct WsReply {
+    #[serde(default, rename = "match")]
+    pub matcher: WsMatcher,
+    pub respond: Vec<Value>,
+}
+
+/// Every condition that is set must hold; an empty matcher matches any text message.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct WsMatcher {
+    pub text: Option<String>,
+    pub contains: Option<String>,
+    pub regex: Option<String>,
+    pub json: Option<Value>,
+    /// `regex`, compiled when the endpoint is added.
+    #[serde(skip)]
+    compiled: Option<Regex>,
+}
+
+#[derive(Deserialize)]
+pub struct RemoveWsConfig {
+    pub path: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct WsFrameLog {
+    pub direction: String,
+    pub kind: String,
+    pub data: Option<String>,
+    pub timestamp: String,
+}
+
+impl WsMatcher {
+    fn compile(&mut self) -> Result<(), String> {
+        if let Some(pattern) = &self.regex {
+            let regex = Regex::new(pattern).map_err(|e| format!("Invalid match regex {}: {}", pattern, e))?;
+            self.compiled = Some(regex);
+        }
+        Ok(())
+    }
+
+    fn matches(&self, text: &str) -> bool {
+        if let Some(expected) = &self.text {
+            if expected != text {
+                return false;
+            }
+        }
+        if let Some(needle) = &self.contains {
+            if !text.contains(needle.as_str()) {
+                return false;
+            }
+        }
+        if let Some(regex) = &self.compiled {
+            if !regex.is_match(text) {
+                return false;
+            }
+        }
+        if let Some(expected) = &self.json {
+            match serde_json::from_str::<Value>(text) {
+                Ok(actual) if json_contains(&actual, expected) => {}
+                _ => return false,
+            }
+        }
+        true
+    }
+}
+
+/// True when every field of `expected` is present in `actual` with an equal value.
+pub fn json_contains(actual: &Value, expected: &Value) -> bool {
+    match (actual, expected) {
+        (Value::Object(a), Value::Object(e)) => e.iter().all(|(k, v)| a.get(k).is_some_and(|av| json_contains(av, v))),
+        _ => actual == expected,
+    }
+}
+
+fn message_text(message: &Value) -> String {
+    match message {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+pub fn is_upgrade(headers: &HeaderMap) -> bool {
+    headers.get("upgrade")
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.eq_ignore_ascii_case("websocket"))
+}
+
+fn record_frame(data: &AppState, log_id: u64, direction: &str, kind: &str, payload: Option<&[u8]>) {
+    let payload = payload.map(|bytes| match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        Err(_) => base64::engine::general_purpose::STANDARD.encode(bytes),
+    });
+    let frame = WsFrameLog {
+        direction: direction.to_string(),
+        kind: kind.to_string(),
+        data: payload,
+        timestamp: Local::now().to_rfc3339(),
+    };
+    data.logs.update(log_id, move |log| {
+        let frames = log.ws_frames.get_or_insert_with(Vec::new);
+        if frames.len() < MAX_LOGGED_FRAMES {
+            frames.push(frame);
+        }
+    });
+}
+
+/// Compiles the paths of `endpoints` for [`handle_upgrade`]; call while holding their lock.
+fn rebuild_router(data: &AppState, endpoints: &HashMap<String, WsEndpointConfig>) {
+    *data.ws_router.write().unwrap() = router::Router::build(endpoints.keys().map(|path| ("GET", path.as_str())));
+}
+
+pub async fn add_ws_endpoint(data: web::Data<AppState>, cfg: web::Json<WsEndpointConfig>) -> impl Responder {
+    let mut cfg = cfg.into_inner();
+    let compiled = router::validate(&cfg.path)
+        .and_then(|_| cfg.replies.iter_mut().try_for_each(|reply| reply.matcher.compile()));
+    if let Err(e) = compiled {
+        return HttpResponse::BadRequest().json(json!({"added": false, "error": e}));
+    }
+    info!("Added WebSocket endpoint {}", cfg.path);
+    let mut endpoints = data.ws_endpoints.lock().unwrap();
+    endpoints.insert(cfg.path.clone(), cfg);
+    rebuild_router(&data, &endpoints);
+    HttpResponse::Ok().json(json!({"added": true}))
+}
+
+pub async fn remove_ws_endpoint(data: web::Data<AppState>, cfg: web::Json<RemoveWsConfig>) -> impl Responder {
+    let mut endpoints = data.ws_endpoints.lock().unwrap();
+    let removed = endpoints.remove(&cfg.path).is_some();
+    rebuild_router(&data, &endpoints);
+    info!("Removed WebSocket endpoint {}: {}", cfg.path, removed);
+    HttpResponse::Ok().json(json!({"removed": removed}))
+}
+
+pub async fn get_ws_endpoints(data: web::Data<AppState>) -> impl Responder {
+    let endpoints = data.ws_endpoints.lock().unwrap();
+    let list: Vec<&WsEndpointConfig> = endpoints.values().collect();
+    HttpResponse::Ok().json(list)
+}
+
+pub async fn handle_upgrade(req: HttpRequest, payload: web::Payload, data: web::Data<AppState>) -> actix_web::Result<HttpResponse> {
+    let path = req.path().to_string();
+    let endpoint = {
+        let endpoints = data.ws_endpoints.lock().unwrap();
+        endpoints.get(&path).or_else(|| {
+            let router = data.ws_router.read().unwrap();
+            router.find("GET", &path).and_then(|route| endpoints.get(route.template))
+        }).cloned()
+    };
+
+    let default_proxy = data.default_proxy_url.read().unwrap().clone();
+    let proxy_url = match &endpoint {
+        Some(ep) => ep.proxy_url.clone(),
+        None => default_proxy,
+    };
+    if endpoint.is_none() && proxy_url.is_none() {
+        return Ok(HttpResponse::NotFound().finish());
+    }
+
+    let log_id = data.next_log_id.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    let (response, session, msg_stream) = actix_ws::handle(&req, payload)?;
+    let msg_stream = msg_stream.aggregate_continuations();
+
+    let matched_endpoint = endpoint.as_ref().map(|ep| format!("{} (websocket)", ep.path));
+    data.logs.push(RequestLog {
+        id: log_id,
+        method: req.method().as_str().to_uppercase(),
+        path: path.clone(),
+        request_headers: req.headers().iter().map(|(k, v)| (k.to_string(), v.to_str().unwrap_or("").to_string())).collect(),
+        query: req.query_string().to_string(),
+        status: 101,
+        timestamp: Local::now().to_rfc3339(),
+        matched_endpoint,
+        proxied_to: proxy_url.as_ref().map(|url| format!("{}{}", url, path)),
+        ws_frames: Some(Vec::new()),
+        ..Default::default()
+    });
+
+    match proxy_url {
+        Some(proxy_url) => {
+            let upstream_url = ws_upstream_url(&proxy_url, &req);
+            info!("Proxying WebSocket {} to {}", path, upstream_url);
+            let mut client_req = awc::Client::new().ws(upstream_url.as_str());
+            for name in ["authorization", "cookie", "sec-websocket-protocol"] {
+                if let Some(value) = req.headers().get(name) {
+                    client_req = client_req.header(name, value.clone());
+                }
+            }
+            actix_web::rt::spawn(async move {
+                match client_req.connect().await {
+                    Ok((_, upstream)) => proxy_session(data, log_id, session, msg_stream, upstream).await,
+                    Err(e) => {
+                        warn!("WebSocket proxy to {} failed: {}", upstream_url, e);
+                        record_frame(&data, log_id, "out", "close", Some(e.to_string().as_bytes()));
+                        let _ = session.close(None).await;
+                    }
+                }
+            });
+        }
+        None => {
+            let endpoint = endpoint.expect("checked above");
+            actix_web::rt::spawn(scripted_session(data, log_id, endpoint, session, msg_stream));
+        }
+    }
+
+    Ok(response)
+}
+
+fn ws_upstream_url(proxy_url: &str, req: &HttpRequest) -> String {
+    let base = proxy_url.trim_end_matches('/');
+    let base = if let Some(rest) = base.strip_prefix("https://") {
+        format!("wss://{}", rest)
+    } else if let Some(rest) = base.strip_prefix("http://") {
+        format!("ws://{}", rest)
+    } else {
+        base.to_string()
+    };
+    if req.query_string().is_empty() {
+        format!("{}{}", base, req.path())
+    } else {
+        format!("{}{}?{}", base, req.path(), req.query_string())
+    }
+}
+
+async fn scripted_session(
+    data: web::Data<AppState>,
+    log_id: u64,
+    endpoint: WsEndpointConfig,
+    mut session: actix_ws::Session,
+    mut msg_stream: actix_ws::AggregatedMessageStream,
+) {
+    for message in &endpoint.on_connect {
+        let text = message_text(message);
+        record_frame(&data, log_id, "out", "text", Some(text.as_bytes()));
+        if session.text(text).await.is_err() {
+            return;
+        }
+    }
+
+    if let Some(interval) = endpoint.interval.clone().filter(|i| !i.messages.is_empty()) {
+        let mut session = session.clone();
+        let data = data.clone();
+        actix_web::rt::spawn(async move {
+            for message in interval.messages.iter().cycle() {
+                actix_web::rt::time::sleep(Duration::from_millis(interval.every_ms)).await;
+                let text = message_text(message);
+                if session.text(text.clone()).await.is_err() {
+                    break;
+                }
+                record_frame(&data, log_id, "out", "text", Some(text.as_bytes()));
+            }
+        });
+    }
+
+    while let Some(Ok(message)) = msg_stream.next().await {
+        match message {
+            AggregatedMessage::Text(text) => {
+                record_frame(&data, log_id, "in", "text", Some(text.as_bytes()));
+                if let Some(reply) = endpoint.replies.iter().find(|r| r.matcher.matches(&text)) {
+                    for message in &reply.respond {
+                        let text = message_text(message);
+                        record_frame(&data, log_id, "out", "text", Some(text.as_bytes()));
+                        if session.text(text).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+            AggregatedMessage::Binary(bytes) => {
+                record_frame(&data, log_id, "in", "binary", Some(&bytes));
+            }
+            AggregatedMessage::Ping(bytes) => {
+                if session.pong(&bytes).await.is_err() {
+                    return;
+                }
+            }
+            AggregatedMessage::Pong(_) => {}
+            AggregatedMessage::Close(reason) => {
+                record_frame(&data, log_id, "in", "close", reason.as_ref().and_then(|r| r.description.as_ref()).map(|d| d.as_bytes()));
+                let _ = session.close(reason).await;
+                return;
+            }
+        }
+    }
+}
+
+async fn proxy_session(
+    data: web::Data<AppState>,
+    log_id: u64,
+    mut session: actix_ws::Session,
+    mut msg_stream: actix_ws::AggregatedMessageStream,
+    upstream: actix_codec::Framed<awc::BoxedSocket, awc::ws::Codec>,
+) {
+    let (mut upstream_tx, mut upstream_rx) = upstream.split();
+
+    let client_data = data.clone();
+    let client_to_upstream = async move {
+        while let Some(Ok(message)) = msg_stream.next().await {
+            let frame = match message {
+                AggregatedMessage::Text(text) => {
+                    record_frame(&client_data, log_id, "in", "text", Some(text.as_bytes()));
+                    awc::ws::Message::Text(text)
+                }
+                AggregatedMessage::Binary(bytes) => {
+                    record_frame(&client_data, log_id, "in", "binary", Some(&bytes));
+                    awc::ws::Message::Binary(bytes)
+                }
+                AggregatedMessage::Ping(bytes) => awc::ws::Message::Ping(bytes),
+                AggregatedMessage::Pong(bytes) => awc::ws::Message::Pong(bytes),
+                AggregatedMessage::Close(reason) => {
+                    record_frame(&client_data, log_id, "in", "close", None);
+                    let _ = upstream_tx.send(awc::ws::Message::Close(reason)).await;
+                    break;
+                }
+            };
+            if upstream_tx.send(frame).await.is_err() {
+                break;
+            }
+        }
+    };
+
+    let upstream_to_client = async move {
+        let mut fragments = Fragments::default();
+        while let Some(Ok(frame)) = upstream_rx.next().await {
+            let sent = match frame {
+                Frame::Text(bytes) => forward(&data, log_id, &mut session, true, bytes).await,
+                Frame::Binary(bytes) => forward(&data, log_id, &mut session, false, bytes).await,
+                Frame::Continuation(item) => match fragments.push(item) {
+                    Ok(Some((text, bytes))) => forward(&data, log_id, &mut session, text, bytes).await,
+                    Ok(None) => Ok(()),
+                    Err(e) => {
+                        warn!("Closing proxied WebSocket: {}", e);
+                        record_frame(&data, log_id, "out", "close", Some(e.as_bytes()));
+                        let _ = session.close(Some(CloseCode::Size.into())).await;
+                        break;
+                    }
+                },
+                Frame::Ping(bytes) => session.ping(&bytes).await,
+                Frame::Pong(bytes) => session.pong(&bytes).await,
+                Frame::Close(reason) => {
+                    record_frame(&data, log_id, "out", "close", None);
+                    let _ = session.close(reason).await;
+                    break;
+                }
+            };
+            if sent.is_err() {
+                break;
+            }
+        }
+    };
+
+    futures_util::future::select(Box::pin(client_to_upstream), Box::pin(upstream_to_client)).await;
+}
+
+/// Sends a complete upstream message on to the client.
+async fn forward(data: &AppState, log_id: u64, session: &mut actix_ws::Session, text: bool, bytes: Bytes) -> Result<(), actix_ws::Closed> {
+    match text {
+        true => {
+            record_frame(data, log_id, "out", "text", Some(&bytes));
+            session.text(String::from_utf8_lossy(&bytes).into_owned()).await
+        }
+        false => {
+            record_frame(data, log_id, "out", "binary", Some(&bytes));
+            session.binary(bytes).await
+        }
+    }
+}
+
+/// A fragmented upstream message being put back together.
+#[derive(Default)]
+struct Fragments {
+    /// Whether the message is text, and its bytes so far.
+    partial: Option<(bool, BytesMut)>,
+}
+
+impl Fragments {
+    /// Adds a continuation frame, returning the whole message once its last frame arrives.
+    fn push(&mut self, item: Item) -> Result<Option<(bool, Bytes)>, String> {
+        let (bytes, last) = match item {
+            Item::FirstText(bytes) => {
+                self.partial = Some((true, BytesMut::new()));
+                (bytes, false)
+            }
+            Item::FirstBinary(bytes) => {
+                self.partial = Some((false, BytesMut::new()));
+                (bytes, false)
+            }
+            Item::Continue(bytes) => (bytes, false),
+            Item::Last(bytes) => (bytes, true),
+        };
+        let Some((text, buffer)) = self.partial.as_mut() else {
+            return Err("continuation frame without a first frame".to_string());
+        };
+        if buffer.len() + bytes.len() > MAX_MESSAGE_SIZE {
+            self.partial = None;
+            return Err(format!("fragmented message over {} bytes", MAX_MESSAGE_SIZE));
+        }
+        buffer.extend_from_slice(&bytes);
+        let text = *text;
+        match last {
+            true => Ok(self.partial.take().map(|(_, buffer)| (text, buffer.freeze()))),
+            false => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use super::common::{TestServer, ADMIN_PORT, BASE_URL};
+use serde_json::json;
+
+#[tokio::test]
+async fn test_admin_api_requires_bearer_token() {
+    let _server = TestServer::start_with_vars(vec![("ADMIN_TOKEN", "s3cret")]).await;
+    let client = reqwest::Client::new();
+
+    let resp = client.get(format!("{}/__mock/logs", BASE_URL)).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 401);
+    assert_eq!(resp.headers()["www-authenticate"], "Bearer realm=\"RustMock admin\"");
+
+    let resp = client
+        .post(format!("{}/__mock/endpoints", BASE_URL))
+        .bearer_auth("wrong")
+        .json(&json!({"method": "GET", "path": "/secured", "response": {"ok": true}}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 401);
+
+    let resp = client
+        .post(format!("{}/__mock/endpoints", BASE_URL))
+        .bearer_auth("s3cret")
+        .json(&json!({"method": "GET", "path": "/secured", "response": {"ok": true}}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    // Mocked traffic stays open.
+    let resp = client.get(format!("{}/secured", BASE_URL)).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(resp.json::<serde_json::Value>().await.unwrap(), json!({"ok": true}));
+}
+
+#[tokio::test]
+async fn test_admin_api_accepts_basic_auth() {
+    let _server = TestServer::start_with_vars(vec![("ADMIN_BASIC_AUTH", "admin:pa55")]).await;
+    let client = reqwest::Client::new();
+
+    let resp = client.get(format!("{}/__mock/config", BASE_URL)).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 401);
+    assert!(resp.headers()["www-authenticate"].to_str().unwrap().starts_with("Basic "));
+
+    let resp = client
+        .get(format!("{}/__mock/config", BASE_URL))
+        .basic_auth("admin", Some("nope"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 401);
+
+    let resp = client
+        .get(format!("{}/__mock/config", BASE_URL))
+        .basic_auth("admin", Some("pa55"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn test_admin_api_on_separate_port() {
+    let admin_port = ADMIN_PORT.to_string();
+    let _server = TestServer::start_with_vars(vec![("ADMIN_PORT", &admin_port), ("ADMIN_HOST", "127.0.0.1")]).await;
+    let client = reqwest::Client::new();
+    let admin_url = format!("http://127.0.0.1:{}", ADMIN_PORT);
+
+    let resp = client
+        .post(format!("{}/__mock/endpoints", admin_url))
+        .json(&json!({"method": "GET", "path": "/split", "response": {"port": "mocks"}}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    // The mock listener no longer exposes the admin API.
+    let resp = client.get(format!("{}/__mock/config", BASE_URL)).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+
+    let resp = client.get(format!("{}/split", BASE_URL)).send().await.unwrap();
+    assert_eq!(resp.json::<serde_json::Value>().await.unwrap(), json!({"port": "mocks"}));
+
+    let logs: serde_json::Value = client
+        .get(format!("{}/__mock/logs", admin_url))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let split = logs.as_array().unwrap().iter().find(|l| l["path"] == "/split");
+    assert_eq!(split.unwrap()["status"], 200);
+}
@@ -0,0 +1,284 @@
+use super::common::{TestServer, BASE_URL, UPSTREAM_URL};
+use serde_json::json;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+const RAW_UPSTREAM_PORT: u16 = 18092;
+
+async fn set_upstream_response(client: &reqwest::Client, version: u32) {
+    client
+        .post(format!("{}/__mock/endpoints", UPSTREAM_URL))
+        .json(&json!({
+            "method": "GET",
+            "path": "/api/catalog",
+            "response": {"version": version},
+            "status": 200
+        }))
+        .send()
+        .await
+        .expect("Failed to set upstream endpoint");
+}
+
+async fn get_catalog(client: &reqwest::Client) -> (Option<String>, serde_json::Value) {
+    let resp = client
+        .get(format!("{}/api/catalog", BASE_URL))
+        .send()
+        .await
+        .unwrap();
+    let cache = resp.headers().get("x-mock-cache").map(|v| v.to_str().unwrap().to_string());
+    (cache, resp.json().await.unwrap())
+}
+
+#[tokio::test]
+async fn test_proxy_cache_disabled_by_default() {
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+
+    let resp = client
+        .get(format!("{}/__mock/cache", BASE_URL))
+        .send()
+        .await
+        .unwrap();
+
+    assert!(resp.status().is_success());
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(body["config"]["enabled"], false);
+    assert_eq!(body["entries"].as_array().unwrap().len(), 0);
+}
+
+#[tokio::test]
+async fn test_proxy_cache_hit_and_purge() {
+    let _upstream = TestServer::start_upstream().await;
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+
+    set_upstream_response(&client, 1).await;
+
+    client
+        .post(format!("{}/__mock/proxy", BASE_URL))
+        .json(&json!({"url": UPSTREAM_URL}))
+        .send()
+        .await
+        .unwrap();
+
+    let resp = client
+        .post(format!("{}/__mock/cache", BASE_URL))
+        .json(&json!({"enabled": true, "ttl_secs": 300}))
+        .send()
+        .await
+        .unwrap();
+    assert!(resp.status().is_success());
+
+    let (cache, body) = get_catalog(&client).await;
+    assert_eq!(cache.as_deref(), Some("MISS"));
+    assert_eq!(body["version"], 1);
+
+    set_upstream_response(&client, 2).await;
+
+    let (cache, body) = get_catalog(&client).await;
+    assert_eq!(cache.as_deref(), Some("HIT"));
+    assert_eq!(body["version"], 1);
+
+    let listing: serde_json::Value = client
+        .get(format!("{}/__mock/cache", BASE_URL))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let entries = listing["entries"].as_array().unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["url"], format!("{}/api/catalog", UPSTREAM_URL));
+    assert_eq!(entries[0]["hits"], 1);
+
+    let purge: serde_json::Value = client
+        .delete(format!("{}/__mock/cache?url_prefix={}/api", BASE_URL, UPSTREAM_URL))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(purge["purged"], 1);
+
+    let (cache, body) = get_catalog(&client).await;
+    assert_eq!(cache.as_deref(), Some("MISS"));
+    assert_eq!(body["version"], 2);
+
+    let logs: serde_json::Value = client
+        .get(format!("{}/__mock/logs", BASE_URL))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let statuses: Vec<&str> = logs.as_array().unwrap().iter()
+        .filter_map(|l| l["cache"].as_str())
+        .collect();
+    assert_eq!(statuses, vec!["MISS", "HIT", "MISS"]);
+}
+
+#[tokio::test]
+async fn test_proxy_cache_skips_non_get_requests() {
+    let _upstream = TestServer::start_upstream().await;
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+
+    client
+        .post(format!("{}/__mock/endpoints", UPSTREAM_URL))
+        .json(&json!({"method": "POST", "path": "/api/orders", "response": {"ok": true}, "status": 201}))
+        .send()
+        .await
+        .unwrap();
+
+    client
+        .post(format!("{}/__mock/proxy", BASE_URL))
+        .json(&json!({"url": UPSTREAM_URL}))
+        .send()
+        .await
+        .unwrap();
+
+    client
+        .post(format!("{}/__mock/cache", BASE_URL))
+        .json(&json!({"enabled": true, "ttl_secs": 300}))
+        .send()
+        .await
+        .unwrap();
+
+    for _ in 0..2 {
+        let resp = client
+            .post(format!("{}/api/orders", BASE_URL))
+            .json(&json!({"item": 1}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status().as_u16(), 201);
+        assert!(resp.headers().get("x-mock-cache").is_none());
+    }
+
+    let listing: serde_json::Value = client
+        .get(format!("{}/__mock/cache", BASE_URL))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(listing["entries"].as_array().unwrap().len(), 0);
+}
+
+#[tokio::test]
+async fn test_proxy_cache_keeps_users_and_variants_apart() {
+    let _upstream = TestServer::start_upstream().await;
+    let _server = TestServer::start_with_vars(vec![("DEFAULT_PROXY_URL", UPSTREAM_URL), ("PROXY_CACHE_TTL", "300")]).await;
+    let client = reqwest::Client::new();
+
+    client
+        .post(format!("{}/__mock/endpoints", UPSTREAM_URL))
+        .json(&json!({
+            "method": "GET",
+            "path": "/api/profile",
+            "response": {"name": "Ada"},
+            "headers": {"Vary": "Accept-Language"}
+        }))
+        .send()
+        .await
+        .expect("Failed to set upstream endpoint");
+
+    let fetch = |header: (&'static str, &'static str)| {
+        let request = client.get(format!("{}/api/profile", BASE_URL)).header(header.0, header.1);
+        async move {
+            let resp = request.send().await.unwrap();
+            assert_eq!(resp.status().as_u16(), 200);
+            resp.headers().get("x-mock-cache").map(|v| v.to_str().unwrap().to_string())
+        }
+    };
+
+    // Credentialed requests never touch the shared cache.
+    assert_eq!(fetch(("Authorization", "Bearer alice")).await, None);
+    assert_eq!(fetch(("Authorization", "Bearer alice")).await, None);
+    assert_eq!(fetch(("Cookie", "session=bob")).await, None);
+
+    // The upstream's `Vary` splits entries by the headers it names.
+    assert_eq!(fetch(("Accept-Language", "en")).await.as_deref(), Some("MISS"));
+    assert_eq!(fetch(("Accept-Language", "en")).await.as_deref(), Some("HIT"));
+    assert_eq!(fetch(("Accept-Language", "de")).await.as_deref(), Some("MISS"));
+
+    let listing: serde_json::Value = client
+        .get(format!("{}/__mock/cache", BASE_URL))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(listing["config"]["enabled"], true);
+    assert_eq!(listing["entries"].as_array().unwrap().len(), 2);
+}
+
+/// An upstream that always sends a stale, ETagged body and answers revalidations slowly with 304.
+async fn start_revalidating_upstream() {
+    let listener = TcpListener::bind(("127.0.0.1", RAW_UPSTREAM_PORT)).await.unwrap();
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            tokio::spawn(async move {
+                let mut request = Vec::new();
+                let mut buf = [0u8; 4096];
+                while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                    let n = socket.read(&mut buf).await.unwrap();
+                    if n == 0 {
+                        return;
+                    }
+                    request.extend_from_slice(&buf[..n]);
+                }
+                let response = if String::from_utf8_lossy(&request).to_lowercase().contains("if-none-match") {
+                    tokio::time::sleep(Duration::from_millis(500)).await;
+                    "HTTP/1.1 304 Not Modified\r\nETag: \"v1\"\r\nConnection: close\r\n\r\n".to_string()
+                } else {
+                    let body = r#"{"version":1}"#;
+                    format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nETag: \"v1\"\r\nCache-Control: max-age=0\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    )
+                };
+                let _ = socket.write_all(response.as_bytes()).await;
+            });
+        }
+    });
+}
+
+#[tokio::test]
+async fn test_proxy_cache_refetches_when_entry_is_evicted_during_revalidation() {
+    let _server = TestServer::start().await;
+    start_revalidating_upstream().await;
+    let client = reqwest::Client::new();
+
+    for (path, config) in [
+        ("proxy", json!({"url": format!("http://127.0.0.1:{}", RAW_UPSTREAM_PORT)})),
+        ("cache", json!({"enabled": true})),
+    ] {
+        let resp = client.post(format!("{}/__mock/{}", BASE_URL, path)).json(&config).send().await.unwrap();
+        assert!(resp.status().is_success());
+    }
+
+    let (cache, body) = get_catalog(&client).await;
+    assert_eq!(cache.as_deref(), Some("MISS"));
+    assert_eq!(body["version"], 1);
+
+    // Purge while the 304 is on its way, so there is nothing left to refresh.
+    let revalidation = tokio::spawn(async move {
+        let resp = reqwest::get(format!("{}/api/catalog", BASE_URL)).await.unwrap();
+        (resp.status().as_u16(), resp.text().await.unwrap())
+    });
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    client.delete(format!("{}/__mock/cache", BASE_URL)).send().await.unwrap();
+
+    let (status, body) = revalidation.await.unwrap();
+    assert_eq!(status, 200);
+    assert_eq!(serde_json::from_str::<serde_json::Value>(&body).unwrap(), json!({"version": 1}));
+}
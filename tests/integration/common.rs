@@ -5,6 +5,11 @@ use tokio::time::sleep;
 
 pub const TEST_PORT: u16 = 18090;
 pub const BASE_URL: &str = "http://127.0.0.1:18090";
+pub const UPSTREAM_PORT: u16 = 18091;
+pub const UPSTREAM_URL: &str = "http://127.0.0.1:18091";
+pub const GRPC_PORT: u16 = 18093;
+pub const ADMIN_PORT: u16 = 18094;
+pub const HTTPS_PORT: u16 = 18096;
 
 pub struct TestServer {
     process: Child,
@@ -12,16 +17,30 @@ pub struct TestServer {
 
 impl TestServer {
     pub async fn start() -> Self {
-        Self::start_with_env(None).await
+        Self::start_with_env(TEST_PORT, None).await
     }
 
     pub async fn start_with_openapi_file(openapi_path: &str) -> Self {
-        Self::start_with_env(Some(vec![("OPENAPI_FILE", openapi_path)])).await
+        Self::start_with_env(TEST_PORT, Some(vec![("OPENAPI_FILE", openapi_path)])).await
     }
 
-    async fn start_with_env(env_vars: Option<Vec<(&str, &str)>>) -> Self {
+    pub async fn start_with_grpc(proto_path: &str) -> Self {
+        let grpc_port = GRPC_PORT.to_string();
+        Self::start_with_env(TEST_PORT, Some(vec![("GRPC_PORT", &grpc_port), ("PROTO_FILES", proto_path)])).await
+    }
+
+    pub async fn start_with_vars(env_vars: Vec<(&str, &str)>) -> Self {
+        Self::start_with_env(TEST_PORT, Some(env_vars)).await
+    }
+
+    /// A second mock server that stands in for the real upstream API.
+    pub async fn start_upstream() -> Self {
+        Self::start_with_env(UPSTREAM_PORT, None).await
+    }
+
+    async fn start_with_env(port: u16, env_vars: Option<Vec<(&str, &str)>>) -> Self {
         let build_status = Command::new("cargo")
-            .args(&["build", "--release"])
+            .args(["build", "--release"])
             .stdout(Stdio::null())
             .stderr(Stdio::null())
             .status()
@@ -30,7 +49,7 @@ impl TestServer {
         assert!(build_status.success(), "Build failed");
 
         let mut cmd = Command::new("./target/release/RustMock");
-        cmd.args(&["--port", &TEST_PORT.to_string()])
+        cmd.args(["--port", &port.to_string()])
             .stdout(Stdio::null())
             .stderr(Stdio::null());
 
@@ -40,18 +59,20 @@ impl TestServer {
             }
         }
 
-        let process = cmd.spawn().expect("Failed to start server");
+        let server = TestServer {
+            process: cmd.spawn().expect("Failed to start server"),
+        };
 
         let client = reqwest::Client::new();
 
         for _ in 0..50 {
-            if client.get(format!("{}/__mock/config", BASE_URL))
+            if client.get(format!("http://127.0.0.1:{}/__mock/config", port))
                 .send()
                 .await
                 .is_ok()
             {
-                println!("Server started successfully on port {}", TEST_PORT);
-                return TestServer { process };
+                println!("Server started successfully on port {}", port);
+                return server;
             }
             sleep(Duration::from_millis(100)).await;
         }
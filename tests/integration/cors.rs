@@ -0,0 +1,158 @@
+use super::common::{TestServer, BASE_URL};
+use serde_json::json;
+
+async fn preflight(client: &reqwest::Client, origin: &str) -> reqwest::Response {
+    client
+        .request(reqwest::Method::OPTIONS, format!("{}/api/items", BASE_URL))
+        .header("Origin", origin)
+        .header("Access-Control-Request-Method", "PUT")
+        .header("Access-Control-Request-Headers", "content-type, x-request-id")
+        .send()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_cors_preflight_and_headers_for_allowed_origin() {
+    let _server = TestServer::start_with_vars(vec![("CORS_ORIGINS", "http://localhost:3000")]).await;
+    let client = reqwest::Client::new();
+
+    client
+        .post(format!("{}/__mock/endpoints", BASE_URL))
+        .json(&json!({"method": "GET", "path": "/api/items", "response": [], "headers": {"X-Total-Count": "0"}}))
+        .send()
+        .await
+        .expect("Failed to add endpoint");
+
+    let response = preflight(&client, "http://localhost:3000").await;
+    assert_eq!(response.status(), 204);
+    assert_eq!(response.headers()["access-control-allow-origin"], "http://localhost:3000");
+    assert_eq!(response.headers()["access-control-allow-methods"], "PUT");
+    assert_eq!(response.headers()["access-control-allow-headers"], "content-type, x-request-id");
+
+    let response = client
+        .get(format!("{}/api/items", BASE_URL))
+        .header("Origin", "http://localhost:3000")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.headers()["access-control-allow-origin"], "http://localhost:3000");
+    assert_eq!(response.headers()["access-control-expose-headers"], "x-total-count");
+    assert!(response.headers().get("access-control-allow-credentials").is_none());
+}
+
+#[tokio::test]
+async fn test_cors_policy_restricts_origins() {
+    let _server = TestServer::start_with_vars(vec![
+        ("CORS_ORIGINS", "https://*.example.com"),
+        ("CORS_CREDENTIALS", "true"),
+        ("CORS_MAX_AGE", "600"),
+        ("ADMIN_TOKEN", "s3cret"),
+    ]).await;
+    let client = reqwest::Client::new();
+
+    let response = preflight(&client, "https://app.example.com").await;
+    assert_eq!(response.status(), 204);
+    assert_eq!(response.headers()["access-control-allow-credentials"], "true");
+    assert_eq!(response.headers()["access-control-max-age"], "600");
+
+    let response = preflight(&client, "https://example.org").await;
+    assert_eq!(response.status(), 403);
+    assert!(response.headers().get("access-control-allow-origin").is_none());
+
+    let response = client
+        .request(reqwest::Method::OPTIONS, format!("{}/__mock/config", BASE_URL))
+        .header("Origin", "https://app.example.com")
+        .header("Access-Control-Request-Method", "GET")
+        .send()
+        .await
+        .unwrap();
+    assert_ne!(response.status(), 204);
+    assert!(response.headers().get("access-control-allow-origin").is_none());
+
+    let response = client
+        .get(format!("{}/__mock/config", BASE_URL))
+        .header("Origin", "https://app.example.com")
+        .header("Authorization", "Bearer s3cret")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    assert!(response.headers().get("access-control-allow-origin").is_none());
+}
+
+#[tokio::test]
+async fn test_cors_is_off_by_default() {
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+
+    let response = preflight(&client, "http://localhost:3000").await;
+    assert_eq!(response.status(), 404);
+    assert!(response.headers().get("access-control-allow-origin").is_none());
+
+    client
+        .post(format!("{}/__mock/endpoints", BASE_URL))
+        .json(&json!({"method": "GET", "path": "/api/items", "response": []}))
+        .send()
+        .await
+        .expect("Failed to add endpoint");
+    let response = client
+        .get(format!("{}/api/items", BASE_URL))
+        .header("Origin", "http://localhost:3000")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    assert!(response.headers().get("access-control-allow-origin").is_none());
+}
+
+#[tokio::test]
+async fn test_dev_mode_allows_local_origins() {
+    let _server = TestServer::start_with_vars(vec![("DEV_MODE", "true")]).await;
+    let client = reqwest::Client::new();
+
+    for origin in ["http://localhost:5173", "http://127.0.0.1:3000", "https://localhost", "http://[::1]:8080"] {
+        let response = preflight(&client, origin).await;
+        assert_eq!(response.status(), 204, "{}", origin);
+        assert_eq!(response.headers()["access-control-allow-origin"], origin);
+    }
+    for origin in ["https://evil.example", "http://localhost.evil.example"] {
+        let response = preflight(&client, origin).await;
+        assert_eq!(response.status(), 403, "{}", origin);
+        assert!(response.headers().get("access-control-allow-origin").is_none());
+    }
+}
+
+#[tokio::test]
+async fn test_options_stub_answers_its_own_preflights() {
+    let _server = TestServer::start_with_vars(vec![("CORS_ORIGINS", "*")]).await;
+    let client = reqwest::Client::new();
+
+    client
+        .post(format!("{}/__mock/endpoints", BASE_URL))
+        .json(&json!({
+            "method": "OPTIONS",
+            "path": "/api/items",
+            "response": {},
+            "status": 200,
+            "headers": {"Access-Control-Allow-Origin": "https://stubbed.example.com"}
+        }))
+        .send()
+        .await
+        .expect("Failed to add endpoint");
+
+    let response = preflight(&client, "http://localhost:3000").await;
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.headers()["access-control-allow-origin"], "https://stubbed.example.com");
+
+    let response = client
+        .request(reqwest::Method::OPTIONS, format!("{}/api/other", BASE_URL))
+        .header("Origin", "http://localhost:3000")
+        .header("Access-Control-Request-Method", "GET")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 204);
+    assert_eq!(response.headers()["access-control-allow-origin"], "http://localhost:3000");
+}
@@ -0,0 +1,58 @@
+use super::common::{TestServer, BASE_URL};
+use serde_json::json;
+
+#[tokio::test]
+async fn test_custom_admin_prefix_frees_reserved_paths() {
+    let _server = TestServer::start_with_vars(vec![("ADMIN_PREFIX", "/_admin/"), ("DISABLE_DASHBOARD", "1")]).await;
+    let client = reqwest::Client::new();
+
+    for (path, response) in [("/", json!({"root": true})), ("/__mock/config", json!({"legacy": true}))] {
+        let resp = client
+            .post(format!("{}/_admin/endpoints", BASE_URL))
+            .json(&json!({"method": "GET", "path": path, "response": response}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status().as_u16(), 200);
+    }
+
+    let root: serde_json::Value = client.get(format!("{}/", BASE_URL)).send().await.unwrap().json().await.unwrap();
+    assert_eq!(root, json!({"root": true}));
+
+    let legacy: serde_json::Value = client.get(format!("{}/__mock/config", BASE_URL)).send().await.unwrap().json().await.unwrap();
+    assert_eq!(legacy, json!({"legacy": true}));
+
+    let config: serde_json::Value = client.get(format!("{}/_admin/config", BASE_URL)).send().await.unwrap().json().await.unwrap();
+    assert_eq!(config.as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_dashboard_served_from_ui_dir_under_mount() {
+    let _server = TestServer::start_with_vars(vec![("UI_DIR", "tests/fixtures/ui"), ("DASHBOARD_PATH", "/ui")]).await;
+    let client = reqwest::Client::builder().redirect(reqwest::redirect::Policy::none()).build().unwrap();
+
+    let resp = client.get(format!("{}/ui", BASE_URL)).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 308);
+    assert_eq!(resp.headers()["location"], "/ui/");
+
+    let html = client.get(format!("{}/ui/", BASE_URL)).send().await.unwrap().text().await.unwrap();
+    assert!(html.contains("Fixture Dashboard"));
+    assert!(html.contains(r#"window.__RUSTMOCK__ = {"adminPrefix":"/__mock","basePath":"/ui"};"#));
+
+    let resp = client.get(format!("{}/ui/assets/app.js", BASE_URL)).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert!(resp.text().await.unwrap().contains("fixture dashboard"));
+
+    // Client-side routes under the mount get the UI, everything else stays mockable.
+    let html = client.get(format!("{}/ui/settings", BASE_URL)).send().await.unwrap().text().await.unwrap();
+    assert!(html.contains("Fixture Dashboard"));
+
+    client
+        .post(format!("{}/__mock/endpoints", BASE_URL))
+        .json(&json!({"method": "GET", "path": "/", "response": {"root": true}}))
+        .send()
+        .await
+        .unwrap();
+    let root: serde_json::Value = client.get(format!("{}/", BASE_URL)).send().await.unwrap().json().await.unwrap();
+    assert_eq!(root, json!({"root": true}));
+}
@@ -0,0 +1,148 @@
+use super::common::{TestServer, BASE_URL};
+use serde_json::json;
+
+const SDL: &str = r#"
+    interface Node { id: ID! }
+    enum Role { ADMIN MEMBER }
+    type User implements Node {
+        id: ID!
+        name: String!
+        age: Int
+        role: Role!
+        friends: [User!]!
+    }
+    type Query {
+        user(id: ID!): User
+        node(id: ID!): Node
+    }
+    type Mutation { rename(id: ID!, name: String!): User }
+"#;
+
+async fn load_schema(client: &reqwest::Client) {
+    let resp = client
+        .post(format!("{}/__mock/graphql/schema", BASE_URL))
+        .json(&json!({"sdl": SDL}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+}
+
+async fn graphql(client: &reqwest::Client, body: serde_json::Value) -> serde_json::Value {
+    client
+        .post(format!("{}/graphql", BASE_URL))
+        .json(&body)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_graphql_generates_typed_response() {
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+    load_schema(&client).await;
+
+    let body = graphql(&client, json!({
+        "query": "query GetUser($id: ID!) { user(id: $id) { id handle: name age role friends { __typename } } node(id: 1) { id ... on User { name } } }",
+        "variables": {"id": "7"}
+    })).await;
+
+    assert_eq!(body, json!({
+        "data": {
+            "user": {"id": "1", "handle": "name string", "age": 1, "role": "ADMIN", "friends": [{"__typename": "User"}]},
+            "node": {"id": "1", "name": "name string"}
+        }
+    }));
+
+    let logs: serde_json::Value = reqwest::get(format!("{}/__mock/logs", BASE_URL))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let operation = &logs[0]["graphql"];
+    assert_eq!(operation["type"], "query");
+    assert_eq!(operation["name"], "GetUser");
+    assert_eq!(operation["variables"], json!({"id": "7"}));
+    assert_eq!(operation["root_fields"], json!(["user", "node"]));
+}
+
+#[tokio::test]
+async fn test_graphql_overrides_match_operation_and_variables() {
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+    load_schema(&client).await;
+
+    client
+        .post(format!("{}/__mock/graphql/operations", BASE_URL))
+        .json(&json!({
+            "operation_name": "GetUser",
+            "variables": {"id": "404"},
+            "response": {"data": {"user": null}, "errors": [{"message": "Not found"}]}
+        }))
+        .send()
+        .await
+        .unwrap();
+
+    let query = "query GetUser($id: ID!) { user(id: $id) { name } }";
+    let missing = graphql(&client, json!({"query": query, "operationName": "GetUser", "variables": {"id": "404"}})).await;
+    assert_eq!(missing["errors"][0]["message"], "Not found");
+
+    let found = graphql(&client, json!({"query": query, "operationName": "GetUser", "variables": {"id": "1"}})).await;
+    assert_eq!(found, json!({"data": {"user": {"name": "name string"}}}));
+}
+
+#[tokio::test]
+async fn test_graphql_reports_errors() {
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+    load_schema(&client).await;
+
+    let unknown = graphql(&client, json!({"query": "{ user(id: 1) { email } }"})).await;
+    assert_eq!(unknown["errors"][0]["message"], "Cannot query field \"email\" on type \"User\".");
+
+    let invalid = graphql(&client, json!({"query": "{ user("})).await;
+    assert!(invalid["errors"][0]["message"].as_str().unwrap().starts_with("Syntax error"));
+
+    let resp = client
+        .post(format!("{}/__mock/graphql/schema", BASE_URL))
+        .json(&json!({"sdl": "type Query {"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn test_graphql_overrides_work_without_schema_and_bad_requests_get_400() {
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+
+    let resp = client
+        .post(format!("{}/__mock/graphql/operations", BASE_URL))
+        .json(&json!({"operation_name": "Me", "response": {"data": {"me": {"id": "1"}}}}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let me = graphql(&client, json!({"query": "query Me { me { id } }", "operationName": "Me"})).await;
+    assert_eq!(me, json!({"data": {"me": {"id": "1"}}}));
+
+    for body in ["not json", r#"{"query": "{ me("}"#] {
+        let resp = client
+            .post(format!("{}/graphql", BASE_URL))
+            .header("content-type", "application/json")
+            .body(body)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status().as_u16(), 400);
+        let errors: serde_json::Value = resp.json().await.unwrap();
+        assert!(errors["errors"][0]["message"].is_string());
+    }
+}
@@ -0,0 +1,219 @@
+use super::common::{TestServer, BASE_URL, GRPC_PORT};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use prost::Message;
+use prost_reflect::{DescriptorPool, DynamicMessage};
+use serde_json::json;
+use tokio::net::TcpStream;
+
+const PROTO: &str = "tests/fixtures/greeter.proto";
+
+struct GrpcReply {
+    messages: Vec<serde_json::Value>,
+    status: String,
+    message: Option<String>,
+}
+
+fn pool() -> DescriptorPool {
+    protox::Compiler::new(["tests/fixtures"]).unwrap()
+        .open_file("greeter.proto").unwrap()
+        .descriptor_pool()
+}
+
+async fn call(method: &str, request: serde_json::Value) -> GrpcReply {
+    let pool = pool();
+    let input = pool.get_message_by_name("test.greeter.HelloRequest").unwrap();
+    let output = pool.get_message_by_name("test.greeter.HelloReply").unwrap();
+
+    let encoded = DynamicMessage::deserialize(input, request).unwrap().encode_to_vec();
+    let mut frame = BytesMut::new();
+    frame.put_u8(0);
+    frame.put_u32(encoded.len() as u32);
+    frame.put_slice(&encoded);
+
+    let socket = TcpStream::connect(("127.0.0.1", GRPC_PORT)).await.unwrap();
+    let (client, connection) = h2::client::handshake(socket).await.unwrap();
+    tokio::spawn(connection);
+    let mut client = client.ready().await.unwrap();
+
+    let request = http::Request::builder()
+        .method("POST")
+        .uri(format!("http://127.0.0.1:{}/test.greeter.Greeter/{}", GRPC_PORT, method))
+        .header("content-type", "application/grpc")
+        .header("te", "trailers")
+        .body(())
+        .unwrap();
+    let (response, mut stream) = client.send_request(request, false).unwrap();
+    stream.send_data(frame.freeze(), true).unwrap();
+
+    let response = response.await.unwrap();
+    assert_eq!(response.headers()["content-type"], "application/grpc");
+    let mut body = response.into_body();
+    let mut buffer = BytesMut::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.unwrap();
+        let _ = body.flow_control().release_capacity(chunk.len());
+        buffer.extend_from_slice(&chunk);
+    }
+    let trailers = body.trailers().await.unwrap().unwrap_or_default();
+
+    let mut buffer: Bytes = buffer.freeze();
+    let mut messages = Vec::new();
+    while buffer.has_remaining() {
+        buffer.advance(1);
+        let len = buffer.get_u32() as usize;
+        let message = DynamicMessage::decode(output.clone(), buffer.split_to(len)).unwrap();
+        messages.push(serde_json::to_value(&message).unwrap());
+    }
+
+    GrpcReply {
+        messages,
+        status: trailers["grpc-status"].to_str().unwrap().to_string(),
+        message: trailers.get("grpc-message").map(|v| v.to_str().unwrap().to_string()),
+    }
+}
+
+async fn add_stub(stub: serde_json::Value) {
+    let resp = reqwest::Client::new()
+        .post(format!("{}/__mock/grpc/stubs", BASE_URL))
+        .json(&stub)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn test_grpc_unary_stub_with_request_matching() {
+    let _server = TestServer::start_with_grpc(PROTO).await;
+
+    add_stub(json!({
+        "service": "Greeter",
+        "method": "SayHello",
+        "match": {"name": "error"},
+        "code": 5,
+        "message": "user not found"
+    })).await;
+    add_stub(json!({
+        "service": "test.greeter.Greeter",
+        "method": "SayHello",
+        "response": {"message": "Hello from mock", "tags": ["a", "b"]}
+    })).await;
+
+    let reply = call("SayHello", json!({"name": "world"})).await;
+    assert_eq!(reply.status, "0");
+    assert_eq!(reply.messages, vec![json!({"message": "Hello from mock", "tags": ["a", "b"]})]);
+
+    let reply = call("SayHello", json!({"name": "error"})).await;
+    assert_eq!(reply.status, "5");
+    assert_eq!(reply.message.as_deref(), Some("user not found"));
+    assert!(reply.messages.is_empty());
+
+    let logs: serde_json::Value = reqwest::get(format!("{}/__mock/logs", BASE_URL))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let log = &logs[0];
+    assert_eq!(log["path"], "/test.greeter.Greeter/SayHello");
+    assert_eq!(log["request_body"], json!({"name": "world", "times": 0}));
+    assert_eq!(log["response_body"]["message"], "Hello from mock");
+    assert_eq!(log["grpc"]["service"], "test.greeter.Greeter");
+    assert_eq!(log["grpc"]["status"], 0);
+    assert_eq!(logs[1]["grpc"]["status"], 5);
+}
+
+#[tokio::test]
+async fn test_grpc_server_streaming() {
+    let _server = TestServer::start_with_grpc(PROTO).await;
+
+    add_stub(json!({
+        "service": "Greeter",
+        "method": "StreamHellos",
+        "stream": [{"message": "one"}, {"message": "two"}, {"message": "three"}],
+        "delay_ms": 20
+    })).await;
+
+    let reply = call("StreamHellos", json!({"name": "world", "times": 3})).await;
+    assert_eq!(reply.status, "0");
+    let texts: Vec<_> = reply.messages.iter().map(|m| m["message"].as_str().unwrap().to_string()).collect();
+    assert_eq!(texts, vec!["one", "two", "three"]);
+}
+
+#[tokio::test]
+async fn test_grpc_unmatched_and_invalid_stubs() {
+    let _server = TestServer::start_with_grpc(PROTO).await;
+
+    let reply = call("SayHello", json!({"name": "nobody"})).await;
+    assert_eq!(reply.status, "12");
+
+    let resp = reqwest::Client::new()
+        .post(format!("{}/__mock/grpc/stubs", BASE_URL))
+        .json(&json!({"service": "Greeter", "method": "Missing", "response": {}}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 400);
+
+    let services: serde_json::Value = reqwest::get(format!("{}/__mock/grpc", BASE_URL))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(services["services"][0]["name"], "test.greeter.Greeter");
+    assert_eq!(services["services"][0]["methods"][1]["server_streaming"], true);
+}
+
+#[tokio::test]
+async fn test_grpc_rejects_oversized_requests() {
+    let _server = TestServer::start_with_grpc(PROTO).await;
+    add_stub(json!({"service": "Greeter", "method": "SayHello", "response": {"message": "hi"}})).await;
+
+    let reply = call("SayHello", json!({"name": "x".repeat(300 * 1024)})).await;
+    assert_eq!(reply.status, "8");
+    assert!(reply.messages.is_empty());
+
+    let reply = call("SayHello", json!({"name": "Ada"})).await;
+    assert_eq!(reply.status, "0");
+}
+
+#[tokio::test]
+async fn test_grpc_failed_proto_load_leaves_services_untouched() {
+    let _server = TestServer::start_with_grpc(PROTO).await;
+
+    let dir = std::env::temp_dir().join("rustmock_grpc_load");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("extra.proto"), "syntax = \"proto3\";\npackage test.extra;\nservice Extra { rpc Ping (Empty) returns (Empty); }\nmessage Empty {}\n").unwrap();
+    let descriptors = protox::compile([dir.join("extra.proto")], [&dir]).unwrap().encode_to_vec();
+    let descriptor_set = dir.join("extra.bin");
+    std::fs::write(&descriptor_set, descriptors).unwrap();
+
+    let resp = reqwest::Client::new()
+        .post(format!("{}/__mock/grpc/protos", BASE_URL))
+        .json(&json!({"files": [descriptor_set, dir.join("missing.proto")]}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 400);
+
+    let services: serde_json::Value = reqwest::get(format!("{}/__mock/grpc", BASE_URL))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let names: Vec<&str> = services["services"].as_array().unwrap().iter().map(|s| s["name"].as_str().unwrap()).collect();
+    assert_eq!(names, ["test.greeter.Greeter"]);
+
+    let resp = reqwest::Client::new()
+        .post(format!("{}/__mock/grpc/protos", BASE_URL))
+        .json(&json!({"files": [descriptor_set]}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(resp.json::<serde_json::Value>().await.unwrap()["services"], 2);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -53,7 +53,7 @@ async fn test_different_http_methods() {
     let _server = TestServer::start().await;
     let client = reqwest::Client::new();
 
-    let methods = vec![
+    let methods = [
         ("PUT", "/api/update"),
         ("PATCH", "/api/partial"),
         ("DELETE", "/api/remove"),
@@ -118,7 +118,7 @@ async fn test_different_status_codes() {
     let _server = TestServer::start().await;
     let client = reqwest::Client::new();
 
-    let status_codes = vec![
+    let status_codes = [
         (200, "/api/ok"),
         (201, "/api/created"),
         (204, "/api/no-content"),
@@ -214,3 +214,154 @@ async fn test_request_body_and_query_params_in_logs() {
     assert_eq!(log["request_body"]["email"], "test@example.com");
     assert_eq!(log["request_body"]["age"], 25);
 }
+
+#[tokio::test]
+async fn test_most_specific_path_template_wins() {
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+
+    for (path, name) in [
+        ("/api/files/*", "wildcard"),
+        ("/api/files/{name}", "param"),
+        ("/api/files/{name}.json", "json"),
+        ("/api/files/readme", "literal"),
+        ("/api/files/{name}/raw", "raw"),
+    ] {
+        client
+            .post(format!("{}/__mock/endpoints", BASE_URL))
+            .json(&json!({"method": "GET", "path": path, "response": {"name": name}}))
+            .send()
+            .await
+            .expect("Failed to add endpoint");
+    }
+
+    for (path, expected) in [
+        ("/api/files/readme", "literal"),
+        ("/api/files/data.json", "json"),
+        ("/api/files/data.csv", "param"),
+        ("/api/files/readme/raw", "raw"),
+    ] {
+        let body: serde_json::Value = client
+            .get(format!("{}{}", BASE_URL, path))
+            .send()
+            .await
+            .expect("Failed to call endpoint")
+            .json()
+            .await
+            .expect("Failed to parse response");
+        assert_eq!(body["name"], expected, "{}", path);
+    }
+
+    client
+        .delete(format!("{}/__mock/endpoints", BASE_URL))
+        .json(&json!({"method": "GET", "path": "/api/files/{name}"}))
+        .send()
+        .await
+        .expect("Failed to remove endpoint");
+
+    let body: serde_json::Value = client
+        .get(format!("{}/api/files/data.csv", BASE_URL))
+        .send()
+        .await
+        .expect("Failed to call endpoint")
+        .json()
+        .await
+        .expect("Failed to parse response");
+    assert_eq!(body["name"], "wildcard");
+}
+
+#[tokio::test]
+async fn test_typed_regex_and_catch_all_paths_capture_params() {
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+
+    for (path, response) in [
+        ("/api/users/me", json!({"kind": "me"})),
+        ("/api/users/{id:int}", json!({"kind": "int", "id": "{$request.path.id}"})),
+        ("/api/users/{id:uuid}", json!({"kind": "uuid", "id": "{$request.path.id}"})),
+        ("/api/users/{name}", json!({"kind": "name", "id": "{$request.path.name}"})),
+        ("/api/codes/{code:[A-Z]{3}}", json!({"kind": "code", "id": "{$request.path.code}"})),
+        ("/api/files/{rest:**}", json!({"kind": "files", "id": "{$request.path.rest}"})),
+        ("/api/docs/{section?}", json!({"kind": "docs", "id": "{$request.path.section}"})),
+        ("^/api/v(?P<version>[0-9]+)/legacy$", json!({"kind": "regex", "id": "{$request.path.version}"})),
+    ] {
+        let response = client
+            .post(format!("{}/__mock/endpoints", BASE_URL))
+            .json(&json!({"method": "GET", "path": path, "response": response, "template": true}))
+            .send()
+            .await
+            .expect("Failed to add endpoint");
+        assert!(response.status().is_success(), "{}", path);
+    }
+
+    for (path, kind, id) in [
+        ("/api/users/me", json!("me"), serde_json::Value::Null),
+        ("/api/users/42", json!("int"), json!("42")),
+        ("/api/users/3fa85f64-5717-4562-b3fc-2c963f66afa6", json!("uuid"), json!("3fa85f64-5717-4562-b3fc-2c963f66afa6")),
+        ("/api/users/bob", json!("name"), json!("bob")),
+        ("/api/codes/ABC", json!("code"), json!("ABC")),
+        ("/api/files/reports/2024/q1.csv", json!("files"), json!("reports/2024/q1.csv")),
+        ("/api/docs", json!("docs"), serde_json::Value::Null),
+        ("/api/docs/intro", json!("docs"), json!("intro")),
+        ("/api/v2/legacy", json!("regex"), json!("2")),
+    ] {
+        let body: serde_json::Value = client
+            .get(format!("{}{}", BASE_URL, path))
+            .send()
+            .await
+            .expect("Failed to call endpoint")
+            .json()
+            .await
+            .expect("Failed to parse response");
+        assert_eq!(body["kind"], kind, "{}", path);
+        assert_eq!(body["id"], id, "{}", path);
+    }
+
+    let response = client
+        .get(format!("{}/api/codes/abcd", BASE_URL))
+        .send()
+        .await
+        .expect("Failed to call endpoint");
+    assert_eq!(response.status(), 404);
+
+    let logs: serde_json::Value = client
+        .get(format!("{}/__mock/logs", BASE_URL))
+        .send()
+        .await
+        .expect("Failed to get logs")
+        .json()
+        .await
+        .expect("Failed to parse logs");
+    let log = logs.as_array().unwrap().iter().find(|l| l["path"] == "/api/users/42").expect("Log not found");
+    assert_eq!(log["path_params"], json!({"id": "42"}));
+
+    let response = client
+        .post(format!("{}/__mock/endpoints", BASE_URL))
+        .json(&json!({"method": "GET", "path": "/api/bad/{id:[}", "response": {}}))
+        .send()
+        .await
+        .expect("Failed to add endpoint");
+    assert_eq!(response.status(), 400);
+}
+
+#[tokio::test]
+async fn test_stub_bodies_are_only_templated_when_asked() {
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+
+    let literal = json!({"docs": "Write {$request.path.id} to echo the id", "raw": "{$request.path.id}"});
+    for (path, template) in [("/api/literal/{id}", false), ("/api/templated/{id}", true)] {
+        let response = client
+            .post(format!("{}/__mock/endpoints", BASE_URL))
+            .json(&json!({"method": "GET", "path": path, "response": literal, "template": template}))
+            .send()
+            .await
+            .expect("Failed to add endpoint");
+        assert!(response.status().is_success());
+    }
+
+    let body: serde_json::Value = client.get(format!("{}/api/literal/7", BASE_URL)).send().await.unwrap().json().await.unwrap();
+    assert_eq!(body, literal);
+    let body: serde_json::Value = client.get(format!("{}/api/templated/7", BASE_URL)).send().await.unwrap().json().await.unwrap();
+    assert_eq!(body, json!({"docs": "Write 7 to echo the id", "raw": "7"}));
+}
@@ -96,3 +96,105 @@ async fn test_clear_logs_integration() {
 
     assert_eq!(log_entries.len(), 0, "Expected logs to be empty after clearing");
 }
+
+async fn last_log(client: &reqwest::Client) -> serde_json::Value {
+    let logs: serde_json::Value = client
+        .get(format!("{}/__mock/logs", BASE_URL))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    logs.as_array().unwrap().last().unwrap().clone()
+}
+
+#[tokio::test]
+async fn test_logs_capture_non_json_bodies() {
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+
+    client
+        .post(format!("{}/api/notes", BASE_URL))
+        .header("content-type", "text/plain")
+        .body("just some text")
+        .send()
+        .await
+        .unwrap();
+    let log = last_log(&client).await;
+    assert_eq!(log["request_body"], serde_json::Value::Null);
+    assert_eq!(log["request_body_raw"], json!({"encoding": "text", "data": "just some text", "size": 14, "truncated": false}));
+
+    client
+        .post(format!("{}/api/upload", BASE_URL))
+        .header("content-type", "application/octet-stream")
+        .body(vec![0u8, 159, 146, 150])
+        .send()
+        .await
+        .unwrap();
+    let log = last_log(&client).await;
+    assert_eq!(log["request_body_raw"]["encoding"], "base64");
+    assert_eq!(log["request_body_raw"]["data"], "AJ+Slg==");
+
+    client
+        .post(format!("{}/api/login", BASE_URL))
+        .header("content-type", "application/x-www-form-urlencoded")
+        .body("user=ada&role=admin&role=dev")
+        .send()
+        .await
+        .unwrap();
+    let log = last_log(&client).await;
+    assert_eq!(log["request_form"], json!({"fields": {"user": "ada", "role": ["admin", "dev"]}}));
+}
+
+#[tokio::test]
+async fn test_logs_capture_multipart_uploads() {
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+
+    let body = "--XyZ\r\n\
+        Content-Disposition: form-data; name=\"title\"\r\n\r\n\
+        Quarterly report\r\n\
+        --XyZ\r\n\
+        Content-Disposition: form-data; name=\"attachment\"; filename=\"report.csv\"\r\n\
+        Content-Type: text/csv\r\n\r\n\
+        a,b\n1,2\n\r\n\
+        --XyZ--\r\n";
+
+    client
+        .post(format!("{}/api/reports", BASE_URL))
+        .header("content-type", "multipart/form-data; boundary=XyZ")
+        .body(body)
+        .send()
+        .await
+        .unwrap();
+
+    let log = last_log(&client).await;
+    assert_eq!(log["request_form"]["fields"], json!({"title": "Quarterly report"}));
+    assert_eq!(log["request_form"]["files"], json!([
+        {"field": "attachment", "filename": "report.csv", "content_type": "text/csv", "size": 8}
+    ]));
+    assert_eq!(log["request_body_raw"]["size"], body.len());
+}
+
+#[tokio::test]
+async fn test_logs_keep_only_the_most_recent_entries() {
+    let _server = TestServer::start_with_vars(vec![("MAX_LOGS", "3")]).await;
+    let client = reqwest::Client::new();
+
+    client.delete(format!("{}/__mock/logs", BASE_URL)).send().await.unwrap();
+    for i in 1..=5 {
+        client.get(format!("{}/api/ring/{}", BASE_URL, i)).send().await.unwrap();
+    }
+
+    let logs: serde_json::Value = client
+        .get(format!("{}/__mock/logs", BASE_URL))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let paths: Vec<&str> = logs.as_array().unwrap().iter().map(|l| l["path"].as_str().unwrap()).collect();
+    assert_eq!(paths, ["/api/ring/3", "/api/ring/4", "/api/ring/5"]);
+}
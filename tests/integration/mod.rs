@@ -1,6 +1,25 @@
 pub mod common;
+pub mod auth;
 pub mod basic;
+pub mod cache;
+pub mod cors;
+pub mod dashboard;
+pub mod graphql;
+pub mod grpc;
 pub mod http;
 pub mod logging;
+pub mod negotiate;
+pub mod oauth;
 pub mod openapi;
 pub mod proxy;
+pub mod ratelimit;
+pub mod resource;
+pub mod shadow;
+pub mod soap;
+pub mod sse;
+pub mod streaming;
+pub mod throttle;
+pub mod tls;
+pub mod variants;
+pub mod webhook;
+pub mod websocket;
@@ -0,0 +1,181 @@
+use super::common::{TestServer, BASE_URL, UPSTREAM_URL};
+use serde_json::json;
+use std::io::Read;
+
+#[tokio::test]
+async fn test_stub_bodies_are_compressed_for_accept_encoding() {
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+
+    let expected = json!({"items": vec!["compressible"; 50]});
+    for (path, encoding) in [("/api/items", None), ("/api/corrupt", Some("broken"))] {
+        let response = client
+            .post(format!("{}/__mock/endpoints", BASE_URL))
+            .json(&json!({"method": "GET", "path": path, "response": expected, "encoding": encoding}))
+            .send()
+            .await
+            .expect("Failed to add endpoint");
+        assert!(response.status().is_success());
+    }
+
+    let fetch = |path: &'static str, accept_encoding: Option<&'static str>| {
+        let mut request = client.get(format!("{}{}", BASE_URL, path));
+        if let Some(accept_encoding) = accept_encoding {
+            request = request.header("accept-encoding", accept_encoding);
+        }
+        async move {
+            let response = request.send().await.unwrap();
+            assert_eq!(response.status(), 200);
+            let encoding = response.headers().get("content-encoding").map(|v| v.to_str().unwrap().to_string());
+            (encoding, response.bytes().await.unwrap().to_vec())
+        }
+    };
+
+    let (encoding, body) = fetch("/api/items", None).await;
+    assert_eq!(encoding, None);
+    assert_eq!(serde_json::from_slice::<serde_json::Value>(&body).unwrap(), expected);
+
+    let (encoding, body) = fetch("/api/items", Some("gzip")).await;
+    assert_eq!(encoding.as_deref(), Some("gzip"));
+    let mut decoded = Vec::new();
+    flate2::read::GzDecoder::new(body.as_slice()).read_to_end(&mut decoded).unwrap();
+    assert_eq!(serde_json::from_slice::<serde_json::Value>(&decoded).unwrap(), expected);
+
+    let (encoding, body) = fetch("/api/items", Some("gzip;q=0.5, br")).await;
+    assert_eq!(encoding.as_deref(), Some("br"));
+    let mut decoded = Vec::new();
+    brotli::Decompressor::new(body.as_slice(), 4096).read_to_end(&mut decoded).unwrap();
+    assert_eq!(serde_json::from_slice::<serde_json::Value>(&decoded).unwrap(), expected);
+
+    let (encoding, body) = fetch("/api/items", Some("zstd, *;q=0.1")).await;
+    assert_eq!(encoding.as_deref(), Some("zstd"));
+    let decoded = zstd::decode_all(body.as_slice()).unwrap();
+    assert_eq!(serde_json::from_slice::<serde_json::Value>(&decoded).unwrap(), expected);
+
+    // A forced encoding ignores what the client asked for.
+    let (encoding, body) = fetch("/api/corrupt", None).await;
+    assert_eq!(encoding.as_deref(), Some("gzip"));
+    let mut decoded = Vec::new();
+    assert!(flate2::read::GzDecoder::new(body.as_slice()).read_to_end(&mut decoded).is_err());
+}
+
+#[tokio::test]
+async fn test_resource_bodies_are_compressed_for_accept_encoding() {
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+
+    let seed: Vec<_> = (1..=20).map(|id| json!({"id": id, "name": "compressible"})).collect();
+    let response = client
+        .post(format!("{}/__mock/resources", BASE_URL))
+        .json(&json!({"path": "/users", "seed": seed}))
+        .send()
+        .await
+        .expect("Failed to add resource");
+    assert!(response.status().is_success());
+
+    let response = client
+        .get(format!("{}/users", BASE_URL))
+        .header("accept-encoding", "gzip")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.headers()["content-encoding"], "gzip");
+    let body = response.bytes().await.unwrap();
+    let mut decoded = Vec::new();
+    flate2::read::GzDecoder::new(body.as_ref()).read_to_end(&mut decoded).unwrap();
+    assert_eq!(serde_json::from_slice::<serde_json::Value>(&decoded).unwrap(), json!(seed));
+}
+
+#[tokio::test]
+async fn test_proxied_bodies_are_not_recompressed() {
+    let _upstream = TestServer::start_upstream().await;
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+
+    let expected = json!({"items": vec!["compressible"; 50]});
+    let response = client
+        .post(format!("{}/__mock/endpoints", UPSTREAM_URL))
+        .json(&json!({"method": "GET", "path": "/api/items", "response": expected}))
+        .send()
+        .await
+        .expect("Failed to add upstream endpoint");
+    assert!(response.status().is_success());
+    let response = client
+        .post(format!("{}/__mock/proxy", BASE_URL))
+        .json(&json!({"url": UPSTREAM_URL}))
+        .send()
+        .await
+        .expect("Failed to set proxy");
+    assert!(response.status().is_success());
+
+    let response = client
+        .get(format!("{}/api/items", BASE_URL))
+        .header("accept-encoding", "gzip")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    assert!(response.headers().get("content-encoding").is_none());
+    assert_eq!(response.json::<serde_json::Value>().await.unwrap(), expected);
+}
+
+#[tokio::test]
+async fn test_stub_representation_is_chosen_by_accept() {
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+
+    let response = client
+        .post(format!("{}/__mock/endpoints", BASE_URL))
+        .json(&json!({
+            "method": "GET",
+            "path": "/api/report",
+            "response": {"total": 3},
+            "representations": {
+                "application/xml": "<report><total>3</total></report>",
+                "text/csv": "total\n3\n"
+            }
+        }))
+        .send()
+        .await
+        .expect("Failed to add endpoint");
+    assert!(response.status().is_success());
+
+    let fetch = |accept: Option<&'static str>| {
+        let mut request = client.get(format!("{}/api/report", BASE_URL));
+        if let Some(accept) = accept {
+            request = request.header("accept", accept);
+        }
+        async move {
+            let response = request.send().await.unwrap();
+            let status = response.status().as_u16();
+            let content_type = response.headers().get("content-type").unwrap().to_str().unwrap().to_string();
+            (status, content_type, response.text().await.unwrap())
+        }
+    };
+
+    let (status, content_type, body) = fetch(None).await;
+    assert_eq!((status, content_type.as_str()), (200, "application/json"));
+    assert_eq!(serde_json::from_str::<serde_json::Value>(&body).unwrap(), json!({"total": 3}));
+
+    let (status, content_type, body) = fetch(Some("application/xml")).await;
+    assert_eq!((status, content_type.as_str()), (200, "application/xml"));
+    assert_eq!(body, "<report><total>3</total></report>");
+
+    let (status, content_type, body) = fetch(Some("application/json;q=0.2, text/*;q=0.8")).await;
+    assert_eq!((status, content_type.as_str()), (200, "text/csv"));
+    assert_eq!(body, "total\n3\n");
+
+    let (status, _, body) = fetch(Some("image/png")).await;
+    assert_eq!(status, 406);
+    let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(body["available"], json!(["application/json", "application/xml", "text/csv"]));
+
+    let response = client
+        .post(format!("{}/__mock/endpoints", BASE_URL))
+        .json(&json!({"method": "GET", "path": "/api/bad", "response": {}, "representations": {"text/*": "x"}}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+}
@@ -0,0 +1,220 @@
+use super::common::{TestServer, BASE_URL};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde_json::json;
+
+const VERIFIER: &str = "dBjftJeZ4CVP-mJ92K9u2aQHk2ZGAJ2sHxzCOmtK8cA";
+
+async fn configure(client: &reqwest::Client) {
+    let response = client
+        .post(format!("{}/__mock/oauth", BASE_URL))
+        .json(&json!({
+            "clients": [
+                {"client_id": "web", "redirect_uris": ["http://app.test/callback"]},
+                {"client_id": "worker", "client_secret": "s3cret"}
+            ],
+            "users": [{"username": "alice", "password": "wonderland", "claims": {"email": "alice@example.com"}}],
+            "claims": {"tenant": "acme"}
+        }))
+        .send()
+        .await
+        .expect("Failed to configure OAuth");
+    assert!(response.status().is_success());
+}
+
+#[tokio::test]
+async fn test_authorization_code_with_pkce_unlocks_protected_stub() {
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::builder().redirect(reqwest::redirect::Policy::none()).build().unwrap();
+    configure(&client).await;
+
+    client
+        .post(format!("{}/__mock/endpoints", BASE_URL))
+        .json(&json!({"method": "GET", "path": "/api/me", "response": {"ok": true}, "require_auth": true}))
+        .send()
+        .await
+        .expect("Failed to add endpoint");
+
+    let response = client.get(format!("{}/api/me", BASE_URL)).send().await.unwrap();
+    assert_eq!(response.status(), 401);
+    assert!(response.headers()["www-authenticate"].to_str().unwrap().starts_with("Bearer"));
+
+    let discovery: serde_json::Value = client
+        .get(format!("{}/.well-known/openid-configuration", BASE_URL))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(discovery["token_endpoint"], format!("{}/oauth/token", BASE_URL));
+    let jwks: serde_json::Value = client.get(discovery["jwks_uri"].as_str().unwrap()).send().await.unwrap().json().await.unwrap();
+    assert_eq!(jwks["keys"][0]["alg"], "ES256");
+
+    let challenge = URL_SAFE_NO_PAD.encode(ring::digest::digest(&ring::digest::SHA256, VERIFIER.as_bytes()));
+    let response = client
+        .get(format!("{}/oauth/authorize", BASE_URL))
+        .query(&[
+            ("response_type", "code"),
+            ("client_id", "web"),
+            ("redirect_uri", "http://app.test/callback"),
+            ("scope", "openid email"),
+            ("state", "xyz"),
+            ("code_challenge", &challenge),
+            ("code_challenge_method", "S256"),
+        ])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 302);
+    let location = reqwest::Url::parse(response.headers()["location"].to_str().unwrap()).unwrap();
+    let params: std::collections::HashMap<_, _> = location.query_pairs().into_owned().collect();
+    assert_eq!(params["state"], "xyz");
+
+    let exchange = |verifier: &'static str| {
+        client
+            .post(format!("{}/oauth/token", BASE_URL))
+            .form(&[
+                ("grant_type", "authorization_code"),
+                ("code", params["code"].as_str()),
+                ("client_id", "web"),
+                ("redirect_uri", "http://app.test/callback"),
+                ("code_verifier", verifier),
+            ])
+            .send()
+    };
+    let tokens: serde_json::Value = exchange(VERIFIER).await.unwrap().json().await.unwrap();
+    assert!(tokens["id_token"].is_string());
+    assert!(tokens["refresh_token"].is_string());
+    let access_token = tokens["access_token"].as_str().unwrap();
+
+    let response = exchange(VERIFIER).await.unwrap();
+    assert_eq!(response.status(), 400);
+    assert_eq!(response.json::<serde_json::Value>().await.unwrap()["error"], "invalid_grant");
+
+    let response = client.get(format!("{}/api/me", BASE_URL)).bearer_auth(access_token).send().await.unwrap();
+    assert_eq!(response.status(), 200);
+
+    let userinfo: serde_json::Value = client
+        .get(format!("{}/oauth/userinfo", BASE_URL))
+        .bearer_auth(access_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(userinfo, json!({"sub": "alice", "email": "alice@example.com", "tenant": "acme"}));
+
+    let response = client.get(format!("{}/api/me", BASE_URL)).bearer_auth(tokens["id_token"].as_str().unwrap()).send().await.unwrap();
+    assert_eq!(response.status(), 401);
+}
+
+#[tokio::test]
+async fn test_token_endpoint_grants_and_client_authentication() {
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+    configure(&client).await;
+    let token = |form: Vec<(&'static str, &'static str)>| client.post(format!("{}/oauth/token", BASE_URL)).form(&form).send();
+
+    let response = client
+        .post(format!("{}/oauth/token", BASE_URL))
+        .basic_auth("worker", Some("wrong"))
+        .form(&[("grant_type", "client_credentials")])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 401);
+
+    let tokens: serde_json::Value = client
+        .post(format!("{}/oauth/token", BASE_URL))
+        .basic_auth("worker", Some("s3cret"))
+        .form(&[("grant_type", "client_credentials"), ("scope", "reports:read")])
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(tokens["scope"], "reports:read");
+    assert!(tokens.get("refresh_token").is_none());
+
+    let response = token(vec![("grant_type", "password"), ("client_id", "web"), ("username", "alice"), ("password", "nope")]).await.unwrap();
+    assert_eq!(response.status(), 400);
+
+    let tokens: serde_json::Value = token(vec![("grant_type", "password"), ("client_id", "web"), ("username", "alice"), ("password", "wonderland")])
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let refresh_token = tokens["refresh_token"].as_str().unwrap().to_string();
+
+    let refreshed: serde_json::Value = client
+        .post(format!("{}/oauth/token", BASE_URL))
+        .form(&[("grant_type", "refresh_token"), ("client_id", "web"), ("refresh_token", refresh_token.as_str())])
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(refreshed["access_token"].is_string());
+    assert_ne!(refreshed["refresh_token"], tokens["refresh_token"]);
+
+    let response = token(vec![("grant_type", "implicit"), ("client_id", "web")]).await.unwrap();
+    assert_eq!(response.json::<serde_json::Value>().await.unwrap()["error"], "unsupported_grant_type");
+}
+
+#[tokio::test]
+async fn test_unregistered_redirects_stay_on_loopback_or_the_issuer() {
+    let _server = TestServer::start_with_vars(vec![("OAUTH_ENABLED", "true")]).await;
+    let client = reqwest::Client::builder().redirect(reqwest::redirect::Policy::none()).build().unwrap();
+
+    let authorize = |redirect_uri: &'static str| {
+        client
+            .get(format!("{}/oauth/authorize", BASE_URL))
+            .query(&[("response_type", "code"), ("client_id", "anything"), ("redirect_uri", redirect_uri)])
+            .send()
+    };
+
+    for redirect_uri in ["http://localhost:3000/callback", "http://127.0.0.1:5173/cb", "http://[::1]/cb"] {
+        let response = authorize(redirect_uri).await.unwrap();
+        assert_eq!(response.status(), 302, "{}", redirect_uri);
+        assert!(response.headers()["location"].to_str().unwrap().starts_with(redirect_uri));
+    }
+    for redirect_uri in ["https://evil.example/steal", "not a url"] {
+        let response = authorize(redirect_uri).await.unwrap();
+        assert_eq!(response.status(), 400, "{}", redirect_uri);
+        assert!(response.headers().get("location").is_none());
+    }
+
+    let response = client
+        .post(format!("{}/__mock/oauth", BASE_URL))
+        .json(&json!({"issuer": "https://auth.test/oauth"}))
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(authorize("https://auth.test/app/callback").await.unwrap().status(), 302);
+    assert_eq!(authorize("https://evil.example/steal").await.unwrap().status(), 400);
+}
+
+#[tokio::test]
+async fn test_provider_is_off_until_enabled() {
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+    let discovery = || client.get(format!("{}/.well-known/openid-configuration", BASE_URL)).send();
+
+    assert_eq!(discovery().await.unwrap().status(), 404);
+    let response = client.post(format!("{}/oauth/token", BASE_URL)).form(&[("grant_type", "client_credentials")]).send().await.unwrap();
+    assert_eq!(response.status(), 404);
+
+    let response = client.post(format!("{}/__mock/oauth", BASE_URL)).json(&json!({})).send().await.unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(discovery().await.unwrap().status(), 200);
+
+    let response = client.post(format!("{}/__mock/oauth", BASE_URL)).json(&json!({"enabled": false})).send().await.unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(discovery().await.unwrap().status(), 404);
+}
@@ -685,3 +685,134 @@ async fn test_openapi_file_auto_import_on_startup() {
 
     let _ = std::fs::remove_file(&openapi_file_path);
 }
+
+#[tokio::test]
+async fn test_import_enforces_security_requirements() {
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+
+    let spec = json!({
+        "openapi": "3.0.0",
+        "info": {"title": "Secured API", "version": "1.0.0"},
+        "security": [{"apiKey": []}],
+        "components": {
+            "securitySchemes": {
+                "apiKey": {"type": "apiKey", "in": "header", "name": "X-API-Key"},
+                "bearer": {"type": "http", "scheme": "bearer"},
+                "oauth": {"type": "oauth2", "flows": {"clientCredentials": {"tokenUrl": "/oauth/token", "scopes": {"orders:write": "Place orders"}}}},
+                "legacyKey": {"$ref": "#/components/securitySchemes/apiKey"}
+            }
+        },
+        "paths": {
+            "/health": {"get": {"security": [], "responses": {"200": {"description": "OK"}}}},
+            "/account": {
+                "get": {
+                    "responses": {
+                        "200": {"description": "OK"},
+                        "401": {"description": "No key", "content": {"application/json": {"example": {"code": "MISSING_KEY"}}}}
+                    }
+                }
+            },
+            "/profile": {"get": {"security": [{"bearer": []}], "responses": {"200": {"description": "OK"}}}},
+            "/orders": {"post": {"security": [{"oauth": ["orders:write"]}], "responses": {"201": {"description": "Created"}}}},
+            "/legacy": {"get": {"security": [{"legacyKey": []}], "responses": {"200": {"description": "OK"}}}},
+            "/typo": {"get": {"security": [{"apikey": []}], "responses": {"200": {"description": "OK"}}}}
+        }
+    });
+
+    let response = client
+        .post(format!("{}/__mock/import", BASE_URL))
+        .json(&json!({"openapi_spec": spec, "enforce_security": true}))
+        .send()
+        .await
+        .expect("Failed to import spec");
+    assert!(response.status().is_success());
+
+    let resp = client.get(format!("{}/health", BASE_URL)).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let resp = client.get(format!("{}/account", BASE_URL)).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 401);
+    assert_eq!(resp.json::<serde_json::Value>().await.unwrap(), json!({"code": "MISSING_KEY"}));
+
+    let resp = client.get(format!("{}/account", BASE_URL)).header("X-API-Key", "abc").send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let resp = client.get(format!("{}/profile", BASE_URL)).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 401);
+    assert_eq!(resp.headers()["www-authenticate"], "Bearer realm=\"RustMock\"");
+
+    // Referenced schemes are followed, and undefined ones keep the endpoint closed.
+    let resp = client.get(format!("{}/legacy", BASE_URL)).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 401);
+    let resp = client.get(format!("{}/legacy", BASE_URL)).header("X-API-Key", "abc").send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let resp = client.get(format!("{}/typo", BASE_URL)).header("X-API-Key", "abc").send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 401);
+
+    let token = |scope: &'static str| {
+        client
+            .post(format!("{}/__mock/oauth/token", BASE_URL))
+            .json(&json!({"scope": scope}))
+            .send()
+    };
+    let read_only: serde_json::Value = token("orders:read").await.unwrap().json().await.unwrap();
+    let resp = client
+        .post(format!("{}/orders", BASE_URL))
+        .bearer_auth(read_only["access_token"].as_str().unwrap())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let writer: serde_json::Value = token("orders:write").await.unwrap().json().await.unwrap();
+    let resp = client
+        .post(format!("{}/orders", BASE_URL))
+        .bearer_auth(writer["access_token"].as_str().unwrap())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 201);
+}
+
+#[tokio::test]
+async fn test_import_serves_examples_of_other_media_types() {
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+
+    let spec = json!({
+        "openapi": "3.0.0",
+        "info": {"title": "Catalog API", "version": "1.0.0"},
+        "paths": {
+            "/books": {
+                "get": {
+                    "responses": {
+                        "200": {
+                            "description": "OK",
+                            "content": {
+                                "application/json": {"example": [{"title": "Dune"}]},
+                                "application/xml": {"example": "<books><book>Dune</book></books>"}
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    });
+
+    let response = client
+        .post(format!("{}/__mock/import", BASE_URL))
+        .json(&json!({"openapi_spec": spec}))
+        .send()
+        .await
+        .expect("Failed to import spec");
+    assert!(response.status().is_success());
+
+    let resp = client.get(format!("{}/books", BASE_URL)).send().await.unwrap();
+    assert_eq!(resp.json::<serde_json::Value>().await.unwrap(), json!([{"title": "Dune"}]));
+
+    let resp = client.get(format!("{}/books", BASE_URL)).header("accept", "application/xml").send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(resp.headers()["content-type"], "application/xml");
+    assert_eq!(resp.text().await.unwrap(), "<books><book>Dune</book></books>");
+}
@@ -0,0 +1,85 @@
+use super::common::{TestServer, BASE_URL};
+use serde_json::json;
+
+#[tokio::test]
+async fn test_endpoint_rate_limit_rejects_with_429_and_headers() {
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+
+    let response = client
+        .post(format!("{}/__mock/endpoints", BASE_URL))
+        .json(&json!({
+            "method": "GET",
+            "path": "/api/limited",
+            "response": {"ok": true},
+            "rate_limit": {"limit": 2, "window_secs": 60}
+        }))
+        .send()
+        .await
+        .expect("Failed to add endpoint");
+    assert!(response.status().is_success());
+
+    for remaining in ["1", "0"] {
+        let response = client.get(format!("{}/api/limited", BASE_URL)).send().await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.headers()["x-ratelimit-limit"], "2");
+        assert_eq!(response.headers()["x-ratelimit-remaining"], remaining);
+    }
+
+    let response = client.get(format!("{}/api/limited", BASE_URL)).send().await.unwrap();
+    assert_eq!(response.status(), 429);
+    assert!(response.headers().contains_key("retry-after"));
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["error"], "Rate limit exceeded");
+
+    let logs: serde_json::Value = client
+        .get(format!("{}/__mock/logs", BASE_URL))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let rate_limit = &logs.as_array().unwrap().last().unwrap()["rate_limit"];
+    assert_eq!(rate_limit["scope"], "endpoint");
+    assert_eq!(rate_limit["limited"], true);
+
+    let response = client
+        .post(format!("{}/__mock/endpoints", BASE_URL))
+        .json(&json!({
+            "method": "GET",
+            "path": "/api/invalid",
+            "response": {},
+            "rate_limit": {"limit": 0}
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+}
+
+#[tokio::test]
+async fn test_global_rate_limit_counts_each_client_separately() {
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+
+    let response = client
+        .post(format!("{}/__mock/rate-limit", BASE_URL))
+        .json(&json!({"algorithm": "token_bucket", "limit": 1, "window_secs": 60, "key": "header:X-Client"}))
+        .send()
+        .await
+        .expect("Failed to set rate limit");
+    assert!(response.status().is_success());
+
+    let get = |name: &'static str| client.get(format!("{}/anything", BASE_URL)).header("X-Client", name).send();
+    assert_eq!(get("a").await.unwrap().status(), 404);
+    assert_eq!(get("a").await.unwrap().status(), 429);
+    assert_eq!(get("b").await.unwrap().status(), 404);
+
+    let response = client.delete(format!("{}/__mock/rate-limit", BASE_URL)).send().await.unwrap();
+    assert_eq!(response.json::<serde_json::Value>().await.unwrap()["removed"], true);
+
+    let response = get("a").await.unwrap();
+    assert_eq!(response.status(), 404);
+    assert!(!response.headers().contains_key("x-ratelimit-limit"));
+}
@@ -0,0 +1,177 @@
+use super::common::{TestServer, BASE_URL};
+use serde_json::json;
+
+#[tokio::test]
+async fn test_resource_crud_lifecycle() {
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+
+    client
+        .post(format!("{}/__mock/resources", BASE_URL))
+        .json(&json!({
+            "path": "/users",
+            "seed": [
+                {"id": 1, "name": "Ada", "role": "admin"},
+                {"id": 2, "name": "Linus", "role": "dev"}
+            ]
+        }))
+        .send()
+        .await
+        .unwrap();
+
+    let created = client
+        .post(format!("{}/users", BASE_URL))
+        .json(&json!({"name": "Grace", "role": "dev"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(created.status().as_u16(), 201);
+    let created: serde_json::Value = created.json().await.unwrap();
+    assert_eq!(created, json!({"id": 3, "name": "Grace", "role": "dev"}));
+
+    let devs = client
+        .get(format!("{}/users?role=dev&per_page=1&page=2", BASE_URL))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(devs.headers()["x-total-count"], "2");
+    let devs: serde_json::Value = devs.json().await.unwrap();
+    assert_eq!(devs, json!([{"id": 3, "name": "Grace", "role": "dev"}]));
+
+    let patched: serde_json::Value = client
+        .patch(format!("{}/users/3", BASE_URL))
+        .json(&json!({"role": null, "team": "compilers"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(patched, json!({"id": 3, "name": "Grace", "team": "compilers"}));
+
+    let replaced: serde_json::Value = client
+        .put(format!("{}/users/1", BASE_URL))
+        .json(&json!({"name": "Ada Lovelace"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(replaced, json!({"name": "Ada Lovelace", "id": 1}));
+
+    let deleted = client.delete(format!("{}/users/2", BASE_URL)).send().await.unwrap();
+    assert_eq!(deleted.status().as_u16(), 204);
+    let missing = client.get(format!("{}/users/2", BASE_URL)).send().await.unwrap();
+    assert_eq!(missing.status().as_u16(), 404);
+
+    client
+        .post(format!("{}/__mock/resources/reset", BASE_URL))
+        .send()
+        .await
+        .unwrap();
+    let users: serde_json::Value = client
+        .get(format!("{}/users", BASE_URL))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(users.as_array().unwrap().len(), 2);
+    assert_eq!(users[0]["name"], "Ada");
+}
+
+#[tokio::test]
+async fn test_resources_inferred_from_openapi() {
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+
+    let spec: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string("tests/fixtures/openapi-test.json").unwrap()
+    ).unwrap();
+
+    let imported: serde_json::Value = client
+        .post(format!("{}/__mock/import", BASE_URL))
+        .json(&json!({"openapi_spec": spec, "infer_resources": true}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(imported["resources"], json!(["/api/users"]));
+
+    let created: serde_json::Value = client
+        .post(format!("{}/api/users", BASE_URL))
+        .json(&json!({"name": "New"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(created["id"], 3);
+    let id = created["id"].as_u64().unwrap();
+
+    let fetched: serde_json::Value = client
+        .get(format!("{}/api/users/{}", BASE_URL, id))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(fetched["name"], "New");
+
+    let products = client.get(format!("{}/api/products/1", BASE_URL)).send().await.unwrap();
+    assert_eq!(products.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn test_resource_rejects_creates_once_integer_ids_run_out() {
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+
+    client
+        .post(format!("{}/__mock/resources", BASE_URL))
+        .json(&json!({"path": "/items"}))
+        .send()
+        .await
+        .unwrap();
+
+    let created = client
+        .post(format!("{}/items", BASE_URL))
+        .json(&json!({"id": u64::MAX, "name": "last"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(created.status().as_u16(), 201);
+
+    let exhausted = client
+        .post(format!("{}/items", BASE_URL))
+        .json(&json!({"name": "one more"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(exhausted.status().as_u16(), 409);
+
+    let items: serde_json::Value = client.get(format!("{}/items", BASE_URL)).send().await.unwrap().json().await.unwrap();
+    assert_eq!(items, json!([{"id": u64::MAX, "name": "last"}]));
+
+    client
+        .post(format!("{}/__mock/resources/reset?path=/items", BASE_URL))
+        .send()
+        .await
+        .unwrap();
+    let created: serde_json::Value = client
+        .post(format!("{}/items", BASE_URL))
+        .json(&json!({"name": "first"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(created, json!({"id": 1, "name": "first"}));
+}
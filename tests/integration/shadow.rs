@@ -0,0 +1,163 @@
+use super::common::{TestServer, BASE_URL, UPSTREAM_URL};
+use serde_json::json;
+use std::time::Duration;
+use tokio::time::sleep;
+
+async fn add_upstream_endpoint(client: &reqwest::Client, path: &str, response: serde_json::Value, status: u16) {
+    client
+        .post(format!("{}/__mock/endpoints", UPSTREAM_URL))
+        .json(&json!({
+            "method": "GET",
+            "path": path,
+            "response": response,
+            "status": status
+        }))
+        .send()
+        .await
+        .expect("Failed to add upstream endpoint");
+}
+
+#[tokio::test]
+async fn test_shadow_stub_serves_client_and_records_diff() {
+    let _upstream = TestServer::start_upstream().await;
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+
+    add_upstream_endpoint(&client, "/api/users/1", json!({"id": 1, "name": "Real", "email": "real@example.com"}), 200).await;
+
+    client
+        .post(format!("{}/__mock/endpoints", BASE_URL))
+        .json(&json!({
+            "method": "GET",
+            "path": "/api/users/{id}",
+            "response": {"id": "1", "name": "Mock"},
+            "status": 200,
+            "proxy_url": UPSTREAM_URL,
+            "shadow": "stub"
+        }))
+        .send()
+        .await
+        .unwrap();
+
+    let resp = client
+        .get(format!("{}/api/users/1", BASE_URL))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status().as_u16(), 200);
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(body["name"], "Mock");
+
+    let mut diff = serde_json::Value::Null;
+    for _ in 0..20 {
+        let logs: serde_json::Value = client
+            .get(format!("{}/__mock/logs", BASE_URL))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        diff = logs[0]["shadow_diff"].clone();
+        if !diff.is_null() {
+            break;
+        }
+        sleep(Duration::from_millis(100)).await;
+    }
+
+    assert_eq!(diff["served_by"], "stub");
+    assert_eq!(diff["upstream_status"], 200);
+    let differences = diff["differences"].as_array().unwrap();
+    assert!(differences.iter().any(|d| d["path"] == "/id" && d["stub"] == "string" && d["upstream"] == "number"));
+    assert!(differences.iter().any(|d| d["path"] == "/email" && d["stub"].is_null()));
+    assert!(!differences.iter().any(|d| d["path"] == "/name"));
+
+    let drift: serde_json::Value = client
+        .get(format!("{}/__mock/drift", BASE_URL))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    let summary = &drift["GET /api/users/{id}"];
+    assert_eq!(summary["comparisons"], 1);
+    assert_eq!(summary["mismatches"], 1);
+    assert_eq!(summary["differing_paths"]["body /email"], 1);
+}
+
+#[tokio::test]
+async fn test_shadow_proxy_serves_upstream_and_diffs_stub() {
+    let _upstream = TestServer::start_upstream().await;
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+
+    add_upstream_endpoint(&client, "/api/orders", json!({"orders": []}), 503).await;
+
+    client
+        .post(format!("{}/__mock/endpoints", BASE_URL))
+        .json(&json!({
+            "method": "GET",
+            "path": "/api/orders",
+            "response": {"orders": []},
+            "status": 200,
+            "proxy_url": UPSTREAM_URL,
+            "shadow": "proxy"
+        }))
+        .send()
+        .await
+        .unwrap();
+
+    let resp = client
+        .get(format!("{}/api/orders", BASE_URL))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status().as_u16(), 503);
+
+    let logs: serde_json::Value = client
+        .get(format!("{}/__mock/logs", BASE_URL))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    let diff = &logs[0]["shadow_diff"];
+    assert_eq!(diff["served_by"], "proxy");
+    let differences = diff["differences"].as_array().unwrap();
+    assert_eq!(differences.len(), 1);
+    assert_eq!(differences[0]["kind"], "status");
+    assert_eq!(differences[0]["stub"], 200);
+    assert_eq!(differences[0]["upstream"], 503);
+}
+
+#[tokio::test]
+async fn test_clear_drift_summary() {
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+
+    let resp = client
+        .delete(format!("{}/__mock/drift", BASE_URL))
+        .send()
+        .await
+        .unwrap();
+
+    assert!(resp.status().is_success());
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(body["cleared"], true);
+
+    let drift: serde_json::Value = client
+        .get(format!("{}/__mock/drift", BASE_URL))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(drift.as_object().unwrap().is_empty());
+}
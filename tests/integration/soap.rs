@@ -0,0 +1,175 @@
+use super::common::{TestServer, BASE_URL};
+use serde_json::json;
+
+const GET_USER: &str = r#"<?xml version="1.0"?>
+<soap:Envelope xmlns:soap="http://schemas.xmlsoap.org/soap/envelope/" xmlns:u="urn:users">
+  <soap:Body>
+    <u:GetUser><u:id>42</u:id></u:GetUser>
+  </soap:Body>
+</soap:Envelope>"#;
+
+async fn soap_call(client: &reqwest::Client, action: &str, body: &str) -> (u16, String) {
+    let resp = client
+        .post(format!("{}/soap/users", BASE_URL))
+        .header("content-type", "text/xml; charset=utf-8")
+        .header("SOAPAction", format!("\"{}\"", action))
+        .body(body.to_string())
+        .send()
+        .await
+        .unwrap();
+    let status = resp.status().as_u16();
+    (status, resp.text().await.unwrap())
+}
+
+#[tokio::test]
+async fn test_soap_matches_action_and_xpath() {
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+
+    client
+        .post(format!("{}/__mock/soap", BASE_URL))
+        .json(&json!({
+            "path": "/soap/users",
+            "soap_action": "urn:users/GetUser",
+            "namespaces": {"u": "urn:users"},
+            "match": [{"xpath": "//u:GetUser/u:id", "equals": "42"}],
+            "response": "<u:GetUserResponse xmlns:u=\"urn:users\"><u:name>Ada</u:name></u:GetUserResponse>"
+        }))
+        .send()
+        .await
+        .unwrap();
+    client
+        .post(format!("{}/__mock/soap", BASE_URL))
+        .json(&json!({
+            "path": "/soap/users",
+            "soap_action": "urn:users/GetUser",
+            "fault": {"code": "Client", "reason": "Unknown user"}
+        }))
+        .send()
+        .await
+        .unwrap();
+
+    let (status, body) = soap_call(&client, "urn:users/GetUser", GET_USER).await;
+    assert_eq!(status, 200);
+    assert!(body.contains("<soap:Envelope xmlns:soap=\"http://schemas.xmlsoap.org/soap/envelope/\">"));
+    assert!(body.contains("<u:name>Ada</u:name>"));
+
+    let (status, body) = soap_call(&client, "urn:users/GetUser", &GET_USER.replace("42", "7")).await;
+    assert_eq!(status, 500);
+    assert!(body.contains("<faultcode>soap:Client</faultcode><faultstring>Unknown user</faultstring>"));
+
+    let (status, body) = soap_call(&client, "urn:users/DeleteUser", GET_USER).await;
+    assert_eq!(status, 500);
+    assert!(body.contains("No stub matches SOAPAction"));
+
+    let logs: serde_json::Value = reqwest::get(format!("{}/__mock/logs", BASE_URL))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(logs[0]["request_body"].as_str().unwrap().contains("<u:id>42</u:id>"));
+    assert_eq!(logs[0]["soap"]["action"], "urn:users/GetUser");
+    assert_eq!(logs[0]["soap"]["operation"], "GetUser");
+}
+
+#[tokio::test]
+async fn test_wsdl_import_creates_operation_stubs() {
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+
+    let wsdl = r#"<definitions xmlns="http://schemas.xmlsoap.org/wsdl/"
+        xmlns:soap="http://schemas.xmlsoap.org/wsdl/soap/"
+        xmlns:soap12="http://schemas.xmlsoap.org/wsdl/soap12/"
+        targetNamespace="urn:users">
+      <binding name="UsersBinding" type="tns:UsersPort">
+        <soap:binding transport="http://schemas.xmlsoap.org/soap/http"/>
+        <operation name="GetUser"><soap:operation soapAction="urn:users/GetUser"/></operation>
+        <operation name="ListUsers"><soap:operation soapAction=""/></operation>
+      </binding>
+      <binding name="UsersBinding12" type="tns:UsersPort">
+        <soap12:binding transport="http://schemas.xmlsoap.org/soap/http"/>
+        <operation name="GetUser"><soap12:operation soapAction="urn:users/GetUser"/></operation>
+        <operation name="ListUsers"><soap12:operation soapAction=""/></operation>
+      </binding>
+      <service name="Users">
+        <port name="UsersPort" binding="tns:UsersBinding">
+          <soap:address location="http://legacy.example.com/soap/users"/>
+        </port>
+      </service>
+    </definitions>"#;
+
+    let imported: serde_json::Value = client
+        .post(format!("{}/__mock/soap/wsdl", BASE_URL))
+        .json(&json!({"wsdl": wsdl}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(imported["imported"], 2);
+    assert_eq!(imported["stubs"][0]["path"], "/soap/users");
+
+    let (status, body) = soap_call(&client, "urn:users/GetUser", GET_USER).await;
+    assert_eq!(status, 200);
+    assert!(body.contains("<tns:GetUserResponse xmlns:tns=\"urn:users\"/>"));
+
+    let list = GET_USER.replace("<u:GetUser><u:id>42</u:id></u:GetUser>", "<u:ListUsers/>");
+    let (status, body) = soap_call(&client, "", &list).await;
+    assert_eq!(status, 200);
+    assert!(body.contains("ListUsersResponse"));
+}
+
+#[tokio::test]
+async fn test_soap_stubs_reject_bad_xpath_and_can_be_removed_one_at_a_time() {
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+
+    let response = client
+        .post(format!("{}/__mock/soap", BASE_URL))
+        .json(&json!({"path": "/soap/users", "match": [{"xpath": "//u:GetUser["}]}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+
+    for (path, action) in [("/soap/users", "urn:users/GetUser"), ("/soap/users", "urn:users/DeleteUser"), ("/soap/orders", "urn:orders/GetOrder")] {
+        let response = client
+            .post(format!("{}/__mock/soap", BASE_URL))
+            .json(&json!({"path": path, "soap_action": action, "response": "<ok/>"}))
+            .send()
+            .await
+            .unwrap();
+        assert!(response.status().is_success());
+    }
+
+    let removed: serde_json::Value = client
+        .delete(format!("{}/__mock/soap?path=/soap/users&soap_action=urn:users/DeleteUser", BASE_URL))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(removed["removed"], 1);
+
+    let (status, _) = soap_call(&client, "urn:users/DeleteUser", GET_USER).await;
+    assert_eq!(status, 500);
+    let (status, _) = soap_call(&client, "urn:users/GetUser", GET_USER).await;
+    assert_eq!(status, 200);
+
+    let removed: serde_json::Value = client
+        .delete(format!("{}/__mock/soap?path=/soap/users", BASE_URL))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(removed["removed"], 1);
+
+    let stubs: serde_json::Value = reqwest::get(format!("{}/__mock/soap", BASE_URL)).await.unwrap().json().await.unwrap();
+    assert_eq!(stubs.as_array().unwrap().len(), 1);
+    assert_eq!(stubs[0]["path"], "/soap/orders");
+}
@@ -0,0 +1,117 @@
+use super::common::{TestServer, BASE_URL};
+use serde_json::json;
+use std::time::Duration;
+
+async fn read_until(resp: &mut reqwest::Response, buffer: &mut String, needle: &str) {
+    while !buffer.contains(needle) {
+        let chunk = tokio::time::timeout(Duration::from_secs(5), resp.chunk())
+            .await
+            .expect("Timed out waiting for event")
+            .unwrap()
+            .expect("Stream ended early");
+        buffer.push_str(std::str::from_utf8(&chunk).unwrap());
+    }
+}
+
+#[tokio::test]
+async fn test_sse_endpoint_emits_scripted_events() {
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+
+    client
+        .post(format!("{}/__mock/endpoints", BASE_URL))
+        .json(&json!({
+            "method": "GET",
+            "path": "/events/orders",
+            "response": {},
+            "sse": {
+                "events": [
+                    {"event": "created", "id": "1", "data": {"order": 1}, "retry": 2000},
+                    {"data": "line one\nline two", "delay_ms": 100}
+                ]
+            }
+        }))
+        .send()
+        .await
+        .unwrap();
+
+    let mut resp = client
+        .get(format!("{}/events/orders", BASE_URL))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(resp.headers()["content-type"], "text/event-stream");
+
+    let mut buffer = String::new();
+    read_until(&mut resp, &mut buffer, "line two\n\n").await;
+    assert_eq!(
+        buffer,
+        "id: 1\nevent: created\nretry: 2000\ndata: {\"order\":1}\n\ndata: line one\ndata: line two\n\n"
+    );
+}
+
+#[tokio::test]
+async fn test_sse_loop_and_push() {
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+
+    client
+        .post(format!("{}/__mock/endpoints", BASE_URL))
+        .json(&json!({
+            "method": "GET",
+            "path": "/events/{channel}",
+            "response": {},
+            "sse": {
+                "events": [{"event": "heartbeat", "data": "ok", "delay_ms": 50}],
+                "loop": true
+            }
+        }))
+        .send()
+        .await
+        .unwrap();
+
+    let mut resp = client
+        .get(format!("{}/events/news", BASE_URL))
+        .send()
+        .await
+        .unwrap();
+
+    let mut buffer = String::new();
+    read_until(&mut resp, &mut buffer, "event: heartbeat\ndata: ok\n\nevent: heartbeat\ndata: ok\n\n").await;
+
+    let clients: serde_json::Value = client
+        .get(format!("{}/__mock/sse/clients", BASE_URL))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(clients["/events/news"], 1);
+
+    let pushed: serde_json::Value = client
+        .post(format!("{}/__mock/sse/push", BASE_URL))
+        .json(&json!({"path": "/events/{channel}", "event": "breaking", "data": {"headline": "Mock wins"}}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(pushed["delivered"], 1);
+
+    read_until(&mut resp, &mut buffer, "event: breaking\ndata: {\"headline\":\"Mock wins\"}\n\n").await;
+
+    let pushed: serde_json::Value = client
+        .post(format!("{}/__mock/sse/push", BASE_URL))
+        .json(&json!({"path": "/events/other", "data": "nobody listens"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(pushed["delivered"], 0);
+}
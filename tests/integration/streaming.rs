@@ -0,0 +1,160 @@
+use super::common::{TestServer, BASE_URL, UPSTREAM_URL};
+use serde_json::json;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::time::sleep;
+
+const RAW_UPSTREAM_PORT: u16 = 18092;
+
+/// A bare HTTP/1.1 upstream that sends a chunked `text/event-stream` body,
+/// pausing between chunks, so the test can tell streaming from buffering.
+async fn start_chunked_upstream(chunks: Vec<String>, pause: Duration) {
+    let listener = TcpListener::bind(("127.0.0.1", RAW_UPSTREAM_PORT)).await.unwrap();
+    tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut buf = [0u8; 4096];
+        let _ = socket.read(&mut buf).await.unwrap();
+        socket.write_all(b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nTransfer-Encoding: chunked\r\n\r\n").await.unwrap();
+        for chunk in chunks {
+            socket.write_all(format!("{:x}\r\n{}\r\n", chunk.len(), chunk).as_bytes()).await.unwrap();
+            socket.flush().await.unwrap();
+            sleep(pause).await;
+        }
+        socket.write_all(b"0\r\n\r\n").await.unwrap();
+    });
+}
+
+#[tokio::test]
+async fn test_chunked_proxied_response_is_streamed() {
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+
+    start_chunked_upstream(
+        vec!["data: 1\n\n".to_string(), "data: 2\n\n".to_string(), "data: 3\n\n".to_string()],
+        Duration::from_millis(500),
+    ).await;
+
+    client
+        .post(format!("{}/__mock/proxy", BASE_URL))
+        .json(&json!({"url": format!("http://127.0.0.1:{}", RAW_UPSTREAM_PORT)}))
+        .send()
+        .await
+        .unwrap();
+
+    let started = Instant::now();
+    let mut resp = client
+        .get(format!("{}/events", BASE_URL))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(resp.headers()["content-type"], "text/event-stream");
+
+    let first = resp.chunk().await.unwrap().unwrap();
+    assert_eq!(&first[..], b"data: 1\n\n");
+    assert!(started.elapsed() < Duration::from_millis(1000), "First event should arrive before the upstream finishes");
+
+    let mut rest = Vec::new();
+    while let Some(chunk) = resp.chunk().await.unwrap() {
+        rest.extend_from_slice(&chunk);
+    }
+    assert_eq!(rest, b"data: 2\n\ndata: 3\n\n");
+
+    let logs: serde_json::Value = client
+        .get(format!("{}/__mock/logs", BASE_URL))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    let raw = &logs[0]["response_body_raw"];
+    assert_eq!(raw["encoding"], "text");
+    assert_eq!(raw["data"], "data: 1\n\ndata: 2\n\ndata: 3\n\n");
+    assert_eq!(raw["size"], 27);
+    assert_eq!(raw["truncated"], false);
+}
+
+#[tokio::test]
+async fn test_streamed_response_log_is_bounded() {
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+
+    start_chunked_upstream(vec!["y".repeat(40 * 1024), "z".repeat(40 * 1024)], Duration::from_millis(10)).await;
+
+    client
+        .post(format!("{}/__mock/proxy", BASE_URL))
+        .json(&json!({"url": format!("http://127.0.0.1:{}", RAW_UPSTREAM_PORT)}))
+        .send()
+        .await
+        .unwrap();
+
+    let resp = client
+        .get(format!("{}/download", BASE_URL))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.bytes().await.unwrap().len(), 80 * 1024);
+
+    let logs: serde_json::Value = client
+        .get(format!("{}/__mock/logs", BASE_URL))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    let raw = &logs[0]["response_body_raw"];
+    assert_eq!(raw["size"], 80 * 1024);
+    assert_eq!(raw["truncated"], true);
+    assert_eq!(raw["data"].as_str().unwrap().len(), 64 * 1024);
+}
+
+#[tokio::test]
+async fn test_small_json_proxied_response_is_buffered() {
+    let _upstream = TestServer::start_upstream().await;
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+
+    client
+        .post(format!("{}/__mock/endpoints", UPSTREAM_URL))
+        .json(&json!({
+            "method": "GET",
+            "path": "/api/small",
+            "response": {"ok": true},
+            "status": 200
+        }))
+        .send()
+        .await
+        .unwrap();
+
+    client
+        .post(format!("{}/__mock/proxy", BASE_URL))
+        .json(&json!({"url": UPSTREAM_URL}))
+        .send()
+        .await
+        .unwrap();
+
+    let resp = client
+        .get(format!("{}/api/small", BASE_URL))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let logs: serde_json::Value = client
+        .get(format!("{}/__mock/logs", BASE_URL))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    assert_eq!(logs[0]["response_body"]["ok"], true);
+    assert!(logs[0]["response_body_raw"].is_null());
+}
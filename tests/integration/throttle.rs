@@ -0,0 +1,87 @@
+use super::common::{TestServer, BASE_URL, UPSTREAM_URL};
+use serde_json::json;
+use std::time::{Duration, Instant};
+
+#[tokio::test]
+async fn test_throttled_stub_delays_first_byte_and_paces_body() {
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+
+    let payload = "x".repeat(1000);
+    let response = client
+        .post(format!("{}/__mock/endpoints", BASE_URL))
+        .json(&json!({
+            "method": "GET",
+            "path": "/api/slow",
+            "response": {"data": payload},
+            "throttle": {"ttfb_ms": 300, "bytes_per_sec": 2000}
+        }))
+        .send()
+        .await
+        .expect("Failed to add endpoint");
+    assert!(response.status().is_success());
+
+    let started = Instant::now();
+    let response = client.get(format!("{}/api/slow", BASE_URL)).send().await.unwrap();
+    let first_byte = started.elapsed();
+    assert_eq!(response.status(), 200);
+    assert!(response.content_length().is_some());
+    let body: serde_json::Value = response.json().await.unwrap();
+    let total = started.elapsed();
+
+    assert_eq!(body["data"], payload);
+    assert!(first_byte >= Duration::from_millis(300), "first byte after {:?}", first_byte);
+    assert!(total >= first_byte + Duration::from_millis(400), "first byte after {:?}, done after {:?}", first_byte, total);
+}
+
+#[tokio::test]
+async fn test_throttle_drips_proxied_bodies_in_chunks() {
+    let _upstream = TestServer::start_upstream().await;
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+
+    client
+        .post(format!("{}/__mock/endpoints", UPSTREAM_URL))
+        .json(&json!({"method": "GET", "path": "/api/report", "response": {"rows": [1, 2, 3, 4, 5]}}))
+        .send()
+        .await
+        .expect("Failed to add upstream endpoint");
+    client
+        .post(format!("{}/__mock/endpoints", BASE_URL))
+        .json(&json!({"method": "GET", "path": "/api/report", "response": {}, "proxy_url": UPSTREAM_URL}))
+        .send()
+        .await
+        .expect("Failed to add endpoint");
+
+    let response = client
+        .post(format!("{}/__mock/throttle", BASE_URL))
+        .json(&json!({"chunk_size": 5, "chunk_delay_ms": 100}))
+        .send()
+        .await
+        .expect("Failed to set throttle");
+    assert!(response.status().is_success());
+
+    let started = Instant::now();
+    let mut response = client.get(format!("{}/api/report", BASE_URL)).send().await.unwrap();
+    let mut chunks = Vec::new();
+    while let Some(chunk) = response.chunk().await.unwrap() {
+        chunks.push(chunk);
+    }
+    let elapsed = started.elapsed();
+    let body = chunks.concat();
+    assert!(chunks.iter().all(|c| c.len() <= 5));
+    assert_eq!(serde_json::from_slice::<serde_json::Value>(&body).unwrap(), json!({"rows": [1, 2, 3, 4, 5]}));
+    let pauses = body.len().div_ceil(5) as u64 - 1;
+    assert!(elapsed >= Duration::from_millis(100 * pauses), "done after {:?}", elapsed);
+
+    let response = client.delete(format!("{}/__mock/throttle", BASE_URL)).send().await.unwrap();
+    assert_eq!(response.json::<serde_json::Value>().await.unwrap()["removed"], true);
+
+    let response = client
+        .post(format!("{}/__mock/throttle", BASE_URL))
+        .json(&json!({"bytes_per_sec": 0}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+}
@@ -0,0 +1,72 @@
+use super::common::{TestServer, BASE_URL, HTTPS_PORT};
+use serde_json::json;
+use std::path::PathBuf;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rustmock-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn https_client(ca_pem: &[u8]) -> reqwest::Client {
+    reqwest::Client::builder()
+        .use_rustls_tls()
+        .add_root_certificate(reqwest::Certificate::from_pem(ca_pem).unwrap())
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_https_with_generated_local_ca() {
+    let dir = temp_dir("tls-generated");
+    let https_port = HTTPS_PORT.to_string();
+    let _server = TestServer::start_with_vars(vec![
+        ("HTTPS_PORT", &https_port),
+        ("TLS_DIR", dir.to_str().unwrap()),
+    ]).await;
+
+    for file in ["rustmock-ca.pem", "rustmock-ca-key.pem", "cert.pem", "key.pem"] {
+        assert!(dir.join(file).is_file(), "{} was not written", file);
+    }
+
+    reqwest::Client::new()
+        .post(format!("{}/__mock/endpoints", BASE_URL))
+        .json(&json!({"method": "GET", "path": "/secure", "response": {"tls": true}}))
+        .send()
+        .await
+        .unwrap();
+
+    let client = https_client(&std::fs::read(dir.join("rustmock-ca.pem")).unwrap());
+    for host in ["localhost", "127.0.0.1"] {
+        let resp = client.get(format!("https://{}:{}/secure", host, HTTPS_PORT)).send().await.unwrap();
+        assert_eq!(resp.json::<serde_json::Value>().await.unwrap(), json!({"tls": true}));
+    }
+
+    // Plain HTTP keeps working alongside HTTPS.
+    let resp = reqwest::get(format!("{}/secure", BASE_URL)).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn test_https_with_provided_certificate() {
+    let dir = temp_dir("tls-provided");
+    let certified = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+    let cert_pem = certified.cert.pem();
+    std::fs::write(dir.join("server.pem"), &cert_pem).unwrap();
+    std::fs::write(dir.join("server-key.pem"), certified.signing_key.serialize_pem()).unwrap();
+
+    let https_port = HTTPS_PORT.to_string();
+    let _server = TestServer::start_with_vars(vec![
+        ("HTTPS_PORT", &https_port),
+        ("TLS_CERT", dir.join("server.pem").to_str().unwrap()),
+        ("TLS_KEY", dir.join("server-key.pem").to_str().unwrap()),
+    ]).await;
+
+    let resp = https_client(cert_pem.as_bytes())
+        .get(format!("https://localhost:{}/__mock/config", HTTPS_PORT))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+}
@@ -0,0 +1,89 @@
+use super::common::{TestServer, BASE_URL};
+use serde_json::json;
+
+async fn add_flaky_endpoint(client: &reqwest::Client, variants: serde_json::Value) {
+    let response = client
+        .post(format!("{}/__mock/endpoints", BASE_URL))
+        .json(&json!({
+            "method": "GET",
+            "path": "/api/flaky",
+            "response": {"ok": true},
+            "variants": variants
+        }))
+        .send()
+        .await
+        .expect("Failed to add endpoint");
+    assert!(response.status().is_success());
+}
+
+async fn statuses(client: &reqwest::Client, count: usize) -> Vec<u16> {
+    let mut statuses = Vec::new();
+    for _ in 0..count {
+        let response = client.get(format!("{}/api/flaky", BASE_URL)).send().await.unwrap();
+        statuses.push(response.status().as_u16());
+    }
+    statuses
+}
+
+#[tokio::test]
+async fn test_round_robin_variants_cycle_in_order() {
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+
+    add_flaky_endpoint(&client, json!({
+        "policy": "round_robin",
+        "responses": [
+            {"name": "healthy"},
+            {"name": "outage", "status": 503, "response": {"error": "unavailable"}, "headers": {"Retry-After": "2"}}
+        ]
+    })).await;
+
+    assert_eq!(statuses(&client, 3).await, [200, 503, 200]);
+
+    let response = client.get(format!("{}/api/flaky", BASE_URL)).send().await.unwrap();
+    assert_eq!(response.headers()["retry-after"], "2");
+    assert_eq!(response.json::<serde_json::Value>().await.unwrap(), json!({"error": "unavailable"}));
+
+    let logs: serde_json::Value = client
+        .get(format!("{}/__mock/logs", BASE_URL))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let variant = &logs.as_array().unwrap().last().unwrap()["variant"];
+    assert_eq!(variant["index"], 1);
+    assert_eq!(variant["name"], "outage");
+    assert_eq!(variant["sequence"], 3);
+}
+
+#[tokio::test]
+async fn test_seeded_weighted_variants_are_reproducible() {
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+
+    let variants = json!({
+        "seed": 42,
+        "responses": [
+            {"weight": 3},
+            {"status": 503, "weight": 1}
+        ]
+    });
+
+    add_flaky_endpoint(&client, variants.clone()).await;
+    let first = statuses(&client, 40).await;
+    add_flaky_endpoint(&client, variants).await;
+    let second = statuses(&client, 40).await;
+
+    assert_eq!(first, second);
+    assert!(first.contains(&200) && first.contains(&503), "{:?}", first);
+
+    let response = client
+        .post(format!("{}/__mock/endpoints", BASE_URL))
+        .json(&json!({"method": "GET", "path": "/api/empty", "response": {}, "variants": {"responses": []}}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+}
@@ -0,0 +1,142 @@
+use super::common::{TestServer, BASE_URL, UPSTREAM_URL};
+use serde_json::json;
+use std::time::Duration;
+use tokio::time::sleep;
+
+async fn wait_for_callbacks(client: &reqwest::Client, count: usize) -> serde_json::Value {
+    for _ in 0..50 {
+        let logs: serde_json::Value = client
+            .get(format!("{}/__mock/logs", BASE_URL))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        let callbacks = logs[0]["callbacks"].clone();
+        if callbacks.as_array().is_some_and(|c| c.len() >= count) {
+            return callbacks;
+        }
+        sleep(Duration::from_millis(100)).await;
+    }
+    panic!("Callbacks were not recorded");
+}
+
+#[tokio::test]
+async fn test_stub_fires_templated_callback() {
+    let _upstream = TestServer::start_upstream().await;
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+
+    client
+        .post(format!("{}/__mock/endpoints", UPSTREAM_URL))
+        .json(&json!({"method": "POST", "path": "/hooks/{order}", "response": {"ok": true}}))
+        .send()
+        .await
+        .unwrap();
+
+    client
+        .post(format!("{}/__mock/endpoints", BASE_URL))
+        .json(&json!({
+            "method": "POST",
+            "path": "/orders/{order}/payments",
+            "response": {"id": "pay_123", "state": "pending"},
+            "status": 202,
+            "callbacks": [{
+                "url": "{$request.body#/callback_url}/{$request.path.order}",
+                "headers": {"x-signature": "sig-{$request.header.x-tenant}"},
+                "body": {
+                    "payment_id": "{$response.body#/id}",
+                    "amount": "{$request.body#/amount}",
+                    "note": "paid {$request.body#/amount} via {$method}"
+                },
+                "delay_ms": 50
+            }]
+        }))
+        .send()
+        .await
+        .unwrap();
+
+    let resp = client
+        .post(format!("{}/orders/o-9/payments", BASE_URL))
+        .header("x-tenant", "acme")
+        .json(&json!({"amount": 1250, "callback_url": format!("{}/hooks", UPSTREAM_URL)}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 202);
+
+    let callbacks = wait_for_callbacks(&client, 1).await;
+    assert_eq!(callbacks[0]["url"], format!("{}/hooks/o-9", UPSTREAM_URL));
+    assert_eq!(callbacks[0]["status"], 200);
+    assert_eq!(callbacks[0]["attempt"], 1);
+
+    let upstream_logs: serde_json::Value = client
+        .get(format!("{}/__mock/logs", UPSTREAM_URL))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let received = &upstream_logs[0];
+    assert_eq!(received["path"], "/hooks/o-9");
+    assert_eq!(received["request_headers"]["x-signature"], "sig-acme");
+    assert_eq!(received["request_body"], json!({
+        "payment_id": "pay_123",
+        "amount": 1250,
+        "note": "paid 1250 via POST"
+    }));
+}
+
+#[tokio::test]
+async fn test_failed_callback_is_retried() {
+    let _upstream = TestServer::start_upstream().await;
+    let _server = TestServer::start().await;
+    let client = reqwest::Client::new();
+
+    let response = client
+        .post(format!("{}/__mock/endpoints", BASE_URL))
+        .json(&json!({
+            "method": "POST",
+            "path": "/subscriptions",
+            "response": {},
+            "callbacks": [{"url": format!("{}/hooks/flaky", UPSTREAM_URL), "retries": u32::MAX}]
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+
+    client
+        .post(format!("{}/__mock/endpoints", UPSTREAM_URL))
+        .json(&json!({"method": "PUT", "path": "/hooks/flaky", "response": {}, "status": 503}))
+        .send()
+        .await
+        .unwrap();
+
+    client
+        .post(format!("{}/__mock/endpoints", BASE_URL))
+        .json(&json!({
+            "method": "POST",
+            "path": "/subscriptions",
+            "response": {"id": 1},
+            "callbacks": [{
+                "url": format!("{}/hooks/flaky", UPSTREAM_URL),
+                "method": "PUT",
+                "retries": 2,
+                "retry_delay_ms": 50
+            }]
+        }))
+        .send()
+        .await
+        .unwrap();
+
+    client.post(format!("{}/subscriptions", BASE_URL)).send().await.unwrap();
+
+    let callbacks = wait_for_callbacks(&client, 3).await;
+    let attempts: Vec<_> = callbacks.as_array().unwrap().iter()
+        .map(|c| (c["attempt"].as_u64().unwrap(), c["status"].as_u64().unwrap()))
+        .collect();
+    assert_eq!(attempts, vec![(1, 503), (2, 503), (3, 503)]);
+}
@@ -0,0 +1,245 @@
+use super::common::{TestServer, BASE_URL, UPSTREAM_URL};
+use awc::ws::{Frame, Message};
+use base64::Engine;
+use futures_util::{SinkExt, StreamExt};
+use serde_json::json;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+const RAW_UPSTREAM_PORT: u16 = 18092;
+
+async fn next_text(
+    conn: &mut actix_codec::Framed<awc::BoxedSocket, awc::ws::Codec>,
+) -> String {
+    loop {
+        let frame = actix_rt::time::timeout(Duration::from_secs(5), conn.next())
+            .await
+            .expect("Timed out waiting for frame")
+            .expect("Connection closed")
+            .expect("Protocol error");
+        if let Frame::Text(bytes) = frame {
+            return String::from_utf8(bytes.to_vec()).unwrap();
+        }
+    }
+}
+
+async fn add_ws_endpoint(base: &str, config: serde_json::Value) {
+    let resp = reqwest::Client::new()
+        .post(format!("{}/__mock/ws", base))
+        .json(&config)
+        .send()
+        .await
+        .expect("Failed to add WebSocket endpoint");
+    assert!(resp.status().is_success());
+}
+
+#[actix_rt::test]
+async fn test_scripted_websocket_endpoint() {
+    let _server = TestServer::start().await;
+
+    add_ws_endpoint(BASE_URL, json!({
+        "path": "/ws/chat/{room}",
+        "on_connect": [{"type": "welcome"}],
+        "replies": [
+            {"match": {"json": {"type": "ping"}}, "respond": [{"type": "pong"}]},
+            {"match": {"contains": "hello"}, "respond": ["hi there", "how are you?"]}
+        ]
+    })).await;
+
+    let (resp, mut conn) = awc::Client::new()
+        .ws(format!("{}/ws/chat/general", BASE_URL))
+        .connect()
+        .await
+        .expect("Failed to connect");
+    assert_eq!(resp.status().as_u16(), 101);
+
+    assert_eq!(next_text(&mut conn).await, r#"{"type":"welcome"}"#);
+
+    conn.send(Message::Text(r#"{"type": "ping", "id": 7}"#.into())).await.unwrap();
+    assert_eq!(next_text(&mut conn).await, r#"{"type":"pong"}"#);
+
+    conn.send(Message::Text("well hello".into())).await.unwrap();
+    assert_eq!(next_text(&mut conn).await, "hi there");
+    assert_eq!(next_text(&mut conn).await, "how are you?");
+
+    conn.send(Message::Close(None)).await.unwrap();
+
+    let logs: serde_json::Value = reqwest::get(format!("{}/__mock/logs", BASE_URL))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    let log = logs.as_array().unwrap().iter()
+        .find(|l| l["path"] == "/ws/chat/general")
+        .expect("WebSocket session not logged");
+    assert_eq!(log["status"], 101);
+    let frames = log["ws_frames"].as_array().unwrap();
+    assert_eq!(frames[0]["direction"], "out");
+    assert_eq!(frames[0]["data"], r#"{"type":"welcome"}"#);
+    assert_eq!(frames[1]["direction"], "in");
+    assert_eq!(frames[1]["data"], r#"{"type": "ping", "id": 7}"#);
+}
+
+#[actix_rt::test]
+async fn test_websocket_interval_messages() {
+    let _server = TestServer::start().await;
+
+    add_ws_endpoint(BASE_URL, json!({
+        "path": "/ws/ticker",
+        "interval": {"every_ms": 50, "messages": ["tick", "tock"]}
+    })).await;
+
+    let (_, mut conn) = awc::Client::new()
+        .ws(format!("{}/ws/ticker", BASE_URL))
+        .connect()
+        .await
+        .expect("Failed to connect");
+
+    assert_eq!(next_text(&mut conn).await, "tick");
+    assert_eq!(next_text(&mut conn).await, "tock");
+    assert_eq!(next_text(&mut conn).await, "tick");
+}
+
+#[actix_rt::test]
+async fn test_websocket_proxy_logs_both_directions() {
+    let _upstream = TestServer::start_upstream().await;
+    let _server = TestServer::start().await;
+
+    add_ws_endpoint(UPSTREAM_URL, json!({
+        "path": "/ws/echo",
+        "on_connect": ["ready"],
+        "replies": [{"match": {"regex": "^say "}, "respond": ["said"]}]
+    })).await;
+
+    add_ws_endpoint(BASE_URL, json!({
+        "path": "/ws/echo",
+        "proxy_url": UPSTREAM_URL
+    })).await;
+
+    let (_, mut conn) = awc::Client::new()
+        .ws(format!("{}/ws/echo", BASE_URL))
+        .connect()
+        .await
+        .expect("Failed to connect");
+
+    assert_eq!(next_text(&mut conn).await, "ready");
+    conn.send(Message::Text("say something".into())).await.unwrap();
+    assert_eq!(next_text(&mut conn).await, "said");
+
+    let logs: serde_json::Value = reqwest::get(format!("{}/__mock/logs", BASE_URL))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    let log = &logs[0];
+    assert_eq!(log["proxied_to"], format!("{}/ws/echo", UPSTREAM_URL));
+    let frames: Vec<(String, String)> = log["ws_frames"].as_array().unwrap().iter()
+        .map(|f| (f["direction"].as_str().unwrap().to_string(), f["data"].as_str().unwrap_or("").to_string()))
+        .collect();
+    assert_eq!(frames, vec![
+        ("out".to_string(), "ready".to_string()),
+        ("in".to_string(), "say something".to_string()),
+        ("out".to_string(), "said".to_string()),
+    ]);
+}
+
+#[actix_rt::test]
+async fn test_websocket_upgrade_without_endpoint_is_not_found() {
+    let _server = TestServer::start().await;
+
+    let result = awc::Client::new()
+        .ws(format!("{}/ws/missing", BASE_URL))
+        .connect()
+        .await;
+
+    match result {
+        Err(awc::error::WsClientError::InvalidResponseStatus(status)) => assert_eq!(status.as_u16(), 404),
+        other => panic!("Expected 404, got {:?}", other.map(|(r, _)| r.status())),
+    }
+}
+
+#[actix_rt::test]
+async fn test_websocket_endpoint_rejects_invalid_regex_and_prefers_literal_paths() {
+    let _server = TestServer::start().await;
+
+    let resp = reqwest::Client::new()
+        .post(format!("{}/__mock/ws", BASE_URL))
+        .json(&json!({"path": "/ws/bad", "replies": [{"match": {"regex": "(unclosed"}, "respond": ["x"]}]}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 400);
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(body["added"], false);
+
+    add_ws_endpoint(BASE_URL, json!({"path": "/ws/{room}", "on_connect": ["any room"]})).await;
+    add_ws_endpoint(BASE_URL, json!({"path": "/ws/lobby", "on_connect": ["lobby"]})).await;
+
+    let (_, mut conn) = awc::Client::new()
+        .ws(format!("{}/ws/lobby", BASE_URL))
+        .connect()
+        .await
+        .expect("Failed to connect");
+    assert_eq!(next_text(&mut conn).await, "lobby");
+
+    let (_, mut conn) = awc::Client::new()
+        .ws(format!("{}/ws/kitchen", BASE_URL))
+        .connect()
+        .await
+        .expect("Failed to connect");
+    assert_eq!(next_text(&mut conn).await, "any room");
+}
+
+/// A bare WebSocket upstream that sends one text message split over three frames.
+async fn start_fragmenting_upstream(fragments: [&'static str; 3]) {
+    let listener = TcpListener::bind(("127.0.0.1", RAW_UPSTREAM_PORT)).await.unwrap();
+    tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut buf = [0u8; 4096];
+        let n = socket.read(&mut buf).await.unwrap();
+        let request = String::from_utf8_lossy(&buf[..n]).to_string();
+        let key = request.lines()
+            .find_map(|line| line.strip_prefix("sec-websocket-key:").or_else(|| line.strip_prefix("Sec-WebSocket-Key:")))
+            .unwrap()
+            .trim();
+        let digest = ring::digest::digest(
+            &ring::digest::SHA1_FOR_LEGACY_USE_ONLY,
+            format!("{}258EAFA5-E914-47DA-95CA-C5AB0DC85B11", key).as_bytes(),
+        );
+        let accept = base64::engine::general_purpose::STANDARD.encode(digest.as_ref());
+        socket.write_all(format!(
+            "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+            accept,
+        ).as_bytes()).await.unwrap();
+        // Text start, continuation, final continuation.
+        for (header, fragment) in [0x01u8, 0x00, 0x80].into_iter().zip(fragments) {
+            socket.write_all(&[header, fragment.len() as u8]).await.unwrap();
+            socket.write_all(fragment.as_bytes()).await.unwrap();
+            socket.flush().await.unwrap();
+        }
+        let _ = socket.read(&mut buf).await;
+    });
+}
+
+#[actix_rt::test]
+async fn test_websocket_proxy_reassembles_fragmented_messages() {
+    let _server = TestServer::start().await;
+    start_fragmenting_upstream(["{\"part\":", " \"one and", " two\"}"]).await;
+
+    add_ws_endpoint(BASE_URL, json!({
+        "path": "/ws/feed",
+        "proxy_url": format!("http://127.0.0.1:{}", RAW_UPSTREAM_PORT)
+    })).await;
+
+    let (_, mut conn) = awc::Client::new()
+        .ws(format!("{}/ws/feed", BASE_URL))
+        .connect()
+        .await
+        .expect("Failed to connect");
+    assert_eq!(next_text(&mut conn).await, "{\"part\": \"one and two\"}");
+}
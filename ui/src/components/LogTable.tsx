@@ -28,6 +28,7 @@ import {
 } from "@/components/ui/select";
 import JsonEditor from "./JsonEditor";
 import { toast } from "sonner";
+import { adminFetch, ADMIN_PREFIX } from "@/utils/api";
 
 interface LogTableProps {
   logs: RequestLog[];
@@ -60,7 +61,7 @@ const LogTable: React.FC<LogTableProps> = ({ logs, onRefresh }) => {
     );
     if (confirmed) {
       try {
-        const response = await fetch(`${window.location.origin}/__mock/logs`, {
+        const response = await adminFetch(`${window.location.origin}${ADMIN_PREFIX}/logs`, {
           method: 'DELETE'
         });
         
@@ -316,8 +317,19 @@ const LogTable: React.FC<LogTableProps> = ({ logs, onRefresh }) => {
                   {/* Request Body */}
                   <div>
                     <h3 className="text-sm font-medium mb-2">Body</h3>
-                    {detailsLog.request_body ? (
+                    {detailsLog.request_form ? (
+                      <JsonEditor value={detailsLog.request_form} onChange={() => {}} />
+                    ) : detailsLog.request_body ? (
                       <JsonEditor value={detailsLog.request_body} onChange={() => {}} />
+                    ) : detailsLog.request_body_raw ? (
+                      <div className="bg-gray-50 p-3 rounded font-mono text-xs border whitespace-pre-wrap break-all">
+                        {detailsLog.request_body_raw.data}
+                        {detailsLog.request_body_raw.truncated && (
+                          <div className="text-gray-500 mt-2">
+                            Truncated, {detailsLog.request_body_raw.size} bytes total
+                          </div>
+                        )}
+                      </div>
                     ) : (
                       <div className="bg-gray-50 p-3 rounded text-xs text-gray-500 border">
                         No request body
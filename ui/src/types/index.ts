@@ -16,6 +16,8 @@ export interface RequestLog {
   request_headers: Record<string, string>;
   query: string;
   request_body?: any;
+  request_body_raw?: BodyCapture;
+  request_form?: FormCapture;
 
   status: number;
   response_body?: any;
@@ -26,6 +28,18 @@ export interface RequestLog {
   proxied_to?: string;
 }
 
+export interface BodyCapture {
+  encoding: 'text' | 'base64';
+  data: string;
+  size: number;
+  truncated: boolean;
+}
+
+export interface FormCapture {
+  fields: Record<string, string | string[]>;
+  files?: { field: string; filename: string; content_type?: string; size: number }[];
+}
+
 export interface EndpointResponse {
   added?: boolean;
   removed?: boolean;
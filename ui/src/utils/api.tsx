@@ -9,8 +9,59 @@ const getBaseUrl = (): string => {
   return '';
 };
 
+/** Where the server mounts the admin API and the dashboard. */
+export const ADMIN_PREFIX = window.__RUSTMOCK__?.adminPrefix ?? "/__mock";
+export const BASE_PATH = window.__RUSTMOCK__?.basePath ?? "/";
+
 const SERVER_CONFIG_KEY = "rustmock_server_config";
 const TEST_HISTORY_KEY = "rustmock_test_history";
+const ADMIN_CREDENTIALS_KEY = "rustmock_admin_credentials";
+
+const authorizationFor = (credentials: string, scheme: string): string =>
+  scheme === "Basic" ? `Basic ${btoa(credentials)}` : `Bearer ${credentials}`;
+
+/**
+ * fetch() for the admin API. Sends the stored credentials and, when the
+ * server answers 401, prompts for a token (or user:password for basic auth),
+ * remembers it and retries once.
+ */
+export const adminFetch = async (url: string, init: RequestInit = {}): Promise<Response> => {
+  const send = (authorization?: string) => {
+    const headers = new Headers(init.headers);
+    if (authorization) {
+      headers.set("Authorization", authorization);
+    }
+    return fetch(url, { ...init, headers });
+  };
+
+  const saved = localStorage.getItem(ADMIN_CREDENTIALS_KEY);
+  const response = await send(saved ?? undefined);
+  if (response.status !== 401) {
+    return response;
+  }
+
+  const challenge = response.headers.get("WWW-Authenticate") ?? "";
+  const scheme = challenge.includes("Bearer") ? "Bearer" : "Basic";
+  const credentials = window.prompt(
+    scheme === "Basic"
+      ? "The admin API requires a login. Enter user:password"
+      : "The admin API requires a token. Enter admin token"
+  );
+  if (!credentials) {
+    localStorage.removeItem(ADMIN_CREDENTIALS_KEY);
+    return response;
+  }
+
+  const authorization = authorizationFor(credentials, scheme);
+  const retried = await send(authorization);
+  if (retried.ok) {
+    localStorage.setItem(ADMIN_CREDENTIALS_KEY, authorization);
+  } else if (retried.status === 401) {
+    localStorage.removeItem(ADMIN_CREDENTIALS_KEY);
+    toast.error("Admin credentials were rejected");
+  }
+  return retried;
+};
 
 export const getServerConfig = (): ServerConfig => {
   if (typeof window !== 'undefined') {
@@ -79,7 +130,7 @@ export const saveTestHistory = (
 
 export const fetchEndpoints = async (): Promise<Endpoint[]> => {
   try {
-    const response = await fetch(`${getBaseUrl()}/__mock/config`);
+    const response = await adminFetch(`${getBaseUrl()}${ADMIN_PREFIX}/config`);
     if (!response.ok) {
       throw new Error(`Error fetching endpoints: ${response.statusText}`);
     }
@@ -93,7 +144,7 @@ export const fetchEndpoints = async (): Promise<Endpoint[]> => {
 
 export const addEndpoint = async (endpoint: Endpoint): Promise<EndpointResponse> => {
   try {
-    const response = await fetch(`${getBaseUrl()}/__mock/endpoints`, {
+    const response = await adminFetch(`${getBaseUrl()}${ADMIN_PREFIX}/endpoints`, {
       method: "POST",
       headers: {
         "Content-Type": "application/json",
@@ -121,7 +172,7 @@ export const addEndpoint = async (endpoint: Endpoint): Promise<EndpointResponse>
 
 export const removeEndpoint = async (method: string, path: string): Promise<EndpointResponse> => {
   try {
-    const response = await fetch(`${getBaseUrl()}/__mock/endpoints`, {
+    const response = await adminFetch(`${getBaseUrl()}${ADMIN_PREFIX}/endpoints`, {
       method: "DELETE",
       headers: {
         "Content-Type": "application/json",
@@ -155,7 +206,7 @@ export const updateEndpoint = async (
   endpoint: Endpoint
 ): Promise<EndpointResponse> => {
   try {
-    const response = await fetch(`${getBaseUrl()}/__mock/endpoints`, {
+    const response = await adminFetch(`${getBaseUrl()}${ADMIN_PREFIX}/endpoints`, {
       method: "PUT",
       headers: {
         "Content-Type": "application/json",
@@ -193,7 +244,7 @@ export const updateEndpoint = async (
 
 export const fetchLogs = async (): Promise<RequestLog[]> => {
   try {
-    const response = await fetch(`${getBaseUrl()}/__mock/logs`);
+    const response = await adminFetch(`${getBaseUrl()}${ADMIN_PREFIX}/logs`);
     if (!response.ok) {
       throw new Error(`Error fetching logs: ${response.statusText}`);
     }
@@ -290,7 +341,7 @@ export const exportEndpoints = (endpoints: Endpoint[]): void => {
 
 export const clearLogs = async (): Promise<void> => {
   try {
-    const response = await fetch(`${getBaseUrl()}/__mock/logs`, {
+    const response = await adminFetch(`${getBaseUrl()}${ADMIN_PREFIX}/logs`, {
       method: "DELETE",
     });
 
@@ -307,7 +358,7 @@ export const clearLogs = async (): Promise<void> => {
 
 export const importOpenAPI = async (openApiSpec: any): Promise<EndpointResponse> => {
   try {
-    const response = await fetch(`${getBaseUrl()}/__mock/import`, {
+    const response = await adminFetch(`${getBaseUrl()}${ADMIN_PREFIX}/import`, {
       method: "POST",
       headers: {
         "Content-Type": "application/json",
@@ -336,7 +387,7 @@ export const importOpenAPI = async (openApiSpec: any): Promise<EndpointResponse>
 
 export const exportOpenAPI = async (): Promise<void> => {
   try {
-    const response = await fetch(`${getBaseUrl()}/__mock/export`);
+    const response = await adminFetch(`${getBaseUrl()}${ADMIN_PREFIX}/export`);
 
     if (!response.ok) {
       throw new Error(`Error exporting OpenAPI: ${response.statusText}`);
@@ -363,7 +414,7 @@ export const exportOpenAPI = async (): Promise<void> => {
 
 export const getProxyConfig = async (): Promise<ProxyConfig | null> => {
   try {
-    const response = await fetch(`${getBaseUrl()}/__mock/proxy`);
+    const response = await adminFetch(`${getBaseUrl()}${ADMIN_PREFIX}/proxy`);
     if (!response.ok) {
       throw new Error(`Error fetching proxy config: ${response.statusText}`);
     }
@@ -377,7 +428,7 @@ export const getProxyConfig = async (): Promise<ProxyConfig | null> => {
 
 export const setProxyConfig = async (url: string): Promise<ProxyConfig | null> => {
   try {
-    const response = await fetch(`${getBaseUrl()}/__mock/proxy`, {
+    const response = await adminFetch(`${getBaseUrl()}${ADMIN_PREFIX}/proxy`, {
       method: "POST",
       headers: {
         "Content-Type": "application/json",
@@ -407,7 +458,7 @@ export const setProxyConfig = async (url: string): Promise<ProxyConfig | null> =
 
 export const deleteProxyConfig = async (): Promise<void> => {
   try {
-    const response = await fetch(`${getBaseUrl()}/__mock/proxy`, {
+    const response = await adminFetch(`${getBaseUrl()}${ADMIN_PREFIX}/proxy`, {
       method: "DELETE",
     });
 
@@ -0,0 +1,9 @@
+/// <reference types="vite/client" />
+
+/** Injected into index.html by the server. */
+interface Window {
+  __RUSTMOCK__?: {
+    adminPrefix: string;
+    basePath: string;
+  };
+}
@@ -4,6 +4,8 @@ import react from "@vitejs/plugin-react-swc";
 import path from "path";
 
 export default defineConfig(({ mode }) => ({
+  // Relative asset URLs so the dashboard can be mounted under any path.
+  base: "./",
   server: {
     host: "::",
     port: 8080,